@@ -12,7 +12,7 @@ extern crate nom;
 #[macro_use]
 extern crate rusticata_macros;
 
-use der_parser::ber::{BerObjectHeader, BerTag};
+use der_parser::ber::{BerObjectHeader, BerSize, BerTag};
 use der_parser::der::{
     der_read_element_header, parse_der, parse_der_integer, parse_der_u32, DerObject,
 };
@@ -32,7 +32,7 @@ fn bench_der_read_element_header(b: &mut Bencher) {
                         class: 0,
                         structured: 0,
                         tag: BerTag(12),
-                        len: 10
+                        len: BerSize::Definite(10)
                     }
                 );
             }
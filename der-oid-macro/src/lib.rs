@@ -0,0 +1,126 @@
+//! Procedural macro computing the encoding of object identifiers at compile time
+//!
+//! This crate is not meant to be used directly, use the `oid!` macro re-exported by
+//! [der-parser](https://crates.io/crates/der-parser).
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+
+/// Append the base-128 encoding of `n` (high bit set on all but the last octet)
+fn encode_base128(v: &mut Vec<u8>, n: u128) {
+    let start = v.len();
+    let mut n = n;
+    loop {
+        let more = if v.len() > start { 0x80 } else { 0 };
+        v.insert(start, (n & 0x7f) as u8 | more);
+        n >>= 7;
+        if n == 0 {
+            break;
+        }
+    }
+}
+
+/// Encode the content octets of an OID given in dotted form (X.690 sections 8.19 and 8.20)
+fn encode_oid(s: &str, relative: bool) -> Result<Vec<u8>, String> {
+    let arcs = s
+        .split('.')
+        .map(|a| {
+            if a.len() > 1 && a.starts_with('0') {
+                return Err(format!("invalid arc '{}' (leading zero)", a));
+            }
+            a.parse::<u128>()
+                .map_err(|_| format!("invalid arc '{}'", a))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut v = Vec::new();
+    if relative {
+        arcs.iter().for_each(|&arc| encode_base128(&mut v, arc));
+        return Ok(v);
+    }
+    if arcs.len() < 2 {
+        return Err("an OID must have at least two arcs".to_string());
+    }
+    if arcs[0] > 2 {
+        return Err("the first arc of an OID must be 0, 1 or 2".to_string());
+    }
+    if arcs[0] < 2 && arcs[1] >= 40 {
+        return Err("the second arc of an OID must be lower than 40".to_string());
+    }
+    // first subidentifier = X*40 + Y (X.690 section 8.19.4)
+    let first = arcs[1]
+        .checked_add(arcs[0] * 40)
+        .ok_or("the second arc of the OID is too large")?;
+    encode_base128(&mut v, first);
+    arcs[2..]
+        .iter()
+        .for_each(|&arc| encode_base128(&mut v, arc));
+    Ok(v)
+}
+
+/// Build an OID at compile time, see the documentation of `der-parser`
+///
+/// The input is an OID in dotted form, optionally preceded by `raw` (to get the encoded bytes
+/// as an array) and/or `rel` (for a relative OID).
+#[proc_macro]
+pub fn oid(input: TokenStream) -> TokenStream {
+    let mut s: String = input
+        .to_string()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let (mut raw, mut relative) = (false, false);
+    loop {
+        if s.starts_with("raw") {
+            raw = true;
+        } else if s.starts_with("rel") {
+            relative = true;
+        } else {
+            break;
+        }
+        s.drain(..3);
+    }
+    let bytes = match encode_oid(&s, relative) {
+        Ok(bytes) => bytes,
+        Err(e) => return format!("compile_error!({:?})", e).parse().unwrap(),
+    };
+    let array = format!(
+        "[{}]",
+        bytes
+            .iter()
+            .map(|b| format!("{:#04x}u8", b))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let out = if raw {
+        array
+    } else {
+        let constructor = if relative { "new_relative" } else { "new" };
+        format!(
+            "::der_parser::oid::Oid::{}(::std::borrow::Cow::Borrowed(&{}))",
+            constructor, array
+        )
+    };
+    out.parse().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode_oid;
+
+    #[test]
+    fn test_encode_oid() {
+        assert_eq!(
+            encode_oid("1.2.840.113549.1.1.5", false),
+            Ok(vec![0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x05])
+        );
+        assert_eq!(encode_oid("2.999.3", false), Ok(vec![0x88, 0x37, 0x03]));
+        assert_eq!(
+            encode_oid("8571.3.2", true),
+            Ok(vec![0xc2, 0x7b, 0x03, 0x02])
+        );
+        for s in &["1", "3.1", "1.40", "1.2.a", "1.02", "1..2"] {
+            assert!(encode_oid(s, false).is_err(), "{}", s);
+        }
+    }
+}
@@ -1,9 +1,13 @@
-use crate::ber::bytes_to_u64;
+use crate::ber::{ber_decode_string, ASN1Time, RealValue};
+use crate::ber::{bytes_to_i128, bytes_to_u128};
+use crate::ber::{CharacterString, EmbeddedPdv, External};
 use crate::error::BerError;
 use crate::oid::Oid;
+use std::borrow::Cow;
 use std::convert::AsRef;
 use std::convert::From;
-use std::ops::Index;
+use std::convert::TryFrom;
+use std::ops::{Index, Range};
 use std::vec::Vec;
 
 /// Defined in X.680 section 8.4
@@ -28,33 +32,113 @@ impl debug BerTag {
     EmbeddedPdv = 0xb,
     Utf8String = 0xc,
     RelativeOid = 0xd,
+    Time = 0xe,
 
     Sequence = 0x10,
     Set = 0x11,
     NumericString = 0x12,
     PrintableString = 0x13,
     T61String = 0x14,
-
+    VideotexString = 0x15,
     Ia5String = 0x16,
     UtcTime = 0x17,
     GeneralizedTime = 0x18,
-
+    GraphicString = 0x19,
+    VisibleString = 0x1a,
     GeneralString = 27, // 0x1b
-
+    UniversalString = 0x1c,
+    CharacterString = 0x1d,
     BmpString = 0x1e,
+    Date = 0x1f,
+    TimeOfDay = 0x20,
+    DateTime = 0x21,
+    Duration = 0x22,
+    OidIri = 0x23,
+    RelativeOidIri = 0x24,
 
     Invalid = 0xff,
 }
 }
 
+/// Name of a universal tag, as used in ASN.1
+pub(crate) fn universal_tag_name(tag: BerTag) -> Option<&'static str> {
+    let s = match tag {
+        BerTag::EndOfContent => "END-OF-CONTENT",
+        BerTag::Boolean => "BOOLEAN",
+        BerTag::Integer => "INTEGER",
+        BerTag::BitString => "BIT STRING",
+        BerTag::OctetString => "OCTET STRING",
+        BerTag::Null => "NULL",
+        BerTag::Oid => "OBJECT IDENTIFIER",
+        BerTag::ObjDescriptor => "ObjectDescriptor",
+        BerTag::External => "EXTERNAL",
+        BerTag::RealType => "REAL",
+        BerTag::Enumerated => "ENUMERATED",
+        BerTag::EmbeddedPdv => "EMBEDDED PDV",
+        BerTag::Utf8String => "UTF8String",
+        BerTag::RelativeOid => "RELATIVE-OID",
+        BerTag::Time => "TIME",
+        BerTag::Sequence => "SEQUENCE",
+        BerTag::Set => "SET",
+        BerTag::NumericString => "NumericString",
+        BerTag::PrintableString => "PrintableString",
+        BerTag::T61String => "T61String",
+        BerTag::VideotexString => "VideotexString",
+        BerTag::Ia5String => "IA5String",
+        BerTag::UtcTime => "UTCTime",
+        BerTag::GeneralizedTime => "GeneralizedTime",
+        BerTag::GraphicString => "GraphicString",
+        BerTag::VisibleString => "VisibleString",
+        BerTag::GeneralString => "GeneralString",
+        BerTag::UniversalString => "UniversalString",
+        BerTag::CharacterString => "CHARACTER STRING",
+        BerTag::BmpString => "BMPString",
+        BerTag::Date => "DATE",
+        BerTag::TimeOfDay => "TIME-OF-DAY",
+        BerTag::DateTime => "DATE-TIME",
+        BerTag::Duration => "DURATION",
+        BerTag::OidIri => "OID-IRI",
+        BerTag::RelativeOidIri => "RELATIVE-OID-IRI",
+        _ => return None,
+    };
+    Some(s)
+}
+
 /// Representation of a DER-encoded (X.690) object
-#[derive(Debug, Clone, PartialEq)]
+///
+/// The `location` of the object is not used when comparing objects.
+#[derive(Debug, Clone)]
 pub struct BerObject<'a> {
     pub class: u8,
     pub structured: u8,
     pub tag: BerTag,
 
     pub content: BerObjectContent<'a>,
+
+    /// Location of the object in the input, if the object was parsed
+    pub location: Option<BerSpan<'a>>,
+}
+
+/// Compare two objects, ignoring their location
+impl<'a> PartialEq for BerObject<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.class == other.class
+            && self.structured == other.structured
+            && self.tag == other.tag
+            && self.content == other.content
+    }
+}
+
+/// Location and raw encoding of a parsed object
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BerSpan<'a> {
+    /// Encoding of the object (header and content, including the end-of-content marker if the
+    /// length uses the indefinite form)
+    pub raw: &'a [u8],
+    /// Length of the header (identifier and length octets)
+    pub header_len: usize,
+    /// Offset of the object, relative to the input of the top-level parsing function
+    pub offset: usize,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -62,7 +146,16 @@ pub struct BerObjectHeader {
     pub class: u8,
     pub structured: u8,
     pub tag: BerTag,
-    pub len: u64,
+    pub len: BerSize,
+}
+
+/// Length of an object, as read from its header (X.690 section 8.1.3)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BerSize {
+    /// Definite form: the content has the given number of octets
+    Definite(usize),
+    /// Indefinite form: the content is terminated by an end-of-content marker (BER only)
+    Indefinite,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -73,29 +166,92 @@ pub enum BerObjectContent<'a> {
     BitString(u8, BitStringObject<'a>),
     OctetString(&'a [u8]),
     Null,
-    Enum(u64),
-    OID(Oid),
-    RelativeOID(Oid),
+    Enum(i64),
+    Real(RealValue<'a>),
+    OID(Oid<'a>),
+    RelativeOID(Oid<'a>),
+    /// OID-IRI (validated, see the [`oid_iri`](../oid_iri/index.html) module)
+    OidIri(&'a str),
+    /// RELATIVE-OID-IRI (validated)
+    RelativeOidIri(&'a str),
     NumericString(&'a [u8]),
     PrintableString(&'a [u8]),
     IA5String(&'a [u8]),
     UTF8String(&'a [u8]),
     T61String(&'a [u8]),
+    VideotexString(&'a [u8]),
+    GraphicString(&'a [u8]),
+    VisibleString(&'a [u8]),
+    ObjDescriptor(&'a [u8]),
 
+    UniversalString(&'a [u8]),
     BmpString(&'a [u8]),
 
     Sequence(Vec<BerObject<'a>>),
     Set(Vec<BerObject<'a>>),
 
+    /// EXTERNAL, with the components of the associated sequence type
+    External(Vec<BerObject<'a>>),
+    /// EMBEDDED PDV, with the components of the associated sequence type
+    EmbeddedPdv(Vec<BerObject<'a>>),
+    /// CHARACTER STRING, with the components of the associated sequence type
+    CharacterString(Vec<BerObject<'a>>),
+
     UTCTime(&'a [u8]),
     GeneralizedTime(&'a [u8]),
 
+    /// TIME, in ISO 8601 format
+    Time(&'a [u8]),
+    /// DATE (`YYYY-MM-DD`)
+    Date(&'a [u8]),
+    /// TIME-OF-DAY (`hh:mm:ss`)
+    TimeOfDay(&'a [u8]),
+    /// DATE-TIME (`YYYY-MM-DDThh:mm:ss`)
+    DateTime(&'a [u8]),
+    /// DURATION, in ISO 8601 format (for ex. `P1Y2M10DT2H30M`)
+    Duration(&'a [u8]),
+
     GeneralString(&'a [u8]),
 
+    /// String type (or bitstring) encoded using the constructed form (BER only)
+    Segmented(BerTag, SegmentedString<'a>),
+
     ContextSpecific(BerTag, Option<Box<BerObject<'a>>>),
     Unknown(BerTag, &'a [u8]),
 }
 
+impl BerSize {
+    /// Return true if length is definite and equal to 0
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        *self == BerSize::Definite(0)
+    }
+
+    /// Return true if length uses the indefinite form
+    #[inline]
+    pub fn is_indefinite(&self) -> bool {
+        *self == BerSize::Indefinite
+    }
+
+    /// Get the length of a primitive object
+    ///
+    /// Returns an error if the length uses the indefinite form, which is only valid for
+    /// constructed objects.
+    #[inline]
+    pub fn primitive(&self) -> Result<usize, BerError> {
+        match *self {
+            BerSize::Definite(sz) => Ok(sz),
+            BerSize::Indefinite => Err(BerError::IndefiniteLengthUnexpected),
+        }
+    }
+}
+
+impl From<usize> for BerSize {
+    fn from(sz: usize) -> Self {
+        BerSize::Definite(sz)
+    }
+}
+
 impl BerObjectHeader {
     /// Test if object class is Universal
     #[inline]
@@ -140,15 +296,20 @@ impl<'a> BerObject<'a> {
             structured: hdr.structured,
             tag: hdr.tag,
             content: c,
+            location: None,
         }
     }
     /// Build a BerObject from its content, using default flags (no class, correct tag,
-    /// and structured flag set only for Set and Sequence)
+    /// and structured flag set only for Set, Sequence and the types encoded as sequences)
     pub fn from_obj(c: BerObjectContent) -> BerObject {
         let class = 0;
         let tag = c.tag();
         let structured = match tag {
-            BerTag::Sequence | BerTag::Set => 1,
+            BerTag::Sequence
+            | BerTag::Set
+            | BerTag::External
+            | BerTag::EmbeddedPdv
+            | BerTag::CharacterString => 1,
             _ => 0,
         };
         BerObject {
@@ -156,6 +317,7 @@ impl<'a> BerObject<'a> {
             structured,
             tag,
             content: c,
+            location: None,
         }
     }
 
@@ -166,6 +328,7 @@ impl<'a> BerObject<'a> {
             structured: 0,
             tag: BerTag::Integer,
             content: BerObjectContent::Integer(i),
+            location: None,
         }
     }
 
@@ -179,26 +342,126 @@ impl<'a> BerObject<'a> {
         BerObject::from_obj(BerObjectContent::Set(l))
     }
 
-    /// Attempt to read integer value from DER object.
-    /// This can fail if the object is not an integer, or if it is too large.
+    /// Set the raw encoding of a parsed object (the offset is set by `set_offsets`)
+    pub(crate) fn with_raw(mut self, raw: &'a [u8], header_len: usize) -> Self {
+        self.location = Some(BerSpan {
+            raw,
+            header_len,
+            offset: 0,
+        });
+        self
+    }
+
+    /// Compute the offsets of this object and all the objects it contains, relative to `input`
+    pub(crate) fn set_offsets(&mut self, input: &[u8]) {
+        let start = input.as_ptr() as usize;
+        if let Some(ref mut loc) = self.location {
+            let p = loc.raw.as_ptr() as usize;
+            if p >= start && p + loc.raw.len() <= start + input.len() {
+                loc.offset = p - start;
+            }
+        }
+        match self.content {
+            BerObjectContent::Sequence(ref mut l)
+            | BerObjectContent::Set(ref mut l)
+            | BerObjectContent::External(ref mut l)
+            | BerObjectContent::EmbeddedPdv(ref mut l)
+            | BerObjectContent::CharacterString(ref mut l) => {
+                for o in l.iter_mut() {
+                    o.set_offsets(input);
+                }
+            }
+            BerObjectContent::Segmented(_, ref mut s) => {
+                for o in s.segments.iter_mut() {
+                    o.set_offsets(input);
+                }
+            }
+            BerObjectContent::ContextSpecific(_, Some(ref mut o)) => o.set_offsets(input),
+            _ => (),
+        }
+    }
+
+    /// Get the raw encoding of the object (header and content).
+    /// Returns `None` if the object was not parsed.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::der::parse_der;
+    /// # fn main() {
+    /// let bytes = [0x30, 0x05, 0x02, 0x03, 0x01, 0x00, 0x01, 0xff];
+    /// let (_, obj) = parse_der(&bytes).expect("parsing failed");
+    /// assert_eq!(obj.raw(), Some(&bytes[..7]));
+    /// assert_eq!(obj.header_len(), Some(2));
+    /// assert_eq!(obj[0].span(), Some(2..7));
+    /// # }
+    /// ```
+    pub fn raw(&self) -> Option<&'a [u8]> {
+        self.location.map(|loc| loc.raw)
+    }
+
+    /// Get the raw content of the object (without the header, but including the end-of-content
+    /// marker if the length uses the indefinite form).
+    /// Returns `None` if the object was not parsed.
+    pub fn raw_content(&self) -> Option<&'a [u8]> {
+        self.location.map(|loc| &loc.raw[loc.header_len..])
+    }
+
+    /// Get the length of the header (identifier and length octets) of the object.
+    /// Returns `None` if the object was not parsed.
+    pub fn header_len(&self) -> Option<usize> {
+        self.location.map(|loc| loc.header_len)
+    }
+
+    /// Get the offset of the object, relative to the input of the top-level parsing function.
+    /// Returns `None` if the object was not parsed.
+    pub fn offset(&self) -> Option<usize> {
+        self.location.map(|loc| loc.offset)
+    }
+
+    /// Get the range of bytes of the object, relative to the input of the top-level parsing
+    /// function.
+    /// Returns `None` if the object was not parsed.
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.location
+            .map(|loc| loc.offset..loc.offset + loc.raw.len())
+    }
+
+    /// Attempt to read an unsigned integer value from DER object.
+    /// This can fail if the object is not an integer (or enumerated), if it is too large, or if
+    /// it is negative.
+    ///
+    /// Integers are encoded in two's complement form, so positive values with the high bit set
+    /// are prefixed by a `0x00` octet, which is ignored.
+    pub fn as_u128(&self) -> Result<u128, BerError> {
+        self.content.as_u128()
+    }
+
+    /// Attempt to read an unsigned integer value from DER object.
+    /// This can fail if the object is not an integer, if it is too large, or if it is negative.
     ///
     /// ```rust
     /// # extern crate der_parser;
     /// # use der_parser::ber::{BerObject,BerObjectContent};
+    /// # use der_parser::error::BerError;
     /// # fn main() {
     /// let der_int  = BerObject::from_int_slice(b"\x01\x00\x01");
     /// assert_eq!(
     ///     der_int.as_u64(),
     ///     Ok(0x10001)
     /// );
+    /// let der_int  = BerObject::from_int_slice(b"\xff");
+    /// assert_eq!(
+    ///     der_int.as_u64(),
+    ///     Err(BerError::IntegerNegative)
+    /// );
     /// # }
     /// ```
     pub fn as_u64(&self) -> Result<u64, BerError> {
         self.content.as_u64()
     }
 
-    /// Attempt to read integer value from DER object.
-    /// This can fail if the object is not an integer, or if it is too large.
+    /// Attempt to read an unsigned integer value from DER object.
+    /// This can fail if the object is not an integer, if it is too large, or if it is negative.
     ///
     /// ```rust
     /// # extern crate der_parser;
@@ -215,24 +478,128 @@ impl<'a> BerObject<'a> {
         self.content.as_u32()
     }
 
+    /// Attempt to read an unsigned integer value from DER object.
+    /// This can fail if the object is not an integer, if it is too large, or if it is negative.
+    pub fn as_u16(&self) -> Result<u16, BerError> {
+        self.content.as_u16()
+    }
+
+    /// Attempt to read an unsigned integer value from DER object.
+    /// This can fail if the object is not an integer, if it is too large, or if it is negative.
+    pub fn as_u8(&self) -> Result<u8, BerError> {
+        self.content.as_u8()
+    }
+
+    /// Attempt to read a signed integer value from DER object.
+    /// This can fail if the object is not an integer (or enumerated), or if it is too large.
+    pub fn as_i128(&self) -> Result<i128, BerError> {
+        self.content.as_i128()
+    }
+
+    /// Attempt to read a signed integer value from DER object.
+    /// This can fail if the object is not an integer, or if it is too large.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::{BerObject,BerObjectContent};
+    /// # fn main() {
+    /// let der_int  = BerObject::from_int_slice(b"\xff");
+    /// assert_eq!(
+    ///     der_int.as_i64(),
+    ///     Ok(-1)
+    /// );
+    /// # }
+    /// ```
+    pub fn as_i64(&self) -> Result<i64, BerError> {
+        self.content.as_i64()
+    }
+
+    /// Attempt to read a signed integer value from DER object.
+    /// This can fail if the object is not an integer, or if it is too large.
+    pub fn as_i32(&self) -> Result<i32, BerError> {
+        self.content.as_i32()
+    }
+
+    /// Attempt to read a signed integer value from DER object.
+    /// This can fail if the object is not an integer, or if it is too large.
+    pub fn as_i16(&self) -> Result<i16, BerError> {
+        self.content.as_i16()
+    }
+
+    /// Attempt to read a signed integer value from DER object.
+    /// This can fail if the object is not an integer, or if it is too large.
+    pub fn as_i8(&self) -> Result<i8, BerError> {
+        self.content.as_i8()
+    }
+
     /// Attempt to read integer value from DER object.
     /// This can fail if the object is not a boolean.
     pub fn as_bool(&self) -> Result<bool, BerError> {
         self.content.as_bool()
     }
 
+    /// Attempt to read a REAL value from DER object.
+    /// This can fail if the object is not a REAL.
+    ///
+    /// The conversion to `f64` may be inexact. To get the exact representation of the value, use
+    /// [`as_real`](struct.BerObject.html#method.as_real)
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::parse_ber;
+    /// # fn main() {
+    /// // 0.15625 = 5 * 2^-5
+    /// let (_, obj) = parse_ber(&[0x09, 0x03, 0x80, 0xfb, 0x05]).expect("parsing failed");
+    /// assert_eq!(obj.as_f64(), Ok(0.15625));
+    /// # }
+    /// ```
+    pub fn as_f64(&self) -> Result<f64, BerError> {
+        self.content.as_f64()
+    }
+
+    /// Attempt to read a REAL value from DER object, as represented in the encoding.
+    /// This can fail if the object is not a REAL.
+    pub fn as_real(&self) -> Result<&RealValue<'a>, BerError> {
+        self.content.as_real()
+    }
+
+    /// Attempt to read a time value (UTCTime, GeneralizedTime, DATE or DATE-TIME) from DER
+    /// object.
+    /// This can fail if the object is not a time, or if the value is invalid.
+    ///
+    /// The other time types (TIME, TIME-OF-DAY and DURATION) can be read as strings, using
+    /// [`as_str`](struct.BerObject.html#method.as_str).
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::parse_ber;
+    /// # fn main() {
+    /// let bytes = b"\x17\x0d021213142923Z";
+    /// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    /// let t = obj.as_time().expect("invalid time");
+    /// assert_eq!((t.year, t.month, t.day), (2002, 12, 13));
+    /// # }
+    /// ```
+    pub fn as_time(&self) -> Result<ASN1Time, BerError> {
+        self.content.as_time()
+    }
+
     /// Attempt to read an OID value from DER object.
     /// This can fail if the object is not an OID.
     ///
     /// Note that this function returns a reference to the OID. To get an owned value,
     /// use [`as_oid_val`](struct.BerObject.html#method.as_oid_val)
-    pub fn as_oid(&self) -> Result<&Oid, BerError> {
+    pub fn as_oid(&self) -> Result<&Oid<'a>, BerError> {
         self.content.as_oid()
     }
 
     /// Attempt to read an OID value from DER object.
     /// This can fail if the object is not an OID.
-    pub fn as_oid_val(&self) -> Result<Oid, BerError> {
+    ///
+    /// The returned OID still borrows the input. Use
+    /// [`Oid::to_owned`](../oid/struct.Oid.html#method.to_owned) to get an OID with a `'static`
+    /// lifetime.
+    pub fn as_oid_val(&self) -> Result<Oid<'a>, BerError> {
         self.content.as_oid_val()
     }
 
@@ -261,6 +628,9 @@ impl<'a> BerObject<'a> {
 
     /// Attempt to extract the list of objects from a DER sequence.
     /// This can fail if the object is not a sequence.
+    ///
+    /// EXTERNAL, EMBEDDED PDV and CHARACTER STRING objects are encoded as sequences, and their
+    /// components are also returned.
     pub fn as_sequence(&self) -> Result<&Vec<BerObject<'a>>, BerError> {
         self.content.as_sequence()
     }
@@ -271,6 +641,41 @@ impl<'a> BerObject<'a> {
         self.content.as_set()
     }
 
+    /// Attempt to decode an EXTERNAL value from DER object.
+    /// This can fail if the object is not an EXTERNAL, or if its components are invalid.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::{parse_ber, ExternalData, Identification};
+    /// # use der_parser::oid::Oid;
+    /// # fn main() {
+    /// // direct-reference 1.2.3, and single-ASN1-type INTEGER 5
+    /// let bytes = [0x28, 0x09, 0x06, 0x02, 0x2a, 0x03, 0xa0, 0x03, 0x02, 0x01, 0x05];
+    /// let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+    /// let ext = obj.as_external().expect("invalid EXTERNAL");
+    /// assert_eq!(ext.identification, Identification::Syntax("1.2.3".parse::<Oid>().unwrap()));
+    /// match ext.data_value {
+    ///     ExternalData::SingleAsn1Type(ref o) => assert_eq!(o.as_u32(), Ok(5)),
+    ///     _ => panic!("unexpected encoding"),
+    /// }
+    /// # }
+    /// ```
+    pub fn as_external(&self) -> Result<External<'a>, BerError> {
+        self.content.as_external()
+    }
+
+    /// Attempt to decode an EMBEDDED PDV value from DER object.
+    /// This can fail if the object is not an EMBEDDED PDV, or if its components are invalid.
+    pub fn as_embedded_pdv(&self) -> Result<EmbeddedPdv<'a>, BerError> {
+        self.content.as_embedded_pdv()
+    }
+
+    /// Attempt to decode a CHARACTER STRING value from DER object.
+    /// This can fail if the object is not a CHARACTER STRING, or if its components are invalid.
+    pub fn as_character_string(&self) -> Result<CharacterString<'a>, BerError> {
+        self.content.as_character_string()
+    }
+
     /// Attempt to get the content from a DER object, as a slice.
     /// This can fail if the object does not contain a type directly equivalent to a slice (e.g a
     /// sequence).
@@ -279,6 +684,62 @@ impl<'a> BerObject<'a> {
         self.content.as_slice()
     }
 
+    /// Attempt to get the content bytes from a DER object.
+    /// This can fail if the object does not contain a string type (or integer, or unknown
+    /// object).
+    ///
+    /// Unlike [`as_slice`](struct.BerObject.html#method.as_slice), this function also accepts
+    /// strings encoded using the constructed form, and returns the concatenated segments.
+    /// For bitstrings, the unused bits count is not included.
+    pub fn as_bytes(&self) -> Result<&[u8], BerError> {
+        self.content.as_bytes()
+    }
+
+    /// Attempt to get the segments of a string encoded using the constructed form.
+    /// This can fail if the object is not a constructed string.
+    pub fn as_segments(&self) -> Result<&[BerObject<'a>], BerError> {
+        self.content.as_segments()
+    }
+
+    /// Attempt to get the value of a character string object, as a string slice.
+    /// This can fail if the object is not a NumericString, PrintableString, IA5String,
+    /// VisibleString, UTF8String, GeneralString, GraphicString, VideotexString,
+    /// ObjectDescriptor or time type (TIME, DATE, TIME-OF-DAY, DATE-TIME, DURATION), or if the
+    /// content is not valid for the string type.
+    ///
+    /// For other string types, which require a conversion to UTF-8, use
+    /// [`as_string`](struct.BerObject.html#method.as_string).
+    pub fn as_str(&self) -> Result<&str, BerError> {
+        self.content.as_str()
+    }
+
+    /// Attempt to decode the value of a character string object, as a `String`.
+    /// This can fail if the object is not a character string, or if the content is not valid for
+    /// the string type.
+    ///
+    /// The characters allowed for each type are verified (for ex. the alphabet of a
+    /// PrintableString). BmpString and UniversalString are decoded as UCS-2 and UCS-4 (big-endian)
+    /// respectively, and T61String is transcoded from T.61 to Unicode.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::parse_ber;
+    /// # fn main() {
+    /// let (_, obj) = parse_ber(b"\x1e\x04\x00\xe9\x20\xac").expect("parsing failed");
+    /// assert_eq!(obj.as_string(), Ok("é€".to_string()));
+    /// # }
+    /// ```
+    pub fn as_string(&self) -> Result<String, BerError> {
+        self.content.as_string()
+    }
+
+    /// Attempt to decode the value of a character string object, as a `String`, replacing
+    /// invalid characters by `U+FFFD REPLACEMENT CHARACTER`.
+    /// This can fail if the object is not a character string.
+    pub fn to_string_lossy(&self) -> Result<String, BerError> {
+        self.content.to_string_lossy()
+    }
+
     /// Test if object class is Universal
     pub fn is_universal(&self) -> bool {
         self.class == 0
@@ -307,8 +768,8 @@ impl<'a> BerObject<'a> {
 }
 
 /// Build a DER object from an OID.
-impl<'a> From<Oid> for BerObject<'a> {
-    fn from(oid: Oid) -> BerObject<'a> {
+impl<'a> From<Oid<'a>> for BerObject<'a> {
+    fn from(oid: Oid<'a>) -> BerObject<'a> {
         BerObject::from_obj(BerObjectContent::OID(oid))
     }
 }
@@ -320,35 +781,43 @@ impl<'a> From<BerObjectContent<'a>> for BerObject<'a> {
     }
 }
 
+/// Implement a conversion to a smaller integer type, using the conversion to `i128` or `u128`
+macro_rules! impl_int_conversion {
+    ($method:ident, $base:ident, $int:ty) => {
+        pub fn $method(&self) -> Result<$int, BerError> {
+            self.$base()
+                .and_then(|x| <$int>::try_from(x).or(Err(BerError::IntegerTooLarge)))
+        }
+    };
+}
+
 impl<'a> BerObjectContent<'a> {
-    pub fn as_u64(&self) -> Result<u64, BerError> {
+    pub fn as_i128(&self) -> Result<i128, BerError> {
         match *self {
-            BerObjectContent::Integer(i) => bytes_to_u64(i),
-            BerObjectContent::Enum(i) => Ok(i as u64),
+            BerObjectContent::Integer(i) => bytes_to_i128(i),
+            BerObjectContent::Enum(i) => Ok(i as i128),
             _ => Err(BerError::BerTypeError),
         }
     }
 
-    pub fn as_u32(&self) -> Result<u32, BerError> {
+    pub fn as_u128(&self) -> Result<u128, BerError> {
         match *self {
-            BerObjectContent::Integer(i) => bytes_to_u64(i).and_then(|x| {
-                if x > std::u32::MAX as u64 {
-                    Err(BerError::IntegerTooLarge)
-                } else {
-                    Ok(x as u32)
-                }
-            }),
-            BerObjectContent::Enum(i) => {
-                if i > std::u32::MAX as u64 {
-                    Err(BerError::IntegerTooLarge)
-                } else {
-                    Ok(i as u32)
-                }
-            }
+            BerObjectContent::Integer(i) => bytes_to_u128(i),
+            BerObjectContent::Enum(i) if i < 0 => Err(BerError::IntegerNegative),
+            BerObjectContent::Enum(i) => Ok(i as u128),
             _ => Err(BerError::BerTypeError),
         }
     }
 
+    impl_int_conversion!(as_i64, as_i128, i64);
+    impl_int_conversion!(as_i32, as_i128, i32);
+    impl_int_conversion!(as_i16, as_i128, i16);
+    impl_int_conversion!(as_i8, as_i128, i8);
+    impl_int_conversion!(as_u64, as_u128, u64);
+    impl_int_conversion!(as_u32, as_u128, u32);
+    impl_int_conversion!(as_u16, as_u128, u16);
+    impl_int_conversion!(as_u8, as_u128, u8);
+
     pub fn as_bool(&self) -> Result<bool, BerError> {
         match *self {
             BerObjectContent::Boolean(b) => Ok(b),
@@ -356,7 +825,28 @@ impl<'a> BerObjectContent<'a> {
         }
     }
 
-    pub fn as_oid(&self) -> Result<&Oid, BerError> {
+    pub fn as_f64(&self) -> Result<f64, BerError> {
+        self.as_real().and_then(|r| r.as_f64())
+    }
+
+    pub fn as_real(&self) -> Result<&RealValue<'a>, BerError> {
+        match *self {
+            BerObjectContent::Real(ref r) => Ok(r),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_time(&self) -> Result<ASN1Time, BerError> {
+        match *self {
+            BerObjectContent::UTCTime(s) => ASN1Time::from_utctime(s),
+            BerObjectContent::GeneralizedTime(s) => ASN1Time::from_generalizedtime(s),
+            BerObjectContent::Date(s) => ASN1Time::from_date(s),
+            BerObjectContent::DateTime(s) => ASN1Time::from_date_time(s),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_oid(&self) -> Result<&Oid<'a>, BerError> {
         match *self {
             BerObjectContent::OID(ref o) => Ok(o),
             BerObjectContent::RelativeOID(ref o) => Ok(o),
@@ -364,10 +854,10 @@ impl<'a> BerObjectContent<'a> {
         }
     }
 
-    pub fn as_oid_val(&self) -> Result<Oid, BerError> {
+    pub fn as_oid_val(&self) -> Result<Oid<'a>, BerError> {
         match *self {
-            BerObjectContent::OID(ref o) => Ok(o.to_owned()),
-            BerObjectContent::RelativeOID(ref o) => Ok(o.to_owned()),
+            BerObjectContent::OID(ref o) => Ok(o.clone()),
+            BerObjectContent::RelativeOID(ref o) => Ok(o.clone()),
             _ => Err(BerError::BerTypeError),
         }
     }
@@ -395,7 +885,10 @@ impl<'a> BerObjectContent<'a> {
 
     pub fn as_sequence(&self) -> Result<&Vec<BerObject<'a>>, BerError> {
         match *self {
-            BerObjectContent::Sequence(ref s) => Ok(s),
+            BerObjectContent::Sequence(ref s)
+            | BerObjectContent::External(ref s)
+            | BerObjectContent::EmbeddedPdv(ref s)
+            | BerObjectContent::CharacterString(ref s) => Ok(s),
             _ => Err(BerError::BerTypeError),
         }
     }
@@ -407,6 +900,27 @@ impl<'a> BerObjectContent<'a> {
         }
     }
 
+    pub fn as_external(&self) -> Result<External<'a>, BerError> {
+        match *self {
+            BerObjectContent::External(ref l) => External::from_components(l),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_embedded_pdv(&self) -> Result<EmbeddedPdv<'a>, BerError> {
+        match *self {
+            BerObjectContent::EmbeddedPdv(ref l) => EmbeddedPdv::from_components(l),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_character_string(&self) -> Result<CharacterString<'a>, BerError> {
+        match *self {
+            BerObjectContent::CharacterString(ref l) => CharacterString::from_components(l),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
     #[rustfmt::skip]
     pub fn as_slice(&self) -> Result<&'a [u8],BerError> {
         match *self {
@@ -418,13 +932,74 @@ impl<'a> BerObjectContent<'a> {
             BerObjectContent::IA5String(s) |
             BerObjectContent::UTF8String(s) |
             BerObjectContent::T61String(s) |
+            BerObjectContent::VideotexString(s) |
+            BerObjectContent::GraphicString(s) |
+            BerObjectContent::VisibleString(s) |
+            BerObjectContent::ObjDescriptor(s) |
+            BerObjectContent::UniversalString(s) |
             BerObjectContent::BmpString(s) |
+            BerObjectContent::Time(s) |
+            BerObjectContent::Date(s) |
+            BerObjectContent::TimeOfDay(s) |
+            BerObjectContent::DateTime(s) |
+            BerObjectContent::Duration(s) |
             BerObjectContent::GeneralString(s) |
             BerObjectContent::Unknown(_,s) => Ok(s),
+            BerObjectContent::OidIri(s) |
+            BerObjectContent::RelativeOidIri(s) => Ok(s.as_bytes()),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_bytes(&self) -> Result<&[u8], BerError> {
+        match *self {
+            BerObjectContent::Segmented(_, ref s) => Ok(&s.data),
+            _ => self.as_slice(),
+        }
+    }
+
+    pub fn as_segments(&self) -> Result<&[BerObject<'a>], BerError> {
+        match *self {
+            BerObjectContent::Segmented(_, ref s) => Ok(&s.segments),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    pub fn as_str(&self) -> Result<&str, BerError> {
+        match self.tag() {
+            BerTag::NumericString
+            | BerTag::PrintableString
+            | BerTag::Ia5String
+            | BerTag::VisibleString
+            | BerTag::Utf8String
+            | BerTag::GeneralString
+            | BerTag::GraphicString
+            | BerTag::VideotexString
+            | BerTag::ObjDescriptor
+            | BerTag::Time
+            | BerTag::Date
+            | BerTag::TimeOfDay
+            | BerTag::DateTime
+            | BerTag::Duration
+            | BerTag::OidIri
+            | BerTag::RelativeOidIri => {
+                match ber_decode_string(self.tag(), self.as_bytes()?, false)? {
+                    Cow::Borrowed(s) => Ok(s),
+                    Cow::Owned(_) => Err(BerError::StringInvalidCharset),
+                }
+            }
             _ => Err(BerError::BerTypeError),
         }
     }
 
+    pub fn as_string(&self) -> Result<String, BerError> {
+        ber_decode_string(self.tag(), self.as_bytes()?, false).map(Cow::into_owned)
+    }
+
+    pub fn to_string_lossy(&self) -> Result<String, BerError> {
+        ber_decode_string(self.tag(), self.as_bytes()?, true).map(Cow::into_owned)
+    }
+
     #[rustfmt::skip]
     pub fn tag(&self) -> BerTag {
         match *self {
@@ -435,19 +1010,36 @@ impl<'a> BerObjectContent<'a> {
             BerObjectContent::OctetString(_)       => BerTag::OctetString,
             BerObjectContent::Null                 => BerTag::Null,
             BerObjectContent::Enum(_)              => BerTag::Enumerated,
+            BerObjectContent::Real(_)              => BerTag::RealType,
             BerObjectContent::OID(_)               => BerTag::Oid,
             BerObjectContent::NumericString(_)     => BerTag::NumericString,
             BerObjectContent::PrintableString(_)   => BerTag::PrintableString,
             BerObjectContent::IA5String(_)         => BerTag::Ia5String,
             BerObjectContent::UTF8String(_)        => BerTag::Utf8String,
             BerObjectContent::RelativeOID(_)       => BerTag::RelativeOid,
+            BerObjectContent::OidIri(_)            => BerTag::OidIri,
+            BerObjectContent::RelativeOidIri(_)    => BerTag::RelativeOidIri,
             BerObjectContent::T61String(_)         => BerTag::T61String,
+            BerObjectContent::VideotexString(_)    => BerTag::VideotexString,
+            BerObjectContent::GraphicString(_)     => BerTag::GraphicString,
+            BerObjectContent::VisibleString(_)     => BerTag::VisibleString,
+            BerObjectContent::ObjDescriptor(_)     => BerTag::ObjDescriptor,
+            BerObjectContent::UniversalString(_)   => BerTag::UniversalString,
             BerObjectContent::BmpString(_)         => BerTag::BmpString,
             BerObjectContent::Sequence(_)          => BerTag::Sequence,
             BerObjectContent::Set(_)               => BerTag::Set,
+            BerObjectContent::External(_)          => BerTag::External,
+            BerObjectContent::EmbeddedPdv(_)       => BerTag::EmbeddedPdv,
+            BerObjectContent::CharacterString(_)   => BerTag::CharacterString,
             BerObjectContent::UTCTime(_)           => BerTag::UtcTime,
             BerObjectContent::GeneralizedTime(_)   => BerTag::GeneralizedTime,
+            BerObjectContent::Time(_)              => BerTag::Time,
+            BerObjectContent::Date(_)              => BerTag::Date,
+            BerObjectContent::TimeOfDay(_)         => BerTag::TimeOfDay,
+            BerObjectContent::DateTime(_)          => BerTag::DateTime,
+            BerObjectContent::Duration(_)          => BerTag::Duration,
             BerObjectContent::GeneralString(_)     => BerTag::GeneralString,
+            BerObjectContent::Segmented(x,_)       |
             BerObjectContent::ContextSpecific(x,_) |
             BerObjectContent::Unknown(x,_)         => x,
         }
@@ -457,19 +1049,26 @@ impl<'a> BerObjectContent<'a> {
 #[cfg(feature = "bigint")]
 mod bigint {
     use super::{BerObject, BerObjectContent};
-    use num_bigint::{BigInt, BigUint, Sign};
+    use num_bigint::{BigInt, BigUint};
 
     impl<'a> BerObject<'a> {
+        /// Attempt to read an integer value from DER object, as a signed big integer.
+        /// Returns `None` if the object is not an integer.
         pub fn as_bigint(&self) -> Option<BigInt> {
             match self.content {
-                BerObjectContent::Integer(s) => Some(BigInt::from_bytes_be(Sign::Plus, s)),
+                BerObjectContent::Integer(s) => Some(BigInt::from_signed_bytes_be(s)),
                 _ => None,
             }
         }
 
+        /// Attempt to read an integer value from DER object, as an unsigned big integer.
+        /// Returns `None` if the object is not an integer, or if the value is negative.
         pub fn as_biguint(&self) -> Option<BigUint> {
             match self.content {
-                BerObjectContent::Integer(s) => Some(BigUint::from_bytes_be(s)),
+                BerObjectContent::Integer(s) => match s.first() {
+                    Some(&b) if b & 0x80 != 0 => None,
+                    _ => Some(BigUint::from_bytes_be(s)),
+                },
                 _ => None,
             }
         }
@@ -592,15 +1191,60 @@ impl<'a> AsRef<[u8]> for BitStringObject<'a> {
     }
 }
 
+/// Content of a string encoded using the constructed form
+///
+/// In BER, string types (and bitstrings) can be split into segments (X.690 section 8.23.6).
+/// Segments are primitive or constructed BIT STRING objects for bitstrings, and OCTET STRING
+/// objects for other types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SegmentedString<'a> {
+    /// Segments of the string, as found in the encoding
+    pub segments: Vec<BerObject<'a>>,
+    /// Concatenated content of all segments (excluding the unused bits octets for bitstrings)
+    pub data: Vec<u8>,
+    /// Number of unused bits in the last octet (bitstrings only)
+    pub unused_bits: u8,
+}
+
 #[cfg(test)]
 mod tests {
     use crate::ber::*;
+    use crate::error::BerError;
     use crate::oid::*;
 
     #[test]
     fn test_der_as_u64() {
         let der_obj = BerObject::from_int_slice(b"\x01\x00\x02");
         assert_eq!(der_obj.as_u64(), Ok(0x10002));
+        // leading zero, required because the high bit is set
+        let der_obj = BerObject::from_int_slice(b"\x00\xff\xff\xff\xff\xff\xff\xff\xff");
+        assert_eq!(der_obj.as_u64(), Ok(u64::MAX));
+        assert_eq!(der_obj.as_i64(), Err(BerError::IntegerTooLarge));
+        let der_obj = BerObject::from_int_slice(b"\x01\x00\x00\x00\x00\x00\x00\x00\x00");
+        assert_eq!(der_obj.as_u64(), Err(BerError::IntegerTooLarge));
+        let der_obj = BerObject::from_int_slice(b"\x80");
+        assert_eq!(der_obj.as_u64(), Err(BerError::IntegerNegative));
+    }
+
+    #[test]
+    fn test_der_as_signed() {
+        let der_obj = BerObject::from_int_slice(b"\xff");
+        assert_eq!(der_obj.as_i8(), Ok(-1));
+        assert_eq!(der_obj.as_i128(), Ok(-1));
+        let der_obj = BerObject::from_int_slice(b"\xff\x7f");
+        assert_eq!(der_obj.as_i16(), Ok(-129));
+        assert_eq!(der_obj.as_i8(), Err(BerError::IntegerTooLarge));
+        let der_obj = BerObject::from_int_slice(b"\x00\x80");
+        assert_eq!(der_obj.as_i32(), Ok(128));
+        assert_eq!(der_obj.as_u8(), Ok(128));
+        // redundant leading octets (not DER) are accepted
+        let der_obj = BerObject::from_int_slice(b"\xff\xff\xff\xff\xff\xff\xff\xff\xff\x80");
+        assert_eq!(der_obj.as_i64(), Ok(-128));
+        let der_obj = BerObject::from_int_slice(&[0x80; 17]);
+        assert_eq!(der_obj.as_i128(), Err(BerError::IntegerTooLarge));
+        let der_obj = BerObject::from_obj(BerObjectContent::Enum(-2));
+        assert_eq!(der_obj.as_i32(), Ok(-2));
+        assert_eq!(der_obj.as_u32(), Err(BerError::IntegerNegative));
     }
 
     #[test]
@@ -629,8 +1273,8 @@ mod tests {
 
     #[test]
     fn test_der_from_oid() {
-        let obj: BerObject = Oid::from(&[1, 2]).into();
-        let expected = BerObject::from_obj(BerObjectContent::OID(Oid::from(&[1, 2])));
+        let obj: BerObject = Oid::from(&[1, 2]).unwrap().into();
+        let expected = BerObject::from_obj(BerObjectContent::OID(Oid::from(&[1, 2]).unwrap()));
 
         assert_eq!(obj, expected);
     }
@@ -663,6 +1307,9 @@ mod tests {
         let expected = ::num_bigint::BigInt::from(0x10001);
 
         assert_eq!(obj.as_bigint(), Some(expected));
+        let obj = BerObject::from_obj(BerObjectContent::Integer(b"\xff\x7f"));
+        let expected = ::num_bigint::BigInt::from(-129);
+        assert_eq!(obj.as_bigint(), Some(expected));
     }
 
     #[cfg(feature = "bigint")]
@@ -672,6 +1319,10 @@ mod tests {
         let expected = ::num_bigint::BigUint::from(0x10001 as u32);
 
         assert_eq!(obj.as_biguint(), Some(expected));
+        let obj = BerObject::from_obj(BerObjectContent::Integer(b"\x00\xff"));
+        let expected = ::num_bigint::BigUint::from(255 as u32);
+        assert_eq!(obj.as_biguint(), Some(expected));
+        let obj = BerObject::from_obj(BerObjectContent::Integer(b"\xff"));
+        assert_eq!(obj.as_biguint(), None);
     }
-
 }
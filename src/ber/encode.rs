@@ -0,0 +1,475 @@
+use crate::ber::*;
+use crate::error::SerializeError;
+use crate::oid::Oid;
+use std::cmp::Ordering;
+use std::io::Write;
+
+/// Encode the identifier octets (class, structured flag and tag) of an object
+///
+/// Tags greater than 30 are encoded using the high tag number form (X.690 section 8.1.2.4),
+/// as read by the parser.
+pub fn ber_encode_identifier(
+    class: u8,
+    structured: u8,
+    tag: BerTag,
+) -> Result<Vec<u8>, SerializeError> {
+    if class > 0b11 {
+        return Err(SerializeError::InvalidClass);
+    }
+    let b0 = (class << 6) | ((structured & 1) << 5);
+    if tag.0 < 0x1f {
+        return Ok(vec![b0 | tag.0 as u8]);
+    }
+    let mut v = vec![b0 | 0x1f];
+    encode_base128(&mut v, tag.0 as u64);
+    Ok(v)
+}
+
+/// Encode the length octets of an object, using the definite form with the minimum number of
+/// octets (X.690 section 10.1)
+pub fn ber_encode_length(len: usize) -> Vec<u8> {
+    if len < 0x80 {
+        return vec![len as u8];
+    }
+    let bytes = (len as u64).to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count();
+    let mut v = Vec::with_capacity(1 + bytes.len() - skip);
+    v.push(0x80 | (bytes.len() - skip) as u8);
+    v.extend_from_slice(&bytes[skip..]);
+    v
+}
+
+/// Append the base-128 encoding of `value` (high bit set on all but the last octet)
+pub(crate) fn encode_base128(v: &mut Vec<u8>, value: u64) {
+    let mut groups = [0u8; 10];
+    let mut n = 0;
+    let mut value = value;
+    loop {
+        groups[n] = (value & 0x7f) as u8;
+        n += 1;
+        value >>= 7;
+        if value == 0 {
+            break;
+        }
+    }
+    for k in (0..n).rev() {
+        let more = if k > 0 { 0x80 } else { 0 };
+        v.push(groups[k] | more);
+    }
+}
+
+/// Encode the content octets of an object identifier (X.690 section 8.19)
+///
+/// The OID is stored encoded, so this only checks that it is not relative, and not empty.
+pub fn ber_encode_oid(oid: &Oid) -> Result<Vec<u8>, SerializeError> {
+    if oid.is_relative() || oid.bytes().is_empty() {
+        return Err(SerializeError::InvalidContent);
+    }
+    Ok(oid.bytes().to_vec())
+}
+
+/// Encode the content octets of a relative object identifier (X.690 section 8.20)
+pub fn ber_encode_relative_oid(oid: &Oid) -> Vec<u8> {
+    oid.bytes().to_vec()
+}
+
+/// Encode the content octets of a REAL value (X.690 section 8.5)
+///
+/// Binary values are encoded in the canonical form required by DER (X.690 section 11.3.1): base
+/// 2, no scaling factor and an odd mantissa. Decimal values are written as they were read.
+pub fn ber_encode_real(r: &RealValue) -> Vec<u8> {
+    match *r {
+        RealValue::Zero => Vec::new(),
+        RealValue::PlusInfinity => vec![0x40],
+        RealValue::MinusInfinity => vec![0x41],
+        RealValue::NotANumber => vec![0x42],
+        RealValue::MinusZero => vec![0x43],
+        RealValue::Decimal(form, s) => {
+            let mut v = Vec::with_capacity(1 + s.len());
+            v.push(form as u8);
+            v.extend_from_slice(s.as_bytes());
+            v
+        }
+        RealValue::Binary { mantissa, .. } if mantissa.iter().all(|&b| b == 0) => Vec::new(),
+        RealValue::Binary {
+            negative,
+            mantissa,
+            base,
+            scale,
+            exponent,
+        } => {
+            let bits_per_digit = match base {
+                8 => 3,
+                16 => 4,
+                _ => 1,
+            };
+            let (mantissa, shift) = shift_mantissa(mantissa);
+            let exponent = scale as i64 + exponent as i64 * bits_per_digit + shift as i64;
+            // minimal two's complement encoding of the exponent
+            let bytes = exponent.to_be_bytes();
+            let skip = (0..7)
+                .take_while(|&k| {
+                    (bytes[k] == 0 && bytes[k + 1] & 0x80 == 0)
+                        || (bytes[k] == 0xff && bytes[k + 1] & 0x80 != 0)
+                })
+                .count();
+            let exp = &bytes[skip..];
+            let mut v = Vec::with_capacity(2 + exp.len() + mantissa.len());
+            let sign = if negative { 0x40 } else { 0x00 };
+            if exp.len() <= 3 {
+                v.push(0x80 | sign | (exp.len() - 1) as u8);
+            } else {
+                v.push(0x80 | sign | 0b11);
+                v.push(exp.len() as u8);
+            }
+            v.extend_from_slice(exp);
+            v.extend_from_slice(&mantissa);
+            v
+        }
+    }
+}
+
+/// Shift a non-zero mantissa (unsigned big-endian integer) to the right until it is odd, and
+/// return the odd mantissa (without leading zero octets) and the number of bits shifted
+fn shift_mantissa(mantissa: &[u8]) -> (Vec<u8>, u32) {
+    let skip = mantissa.iter().take_while(|&&b| b == 0).count();
+    let zeroes = mantissa.iter().rev().take_while(|&&b| b == 0).count();
+    let m = &mantissa[skip..mantissa.len() - zeroes];
+    let bits = m[m.len() - 1].trailing_zeros();
+    let mut v: Vec<u8> = (0..m.len())
+        .map(|k| match k {
+            0 => m[0] >> bits,
+            _ => (((m[k - 1] as u16) << 8 | m[k] as u16) >> bits) as u8,
+        })
+        .collect();
+    if v[0] == 0 {
+        v.remove(0);
+    }
+    (v, 8 * zeroes as u32 + bits)
+}
+
+/// Encode a value as the content of an INTEGER or ENUMERATED, in two's complement form using the
+/// minimum number of octets
+fn encode_signed(value: i64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    // a leading octet is redundant if all its bits are equal to the high bit of the next one
+    let skip = bytes
+        .windows(2)
+        .take_while(|w| (w[0] == 0 && w[1] & 0x80 == 0) || (w[0] == 0xff && w[1] & 0x80 != 0))
+        .count();
+    bytes[skip..].to_vec()
+}
+
+/// Encode the content octets of an object
+///
+/// Note: for `ContextSpecific` objects, this returns the encoding of the inner object (which is
+/// the content of an EXPLICIT tagged value), or an empty vector if the value is absent.
+pub fn ber_encode_object_content(obj: &BerObject) -> Result<Vec<u8>, SerializeError> {
+    let v = match obj.content {
+        BerObjectContent::EndOfContent | BerObjectContent::Null => Vec::new(),
+        BerObjectContent::Boolean(b) => vec![if b { 0xff } else { 0x00 }],
+        BerObjectContent::Enum(i) => encode_signed(i),
+        BerObjectContent::Real(ref r) => ber_encode_real(r),
+        BerObjectContent::OID(ref oid) => ber_encode_oid(oid)?,
+        BerObjectContent::RelativeOID(ref oid) => ber_encode_relative_oid(oid),
+        BerObjectContent::OidIri(s) | BerObjectContent::RelativeOidIri(s) => s.as_bytes().to_vec(),
+        BerObjectContent::BitString(ignored_bits, ref b) => {
+            let mut v = Vec::with_capacity(1 + b.data.len());
+            v.push(ignored_bits);
+            v.extend_from_slice(b.data);
+            v
+        }
+        BerObjectContent::Integer(s)
+        | BerObjectContent::OctetString(s)
+        | BerObjectContent::NumericString(s)
+        | BerObjectContent::PrintableString(s)
+        | BerObjectContent::IA5String(s)
+        | BerObjectContent::UTF8String(s)
+        | BerObjectContent::T61String(s)
+        | BerObjectContent::VideotexString(s)
+        | BerObjectContent::GraphicString(s)
+        | BerObjectContent::VisibleString(s)
+        | BerObjectContent::ObjDescriptor(s)
+        | BerObjectContent::UniversalString(s)
+        | BerObjectContent::BmpString(s)
+        | BerObjectContent::UTCTime(s)
+        | BerObjectContent::GeneralizedTime(s)
+        | BerObjectContent::Time(s)
+        | BerObjectContent::Date(s)
+        | BerObjectContent::TimeOfDay(s)
+        | BerObjectContent::DateTime(s)
+        | BerObjectContent::Duration(s)
+        | BerObjectContent::GeneralString(s)
+        | BerObjectContent::Unknown(_, s) => s.to_vec(),
+        BerObjectContent::Segmented(tag, ref s) => {
+            let mut v = Vec::with_capacity(1 + s.data.len());
+            if tag == BerTag::BitString {
+                v.push(s.unused_bits);
+            }
+            v.extend_from_slice(&s.data);
+            v
+        }
+        BerObjectContent::Sequence(ref l)
+        | BerObjectContent::Set(ref l)
+        | BerObjectContent::External(ref l)
+        | BerObjectContent::EmbeddedPdv(ref l)
+        | BerObjectContent::CharacterString(ref l) => {
+            let mut v = Vec::new();
+            for o in l {
+                o.write_der(&mut v)?;
+            }
+            v
+        }
+        BerObjectContent::ContextSpecific(_, Some(ref o)) => o.to_der_vec()?,
+        BerObjectContent::ContextSpecific(_, None) => Vec::new(),
+    };
+    Ok(v)
+}
+
+impl<'a> BerObject<'a> {
+    /// Encode the object using DER, and write it to `writer`
+    ///
+    /// Lengths are encoded using the definite form with the minimum number of octets. The header
+    /// is built from the `class`, `structured` and `tag` fields of the object.
+    ///
+    /// Strings encoded using the constructed form (BER) are encoded using the primitive form.
+    ///
+    /// `ContextSpecific` objects are encoded as EXPLICIT tagged values. If the object has the
+    /// universal class (for ex. when built by
+    /// [`parse_der_optional`](../macro.parse_der_optional.html)), only the inner object is
+    /// written. Absent values (`ContextSpecific(_, None)`) are not written.
+    ///
+    /// Returns the number of bytes written.
+    pub fn write_der<W: Write>(&self, writer: &mut W) -> Result<usize, SerializeError> {
+        if let BerObjectContent::ContextSpecific(tag, ref o) = self.content {
+            match *o {
+                None => return Ok(0),
+                Some(ref o) if self.class == 0 => return o.write_der(writer),
+                Some(_) => {
+                    let content = ber_encode_object_content(self)?;
+                    return write_object(writer, self.class, 1, tag, &content);
+                }
+            }
+        }
+        let content = ber_encode_object_content(self)?;
+        let structured = match self.content {
+            BerObjectContent::Segmented(_, _) => 0,
+            _ => self.structured,
+        };
+        write_object(writer, self.class, structured, self.tag, &content)
+    }
+
+    /// Encode the object using DER, and return the encoded bytes
+    ///
+    /// See [`write_der`](#method.write_der) for details.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::BerObject;
+    /// # fn main() {
+    /// let obj = BerObject::from_seq(vec![BerObject::from_int_slice(b"\x01\x00\x01")]);
+    /// assert_eq!(
+    ///     obj.to_der_vec().expect("serialization failed"),
+    ///     vec![0x30, 0x05, 0x02, 0x03, 0x01, 0x00, 0x01]
+    /// );
+    /// # }
+    /// ```
+    pub fn to_der_vec(&self) -> Result<Vec<u8>, SerializeError> {
+        let mut v = Vec::new();
+        self.write_der(&mut v)?;
+        Ok(v)
+    }
+
+    /// Sort the elements of all the `Set` objects contained in this object (recursively), so the
+    /// DER encoding of the object is canonical (X.690 section 11.6)
+    ///
+    /// The schema is not known, so the elements are sorted by their DER encoding, as required for
+    /// a SET OF. This is the order checked by [`parse_der`](../der/fn.parse_der.html).
+    /// It is also the order of the components of a SET (sorted by tag), unless primitive and
+    /// constructed components are mixed: in this case, the components must be sorted by the
+    /// caller.
+    ///
+    /// ```rust
+    /// # extern crate der_parser;
+    /// # use der_parser::ber::BerObject;
+    /// # fn main() {
+    /// let mut obj = BerObject::from_set(vec![
+    ///     BerObject::from_int_slice(b"\x02"),
+    ///     BerObject::from_int_slice(b"\x01"),
+    /// ]);
+    /// obj.canonicalize_sets().expect("serialization failed");
+    /// assert_eq!(
+    ///     obj.to_der_vec().expect("serialization failed"),
+    ///     vec![0x31, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x02]
+    /// );
+    /// # }
+    /// ```
+    pub fn canonicalize_sets(&mut self) -> Result<(), SerializeError> {
+        match self.content {
+            BerObjectContent::Sequence(ref mut l) => {
+                for o in l.iter_mut() {
+                    o.canonicalize_sets()?;
+                }
+            }
+            BerObjectContent::Set(ref mut l) => {
+                for o in l.iter_mut() {
+                    o.canonicalize_sets()?;
+                }
+                let mut items = l
+                    .drain(..)
+                    .map(|o| o.to_der_vec().map(|v| (v, o)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                items.sort_by(|a, b| der_cmp_padded(&a.0, &b.0));
+                l.extend(items.into_iter().map(|(_, o)| o));
+            }
+            BerObjectContent::ContextSpecific(_, Some(ref mut o)) => o.canonicalize_sets()?,
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+/// Compare two encodings as octet strings, the shorter one being padded at its trailing end with
+/// 0-octets (X.690 section 11.6)
+pub(crate) fn der_cmp_padded(a: &[u8], b: &[u8]) -> Ordering {
+    let n = a.len().min(b.len());
+    match a[..n].cmp(&b[..n]) {
+        Ordering::Equal => {
+            let a_tail = a[n..].iter().any(|&x| x != 0);
+            let b_tail = b[n..].iter().any(|&x| x != 0);
+            a_tail.cmp(&b_tail)
+        }
+        o => o,
+    }
+}
+
+fn write_object<W: Write>(
+    writer: &mut W,
+    class: u8,
+    structured: u8,
+    tag: BerTag,
+    content: &[u8],
+) -> Result<usize, SerializeError> {
+    let identifier = ber_encode_identifier(class, structured, tag)?;
+    let length = ber_encode_length(content.len());
+    writer.write_all(&identifier)?;
+    writer.write_all(&length)?;
+    writer.write_all(content)?;
+    Ok(identifier.len() + length.len() + content.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ber::*;
+    use crate::oid::Oid;
+
+    #[test]
+    fn test_encode_identifier() {
+        assert_eq!(
+            ber_encode_identifier(0, 1, BerTag::Sequence),
+            Ok(vec![0x30])
+        );
+        assert_eq!(ber_encode_identifier(2, 0, BerTag(1)), Ok(vec![0x81]));
+        assert_eq!(
+            ber_encode_identifier(1, 0, BerTag(0x52)),
+            Ok(vec![0x5f, 0x52])
+        );
+        assert_eq!(
+            ber_encode_identifier(3, 1, BerTag(0x1234)),
+            Ok(vec![0xff, 0xa4, 0x34])
+        );
+        assert!(ber_encode_identifier(4, 0, BerTag::Integer).is_err());
+    }
+
+    #[test]
+    fn test_encode_length() {
+        assert_eq!(ber_encode_length(0), vec![0x00]);
+        assert_eq!(ber_encode_length(0x7f), vec![0x7f]);
+        assert_eq!(ber_encode_length(0x80), vec![0x81, 0x80]);
+        assert_eq!(ber_encode_length(0x1234), vec![0x82, 0x12, 0x34]);
+    }
+
+    #[test]
+    fn test_encode_oid() {
+        let oid = Oid::from(&[1, 2, 840, 113549, 1, 1, 5]).unwrap();
+        assert_eq!(
+            ber_encode_oid(&oid),
+            Ok(vec![0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x05])
+        );
+        let oid = Oid::from(&[2, 999, 3]).unwrap();
+        assert_eq!(ber_encode_oid(&oid), Ok(vec![0x88, 0x37, 0x03]));
+        let oid = Oid::from_relative(&[8571, 3, 2]).unwrap();
+        assert!(ber_encode_oid(&oid).is_err());
+        assert_eq!(ber_encode_relative_oid(&oid), vec![0xc2, 0x7b, 0x03, 0x02]);
+    }
+
+    #[test]
+    fn test_encode_real() {
+        // -3 * 2^1 * 16^1 = -3 * 2^5
+        let r = RealValue::Binary {
+            negative: true,
+            mantissa: &[3],
+            base: 16,
+            scale: 1,
+            exponent: 1,
+        };
+        assert_eq!(ber_encode_real(&r), vec![0xc0, 0x05, 0x03]);
+        // 0.5 = 4 * 2^-3
+        let r = RealValue::Binary {
+            negative: false,
+            mantissa: &[4],
+            base: 2,
+            scale: 0,
+            exponent: -3,
+        };
+        assert_eq!(ber_encode_real(&r), vec![0x80, 0xff, 0x01]);
+        let r = RealValue::Binary {
+            negative: false,
+            mantissa: &[1],
+            base: 2,
+            scale: 0,
+            exponent: 0x1234,
+        };
+        assert_eq!(ber_encode_real(&r), vec![0x81, 0x12, 0x34, 0x01]);
+        let r = RealValue::Binary {
+            negative: false,
+            mantissa: &[1],
+            base: 16,
+            scale: 0,
+            exponent: 0x0100_0000,
+        };
+        assert_eq!(
+            ber_encode_real(&r),
+            vec![0x83, 0x04, 0x04, 0x00, 0x00, 0x00, 0x01]
+        );
+        // mantissa larger than 64 bits: (2^64 + 2^9) * 2^-1 = (2^55 + 1) * 2^8
+        let r = RealValue::Binary {
+            negative: false,
+            mantissa: &[0x00, 0x01, 0, 0, 0, 0, 0, 0, 0x02, 0x00],
+            base: 2,
+            scale: 0,
+            exponent: -1,
+        };
+        assert_eq!(
+            ber_encode_real(&r),
+            vec![0x80, 0x08, 0x80, 0, 0, 0, 0, 0, 0x01]
+        );
+    }
+
+    #[test]
+    fn test_encode_enum() {
+        let obj = BerObject::from_obj(BerObjectContent::Enum(0x80));
+        assert_eq!(obj.to_der_vec(), Ok(vec![0x0a, 0x02, 0x00, 0x80]));
+        let obj = BerObject::from_obj(BerObjectContent::Enum(0));
+        assert_eq!(obj.to_der_vec(), Ok(vec![0x0a, 0x01, 0x00]));
+        let obj = BerObject::from_obj(BerObjectContent::Enum(-1));
+        assert_eq!(obj.to_der_vec(), Ok(vec![0x0a, 0x01, 0xff]));
+        let obj = BerObject::from_obj(BerObjectContent::Enum(-129));
+        assert_eq!(obj.to_der_vec(), Ok(vec![0x0a, 0x02, 0xff, 0x7f]));
+        let obj = BerObject::from_obj(BerObjectContent::Enum(i64::MIN));
+        assert_eq!(
+            obj.to_der_vec(),
+            Ok(vec![0x0a, 0x08, 0x80, 0, 0, 0, 0, 0, 0, 0])
+        );
+    }
+}
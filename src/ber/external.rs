@@ -0,0 +1,355 @@
+use crate::ber::*;
+use crate::error::BerError;
+use crate::oid::Oid;
+use nom::Err;
+use std::borrow::Cow;
+
+/// Identification of the abstract and transfer syntaxes of an EXTERNAL, EMBEDDED PDV or
+/// CHARACTER STRING value (X.680 section 36.5)
+#[derive(Clone, Debug, PartialEq)]
+pub enum Identification<'a> {
+    /// Abstract and transfer syntaxes
+    Syntaxes {
+        abstract_syntax: Oid<'a>,
+        transfer_syntax: Oid<'a>,
+    },
+    /// Abstract syntax (the transfer syntax is implied)
+    Syntax(Oid<'a>),
+    /// Presentation context, negotiated by the OSI presentation layer
+    PresentationContextId(i64),
+    /// Presentation context, with the transfer syntax used during the negotiation
+    ContextNegotiation {
+        presentation_context_id: i64,
+        transfer_syntax: Oid<'a>,
+    },
+    /// Transfer syntax (the abstract syntax is known by the application)
+    TransferSyntax(Oid<'a>),
+    /// Abstract and transfer syntaxes fixed by the application
+    Fixed,
+}
+
+/// Encoding of the data value of an EXTERNAL
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExternalData<'a> {
+    /// Value of a single ASN.1 type (`single-ASN1-type`)
+    SingleAsn1Type(Box<BerObject<'a>>),
+    /// Encoding using an integral number of octets (`octet-aligned`)
+    OctetAligned(Cow<'a, [u8]>),
+    /// Encoding using any number of bits (`arbitrary`), with the number of unused bits in the last
+    /// octet
+    Arbitrary(u8, Cow<'a, [u8]>),
+}
+
+/// Decoded value of an EXTERNAL object (X.690 section 8.18)
+///
+/// Both the encoding defined in X.208 (with `direct-reference`, `indirect-reference` and
+/// `encoding` components) and the encoding of the X.680 associated type (with `identification`
+/// and `data-value` components) are accepted. For the X.208 form, the `identification` is
+/// derived from the references, and for the X.680 form, the data value is returned as
+/// `OctetAligned`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct External<'a> {
+    pub identification: Identification<'a>,
+    pub data_value_descriptor: Option<Cow<'a, str>>,
+    pub data_value: ExternalData<'a>,
+}
+
+/// Decoded value of an EMBEDDED PDV object (X.690 section 8.11)
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmbeddedPdv<'a> {
+    pub identification: Identification<'a>,
+    pub data_value: Cow<'a, [u8]>,
+}
+
+/// Decoded value of a CHARACTER STRING object (X.690 section 8.24)
+///
+/// The string value is encoded using the character abstract and transfer syntaxes given by the
+/// identification, and is not decoded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CharacterString<'a> {
+    pub identification: Identification<'a>,
+    pub string_value: Cow<'a, [u8]>,
+}
+
+fn to_ber_error(e: Err<BerError>) -> BerError {
+    match e {
+        Err::Error(e) | Err::Failure(e) => e,
+        Err::Incomplete(_) => BerError::ObjectTooShort,
+    }
+}
+
+/// Get the content of a context-specific component with the given tag
+fn context_content<'a>(obj: &BerObject<'a>, tag: u32) -> Result<&'a [u8], BerError> {
+    match obj.content {
+        BerObjectContent::Unknown(t, data) if obj.is_contextspecific() && t.0 == tag => Ok(data),
+        _ => Err(BerError::InvalidTag),
+    }
+}
+
+/// Decode an IMPLICIT tagged component, using the universal type `ty`
+fn implicit<'a>(
+    obj: &BerObject<'a>,
+    tag: u32,
+    ty: BerTag,
+) -> Result<BerObjectContent<'a>, BerError> {
+    let data = context_content(obj, tag)?;
+    let len = BerSize::Definite(data.len());
+    ber_read_element_content_as(data, ty, len, obj.is_constructed(), 0)
+        .map(|(_, content)| content)
+        .map_err(to_ber_error)
+}
+
+/// Decode an EXPLICIT tagged component
+fn explicit<'a>(obj: &BerObject<'a>, tag: u32) -> Result<BerObject<'a>, BerError> {
+    let data = context_content(obj, tag)?;
+    if !obj.is_constructed() {
+        return Err(BerError::ConstructExpected);
+    }
+    match parse_ber(data) {
+        Ok((&[], inner)) => Ok(inner),
+        Ok(_) => Err(BerError::BerValueError),
+        Err(e) => Err(to_ber_error(e)),
+    }
+}
+
+fn implicit_oid<'a>(obj: &BerObject<'a>, tag: u32) -> Result<Oid<'a>, BerError> {
+    implicit(obj, tag, BerTag::Oid)?.as_oid_val()
+}
+
+fn implicit_int(obj: &BerObject, tag: u32) -> Result<i64, BerError> {
+    implicit(obj, tag, BerTag::Integer)?.as_i64()
+}
+
+/// Get the value of an OCTET STRING (using the primitive or the constructed form)
+fn octets(content: BerObjectContent) -> Result<Cow<[u8]>, BerError> {
+    match content {
+        BerObjectContent::OctetString(s) => Ok(Cow::Borrowed(s)),
+        BerObjectContent::Segmented(_, s) => Ok(Cow::Owned(s.data)),
+        _ => Err(BerError::BerTypeError),
+    }
+}
+
+/// Get the value of an ObjectDescriptor (using the primitive or the constructed form)
+fn descriptor<'a>(content: &BerObjectContent<'a>) -> Result<Cow<'a, str>, BerError> {
+    match *content {
+        BerObjectContent::ObjDescriptor(s) => ber_decode_string(BerTag::ObjDescriptor, s, false),
+        BerObjectContent::Segmented(_, ref s) => {
+            ber_decode_string(BerTag::ObjDescriptor, &s.data, false)
+                .map(|s| Cow::Owned(s.into_owned()))
+        }
+        _ => Err(BerError::BerTypeError),
+    }
+}
+
+/// Decode the `identification` component ([0] CHOICE) of the X.680 associated types
+fn decode_identification<'a>(obj: &BerObject<'a>) -> Result<Identification<'a>, BerError> {
+    let alt = explicit(obj, 0)?;
+    if !alt.is_contextspecific() {
+        return Err(BerError::InvalidTag);
+    }
+    let id = match alt.tag.0 {
+        0 => match implicit(&alt, 0, BerTag::Sequence)? {
+            BerObjectContent::Sequence(ref l) if l.len() == 2 => Identification::Syntaxes {
+                abstract_syntax: implicit_oid(&l[0], 0)?,
+                transfer_syntax: implicit_oid(&l[1], 1)?,
+            },
+            _ => return Err(BerError::BerValueError),
+        },
+        1 => Identification::Syntax(implicit_oid(&alt, 1)?),
+        2 => Identification::PresentationContextId(implicit_int(&alt, 2)?),
+        3 => match implicit(&alt, 3, BerTag::Sequence)? {
+            BerObjectContent::Sequence(ref l) if l.len() == 2 => {
+                Identification::ContextNegotiation {
+                    presentation_context_id: implicit_int(&l[0], 0)?,
+                    transfer_syntax: implicit_oid(&l[1], 1)?,
+                }
+            }
+            _ => return Err(BerError::BerValueError),
+        },
+        4 => Identification::TransferSyntax(implicit_oid(&alt, 4)?),
+        5 => {
+            implicit(&alt, 5, BerTag::Null)?;
+            Identification::Fixed
+        }
+        _ => return Err(BerError::InvalidTag),
+    };
+    Ok(id)
+}
+
+/// Decode the components of an X.680 associated type: `identification [0]`,
+/// `data-value-descriptor [1] OPTIONAL` and `data-value [2]`
+///
+/// The data value is returned as `OctetAligned`.
+fn decode_associated<'a>(l: &[BerObject<'a>]) -> Result<External<'a>, BerError> {
+    let (first, rest) = l.split_first().ok_or(BerError::BerValueError)?;
+    let identification = decode_identification(first)?;
+    let (data_value_descriptor, rest) = match rest.split_first() {
+        Some((o, rest)) if o.is_contextspecific() && o.tag.0 == 1 => {
+            let content = implicit(o, 1, BerTag::ObjDescriptor)?;
+            (Some(descriptor(&content)?), rest)
+        }
+        _ => (None, rest),
+    };
+    match rest {
+        [o] => Ok(External {
+            identification,
+            data_value_descriptor,
+            data_value: ExternalData::OctetAligned(octets(implicit(o, 2, BerTag::OctetString)?)?),
+        }),
+        _ => Err(BerError::BerValueError),
+    }
+}
+
+/// Decode the components of an EXTERNAL, using the X.208 form
+fn decode_external_x208<'a>(l: &[BerObject<'a>]) -> Result<External<'a>, BerError> {
+    let mut iter = l.iter().peekable();
+    let direct_reference = match iter.peek() {
+        Some(o) if o.is_universal() && o.tag == BerTag::Oid => Some(o.as_oid_val()?),
+        _ => None,
+    };
+    if direct_reference.is_some() {
+        iter.next();
+    }
+    let indirect_reference = match iter.peek() {
+        Some(o) if o.is_universal() && o.tag == BerTag::Integer => Some(o.as_i64()?),
+        _ => None,
+    };
+    if indirect_reference.is_some() {
+        iter.next();
+    }
+    let data_value_descriptor = match iter.peek() {
+        Some(o) if o.is_universal() && o.tag == BerTag::ObjDescriptor => {
+            Some(descriptor(&o.content)?)
+        }
+        _ => None,
+    };
+    if data_value_descriptor.is_some() {
+        iter.next();
+    }
+    let identification = match (direct_reference, indirect_reference) {
+        (Some(oid), None) => Identification::Syntax(oid),
+        (None, Some(id)) => Identification::PresentationContextId(id),
+        (Some(oid), Some(id)) => Identification::ContextNegotiation {
+            presentation_context_id: id,
+            transfer_syntax: oid,
+        },
+        (None, None) => return Err(BerError::BerValueError),
+    };
+    let encoding = iter.next().ok_or(BerError::BerValueError)?;
+    if iter.next().is_some() || !encoding.is_contextspecific() {
+        return Err(BerError::BerValueError);
+    }
+    let data_value = match encoding.tag.0 {
+        0 => ExternalData::SingleAsn1Type(Box::new(explicit(encoding, 0)?)),
+        1 => ExternalData::OctetAligned(octets(implicit(encoding, 1, BerTag::OctetString)?)?),
+        2 => match implicit(encoding, 2, BerTag::BitString)? {
+            BerObjectContent::BitString(u, b) => ExternalData::Arbitrary(u, Cow::Borrowed(b.data)),
+            BerObjectContent::Segmented(_, s) => {
+                ExternalData::Arbitrary(s.unused_bits, Cow::Owned(s.data))
+            }
+            _ => return Err(BerError::BerTypeError),
+        },
+        _ => return Err(BerError::InvalidTag),
+    };
+    Ok(External {
+        identification,
+        data_value_descriptor,
+        data_value,
+    })
+}
+
+impl<'a> External<'a> {
+    /// Decode the components of an EXTERNAL object
+    pub(crate) fn from_components(l: &[BerObject<'a>]) -> Result<External<'a>, BerError> {
+        // in the X.208 form, the first component is one of the (universal) references
+        match l.first() {
+            Some(o) if o.is_contextspecific() => decode_associated(l),
+            _ => decode_external_x208(l),
+        }
+    }
+}
+
+impl<'a> EmbeddedPdv<'a> {
+    /// Decode the components of an EMBEDDED PDV object
+    pub(crate) fn from_components(l: &[BerObject<'a>]) -> Result<EmbeddedPdv<'a>, BerError> {
+        match decode_associated(l)? {
+            External {
+                identification,
+                data_value_descriptor: None,
+                data_value: ExternalData::OctetAligned(data_value),
+            } => Ok(EmbeddedPdv {
+                identification,
+                data_value,
+            }),
+            // the data-value-descriptor is absent (X.680 section 36.9)
+            _ => Err(BerError::BerValueError),
+        }
+    }
+}
+
+impl<'a> CharacterString<'a> {
+    /// Decode the components of a CHARACTER STRING object
+    pub(crate) fn from_components(l: &[BerObject<'a>]) -> Result<CharacterString<'a>, BerError> {
+        match decode_associated(l)? {
+            External {
+                identification,
+                data_value_descriptor: None,
+                data_value: ExternalData::OctetAligned(string_value),
+            } => Ok(CharacterString {
+                identification,
+                string_value,
+            }),
+            // the data-value-descriptor is absent (X.680 section 44.5)
+            _ => Err(BerError::BerValueError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+    use hex_literal::hex;
+
+    fn identification(bytes: &[u8]) -> Result<Identification<'_>, BerError> {
+        let (_, obj) = parse_ber(bytes).expect("parsing failed");
+        decode_identification(&obj)
+    }
+
+    #[test]
+    fn test_identification() {
+        assert_eq!(
+            identification(&hex!("a0 0a a0 08 80 02 2a 03 81 02 2a 04")),
+            Ok(Identification::Syntaxes {
+                abstract_syntax: oid!(1.2.3),
+                transfer_syntax: oid!(1.2.4),
+            })
+        );
+        assert_eq!(
+            identification(&hex!("a0 04 81 02 2a 03")),
+            Ok(Identification::Syntax(oid!(1.2.3)))
+        );
+        assert_eq!(
+            identification(&hex!("a0 03 82 01 07")),
+            Ok(Identification::PresentationContextId(7))
+        );
+        assert_eq!(
+            identification(&hex!("a0 09 a3 07 80 01 07 81 02 2a 03")),
+            Ok(Identification::ContextNegotiation {
+                presentation_context_id: 7,
+                transfer_syntax: oid!(1.2.3),
+            })
+        );
+        assert_eq!(
+            identification(&hex!("a0 04 84 02 2a 03")),
+            Ok(Identification::TransferSyntax(oid!(1.2.3)))
+        );
+        assert_eq!(
+            identification(&hex!("a0 02 85 00")),
+            Ok(Identification::Fixed)
+        );
+        // unknown alternative, and wrong tag
+        assert!(identification(&hex!("a0 02 86 00")).is_err());
+        assert!(identification(&hex!("a1 02 85 00")).is_err());
+    }
+}
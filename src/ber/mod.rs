@@ -1,8 +1,21 @@
-//! Basic Encoding Rules (BER) objects and parser
+//! Basic Encoding Rules (BER) objects, parser and encoder
 
 mod ber;
+mod encode;
+mod external;
+mod options;
 mod parser;
 mod print;
+mod real;
+mod string;
+mod time;
 
 pub use crate::ber::ber::*;
+pub use crate::ber::encode::*;
+pub use crate::ber::external::*;
+pub use crate::ber::options::*;
 pub use crate::ber::parser::*;
+pub use crate::ber::print::*;
+pub use crate::ber::real::*;
+pub(crate) use crate::ber::string::*;
+pub use crate::ber::time::*;
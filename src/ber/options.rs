@@ -0,0 +1,157 @@
+use crate::ber::{BerTag, MAX_RECURSION};
+use crate::error::BerError;
+use std::cell::Cell;
+
+/// Limits applied when parsing BER/DER objects
+///
+/// The default limits are the ones used by `parse_ber` and `parse_der`. To parse untrusted input,
+/// tighter limits can be given to
+/// [`parse_ber_with_options`](fn.parse_ber_with_options.html) or
+/// [`parse_der_with_options`](../der/fn.parse_der_with_options.html):
+///
+/// ```rust
+/// # extern crate der_parser;
+/// # extern crate nom;
+/// # use der_parser::ber::*;
+/// # use der_parser::error::BerError;
+/// # use nom::Err;
+/// # fn main() {
+/// let options = ParserOptions {
+///     max_elements: 2,
+///     ..Default::default()
+/// };
+/// let bytes = [0x30, 0x09, 0x02, 0x01, 0x01, 0x02, 0x01, 0x02, 0x02, 0x01, 0x03];
+/// assert_eq!(
+///     parse_ber_with_options(&bytes, &options),
+///     Err(Err::Error(BerError::MaxElementsExceeded))
+/// );
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// Maximum depth of nested objects (default: `MAX_RECURSION`)
+    pub max_depth: usize,
+    /// Maximum length of the content of an object (default: `u32::MAX`)
+    pub max_length: usize,
+    /// Maximum number of objects, including the nested objects (default: no limit)
+    pub max_objects: usize,
+    /// Maximum number of elements in a SEQUENCE or SET, or of segments in a constructed string
+    /// (default: no limit)
+    pub max_elements: usize,
+    /// Maximum number of arcs in an OBJECT IDENTIFIER or RELATIVE-OID (default: no limit)
+    pub max_oid_arcs: usize,
+    /// Maximum size in bytes of the content of a string, OCTET STRING or BIT STRING, after
+    /// concatenation of the segments (default: no limit)
+    pub max_string_size: usize,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions {
+            max_depth: MAX_RECURSION,
+            max_length: u32::MAX as usize,
+            max_objects: usize::MAX,
+            max_elements: usize::MAX,
+            max_oid_arcs: usize::MAX,
+            max_string_size: usize::MAX,
+        }
+    }
+}
+
+/// State of a parser: limits, and counters shared by all the objects of a top-level call
+#[derive(Debug, Default)]
+pub(crate) struct ParserState {
+    pub(crate) options: ParserOptions,
+    objects: Cell<usize>,
+}
+
+impl ParserState {
+    pub(crate) fn new(options: &ParserOptions) -> Self {
+        ParserState {
+            options: *options,
+            objects: Cell::new(0),
+        }
+    }
+
+    /// Count a new object, and check the total number of objects
+    pub(crate) fn add_object(&self) -> Result<(), BerError> {
+        let n = self.objects.get() + 1;
+        if n > self.options.max_objects {
+            return Err(BerError::MaxObjectsExceeded);
+        }
+        self.objects.set(n);
+        Ok(())
+    }
+
+    pub(crate) fn check_depth(&self, depth: usize) -> Result<(), BerError> {
+        if depth > self.options.max_depth {
+            return Err(BerError::BerMaxDepth);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_length(&self, len: usize) -> Result<(), BerError> {
+        if len > self.options.max_length {
+            return Err(BerError::MaxLengthExceeded);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_elements(&self, n: usize) -> Result<(), BerError> {
+        if n > self.options.max_elements {
+            return Err(BerError::MaxElementsExceeded);
+        }
+        Ok(())
+    }
+
+    /// Check the limits on the content of a primitive object
+    pub(crate) fn check_primitive(&self, tag: BerTag, content: &[u8]) -> Result<(), BerError> {
+        match tag {
+            BerTag::Oid => self.check_oid_arcs(content, false),
+            BerTag::RelativeOid => self.check_oid_arcs(content, true),
+            BerTag::BitString
+            | BerTag::OctetString
+            | BerTag::ObjDescriptor
+            | BerTag::Utf8String
+            | BerTag::NumericString
+            | BerTag::PrintableString
+            | BerTag::T61String
+            | BerTag::VideotexString
+            | BerTag::Ia5String
+            | BerTag::GraphicString
+            | BerTag::VisibleString
+            | BerTag::GeneralString
+            | BerTag::UniversalString
+            | BerTag::BmpString
+            | BerTag::Time
+            | BerTag::Date
+            | BerTag::TimeOfDay
+            | BerTag::DateTime
+            | BerTag::Duration
+            | BerTag::OidIri
+            | BerTag::RelativeOidIri => self.check_string_size(content.len()),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_string_size(&self, len: usize) -> Result<(), BerError> {
+        if len > self.options.max_string_size {
+            return Err(BerError::MaxStringSizeExceeded);
+        }
+        Ok(())
+    }
+
+    /// Check the number of arcs of the encoded OID (or relative OID) `oid`
+    fn check_oid_arcs(&self, oid: &[u8], relative: bool) -> Result<(), BerError> {
+        // each arc ends with a byte having the high bit cleared, and the first subidentifier of
+        // an absolute OID encodes the two first arcs
+        let mut arcs = oid.iter().filter(|&&b| b & 0x80 == 0).count();
+        if !relative && arcs > 0 {
+            arcs += 1;
+        }
+        if arcs > self.options.max_oid_arcs {
+            return Err(BerError::MaxOidArcsExceeded);
+        }
+        Ok(())
+    }
+}
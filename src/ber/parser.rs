@@ -1,11 +1,14 @@
 use crate::ber::*;
 use crate::error::*;
 use crate::oid::*;
+use crate::oid_iri::is_valid_oid_iri;
 use nom::error::ErrorKind;
 use nom::number::streaming::be_u8;
 use nom::{Err, IResult, Needed};
+use std::borrow::Cow;
+use std::str;
 
-/// Maximum recursion limit
+/// Maximum recursion limit (default value of `ParserOptions::max_depth`)
 pub const MAX_RECURSION: usize = 50;
 
 /// Try to parse input bytes as u64
@@ -21,6 +24,50 @@ pub(crate) fn bytes_to_u64(s: &[u8]) -> Result<u64, BerError> {
     Ok(u)
 }
 
+/// Remove the redundant leading octets of an integer encoded in two's complement form
+/// (`0x00` followed by a byte with the high bit clear, or `0xff` followed by a byte with the high
+/// bit set)
+fn trim_int_slice(s: &[u8]) -> &[u8] {
+    let mut s = s;
+    while s.len() > 1 && ((s[0] == 0x00 && s[1] & 0x80 == 0) || (s[0] == 0xff && s[1] & 0x80 != 0))
+    {
+        s = &s[1..];
+    }
+    s
+}
+
+/// Decode the content of an INTEGER or ENUMERATED (two's complement form) as an `i128`
+pub(crate) fn bytes_to_i128(s: &[u8]) -> Result<i128, BerError> {
+    let s = trim_int_slice(s);
+    if s.len() > 16 {
+        return Err(BerError::IntegerTooLarge);
+    }
+    let negative = matches!(s.first(), Some(&b) if b & 0x80 != 0);
+    let init: i128 = if negative { -1 } else { 0 };
+    Ok(s.iter().fold(init, |acc, &b| (acc << 8) | b as i128))
+}
+
+/// Decode the content of an INTEGER or ENUMERATED (two's complement form) as an `u128`
+///
+/// The leading `0x00` octet, required to encode values with the high bit set, is removed.
+/// Negative values are rejected.
+pub(crate) fn bytes_to_u128(s: &[u8]) -> Result<u128, BerError> {
+    if matches!(s.first(), Some(&b) if b & 0x80 != 0) {
+        return Err(BerError::IntegerNegative);
+    }
+    let s = trim_int_slice(s);
+    // a positive value can need one more octet than the native type
+    let s = if s.len() == 17 && s[0] == 0 {
+        &s[1..]
+    } else {
+        s
+    };
+    if s.len() > 16 {
+        return Err(BerError::IntegerTooLarge);
+    }
+    Ok(s.iter().fold(0, |acc, &b| (acc << 8) | b as u128))
+}
+
 pub(crate) fn parse_identifier(i: &[u8]) -> IResult<&[u8], (u8, u8, u32), BerError> {
     if i.is_empty() {
         Err(Err::Incomplete(Needed::Size(1)))
@@ -64,51 +111,14 @@ pub(crate) fn parse_ber_length_byte(i: &[u8]) -> IResult<&[u8], (u8, u8), BerErr
     }
 }
 
-fn ber_read_relative_oid(i: &[u8]) -> Result<Vec<u64>, u64> {
-    let mut oid = Vec::new();
-    let mut acc: u64;
-
-    if i.is_empty() {
-        return Ok(oid);
-    };
-
-    acc = 0;
-    for &c in &i[0..] {
-        acc = (acc << 7) | (c & 0b0111_1111) as u64;
-        if (c & (1 << 7)) == 0 {
-            oid.push(acc);
-            acc = 0;
-        }
-    }
-
-    match acc {
-        0 => Ok(oid),
-        _ => Err(acc),
-    }
-}
-
-fn ber_read_oid(i: &[u8]) -> Result<Vec<u64>, u64> {
-    let mut oid = Vec::new();
-    let mut index = 0;
-
-    if i.is_empty() {
-        return Err(0);
-    };
-
-    /* first element = X*40 + Y (See 8.19.4) */
-    let acc = i[0] as u64;
-    if acc < 128 {
-        oid.push(acc / 40);
-        oid.push(acc % 40);
-        index = 1;
-    }
-
-    let rel_oid = ber_read_relative_oid(&i[index..])?;
-    oid.extend(&rel_oid);
-    Ok(oid)
+/// Check that the last subidentifier of an encoded OID is complete
+fn ber_check_oid(i: &[u8]) -> bool {
+    !matches!(i.last(), Some(b) if b & 0x80 != 0)
 }
 
 /// Read an object header
+///
+/// The indefinite length form is only accepted for constructed objects (X.690 section 8.1.3.2).
 pub fn ber_read_element_header(i: &[u8]) -> IResult<&[u8], BerObjectHeader, BerError> {
     do_parse! {
         i,
@@ -116,17 +126,21 @@ pub fn ber_read_element_header(i: &[u8]) -> IResult<&[u8], BerObjectHeader, BerE
         len:  parse_ber_length_byte >>
         llen: cond!(len.0 == 1, take!(len.1)) >>
         ( {
-            let len : u64 = match len.0 {
-                0 => len.1 as u64,
-                _ => {
+            let len = match (len.0, len.1) {
+                (0, l) => BerSize::Definite(l as usize),
+                (_, 0) => {
+                    // indefinite form, only valid for constructed objects (8.1.3.2)
+                    custom_check!(i, el.1 == 0, BerError::IndefiniteLengthUnexpected)?;
+                    BerSize::Indefinite
+                }
+                (_, l) => {
                     // if len is 0xff -> error (8.1.3.5)
-                    if len.1 == 0b0111_1111 {
+                    if l == 0b0111_1111 {
                         return Err(::nom::Err::Error(BerError::InvalidTag));
                     }
-                    // XXX llen: test if 0 (indefinite form), if len is 0xff -> error
                     match bytes_to_u64(llen.unwrap()) {
-                        Ok(l)  => l,
-                        Err(_) => { return Err(::nom::Err::Error(BerError::InvalidTag)); },
+                        Ok(l) if l <= usize::MAX as u64 => BerSize::Definite(l as usize),
+                        _ => { return Err(::nom::Err::Error(BerError::InvalidTag)); },
                     }
                 },
             };
@@ -162,7 +176,6 @@ pub(crate) fn ber_read_content_integer(
     map!(i, take!(len), |i| { BerObjectContent::Integer(i) })
 }
 
-// XXX check if constructed (8.6.3)
 #[inline]
 pub(crate) fn ber_read_content_bitstring(
     i: &[u8],
@@ -177,7 +190,6 @@ pub(crate) fn ber_read_content_bitstring(
     }
 }
 
-// XXX check if constructed (8.7)
 #[inline]
 pub(crate) fn ber_read_content_octetstring(
     i: &[u8],
@@ -200,21 +212,34 @@ pub(crate) fn ber_read_content_oid(
     do_parse! {
         i,
              error_if!(len == 0, ErrorKind::LengthValue) >>
-        oid: map_res!(take!(len),ber_read_oid) >>
-        ( BerObjectContent::OID(Oid::from(&oid)) )
+        oid: verify!(take!(len), ber_check_oid) >>
+        ( BerObjectContent::OID(Oid::new(Cow::Borrowed(oid))) )
     }
 }
 
-// XXX check if primitive (8.4)
+#[inline]
+pub(crate) fn ber_read_content_real(
+    i: &[u8],
+    len: usize,
+) -> IResult<&[u8], BerObjectContent<'_>, BerError> {
+    let (rem, s) = take!(i, len)?;
+    let r = ber_decode_real(s)?;
+    Ok((rem, BerObjectContent::Real(r)))
+}
+
 #[inline]
 pub(crate) fn ber_read_content_enum(
     i: &[u8],
     len: usize,
 ) -> IResult<&[u8], BerObjectContent, BerError> {
-    parse_hex_to_u64!(i, len).map(|(rem, i)| (rem, BerObjectContent::Enum(i)))
+    let (rem, s) = take!(i, len)?;
+    let v = bytes_to_i128(s)?;
+    if v < i64::MIN as i128 || v > i64::MAX as i128 {
+        return Err(Err::Error(BerError::IntegerTooLarge));
+    }
+    Ok((rem, BerObjectContent::Enum(v as i64)))
 }
 
-// XXX check if constructed, or indefinite length (8.21)
 #[inline]
 pub(crate) fn ber_read_content_utf8string(
     i: &[u8],
@@ -231,70 +256,127 @@ pub(crate) fn ber_read_content_relativeoid(
     do_parse! {
         i,
              custom_check!(len == 0, BerError::InvalidLength) >>
-        oid: map_res!(take!(len), ber_read_relative_oid) >>
-        ( BerObjectContent::RelativeOID(Oid::from(&oid)) )
+        oid: verify!(take!(len), ber_check_oid) >>
+        ( BerObjectContent::RelativeOID(Oid::new_relative(Cow::Borrowed(oid))) )
     }
 }
 
 #[inline]
-pub(crate) fn ber_read_content_sequence(
+pub(crate) fn ber_read_content_oid_iri(
     i: &[u8],
     len: usize,
-    depth: usize,
-) -> IResult<&[u8], BerObjectContent, BerError> {
-    if len == 0 {
-        // indefinite form
-        // read until end-of-content
-        map!(
-            i,
-            many_till!(
-                call!(parse_ber_recursive, depth + 1),
-                parse_ber_endofcontent
-            ),
-            |(l, _)| { BerObjectContent::Sequence(l) }
-        )
+    relative: bool,
+) -> IResult<&[u8], BerObjectContent<'_>, BerError> {
+    let (rem, s) = take!(i, len)?;
+    let s = str::from_utf8(s).or(Err(Err::Error(BerError::StringInvalidCharset)))?;
+    custom_check!(i, !is_valid_oid_iri(s, relative), BerError::BerValueError)?;
+    if relative {
+        Ok((rem, BerObjectContent::RelativeOidIri(s)))
     } else {
-        map!(
-            i,
-            flat_take!(
-                len,
-                many0!(complete!(call!(parse_ber_recursive, depth + 1)))
-            ),
-            |l| { BerObjectContent::Sequence(l) }
-        )
+        Ok((rem, BerObjectContent::OidIri(s)))
     }
 }
 
-#[inline]
-pub(crate) fn ber_read_content_set(
-    i: &[u8],
-    len: usize,
+/// Read the elements of a SEQUENCE or SET
+///
+/// With the definite length form, reading stops at the first invalid element (and the remaining
+/// bytes are ignored), unless a parser limit was exceeded.
+fn ber_read_elements<'a>(
+    i: &'a [u8],
+    len: BerSize,
     depth: usize,
-) -> IResult<&[u8], BerObjectContent, BerError> {
-    if len == 0 {
-        // indefinite form
+    st: &ParserState,
+) -> IResult<&'a [u8], Vec<BerObject<'a>>, BerError> {
+    let len = match len {
         // read until end-of-content
-        map!(
-            i,
-            many_till!(
-                call!(parse_ber_recursive, depth + 1),
-                parse_ber_endofcontent
-            ),
-            |(l, _)| { BerObjectContent::Set(l) }
-        )
-    } else {
-        map!(
-            i,
-            flat_take!(
-                len,
-                many0!(complete!(call!(parse_ber_recursive, depth + 1)))
-            ),
-            |l| { BerObjectContent::Set(l) }
-        )
+        BerSize::Indefinite => return ber_read_objects(i, true, depth, st),
+        BerSize::Definite(len) => len,
+    };
+    let (rem, mut content) = take!(i, len)?;
+    let mut v = Vec::new();
+    loop {
+        match parse_ber_recursive(content, depth + 1, st) {
+            Ok((r, obj)) => {
+                v.push(obj);
+                st.check_elements(v.len())?;
+                content = r;
+            }
+            Err(Err::Error(ref e)) if !e.is_limit_exceeded() => break,
+            Err(Err::Incomplete(_)) => break,
+            Err(e) => return Err(e.map(|e| e.with_index(v.len(), content))),
+        }
     }
+    Ok((rem, v))
+}
+
+#[inline]
+pub(crate) fn ber_read_content_sequence<'a>(
+    i: &'a [u8],
+    len: BerSize,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, call!(ber_read_elements, len, depth, st), |l| {
+        BerObjectContent::Sequence(l)
+    })
+}
+
+#[inline]
+pub(crate) fn ber_read_content_set<'a>(
+    i: &'a [u8],
+    len: BerSize,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, call!(ber_read_elements, len, depth, st), |l| {
+        BerObjectContent::Set(l)
+    })
+}
+
+#[inline]
+pub(crate) fn ber_read_content_external<'a>(
+    i: &'a [u8],
+    len: BerSize,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, call!(ber_read_elements, len, depth, st), |l| {
+        BerObjectContent::External(l)
+    })
+}
+
+#[inline]
+pub(crate) fn ber_read_content_embeddedpdv<'a>(
+    i: &'a [u8],
+    len: BerSize,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, call!(ber_read_elements, len, depth, st), |l| {
+        BerObjectContent::EmbeddedPdv(l)
+    })
+}
+
+#[inline]
+pub(crate) fn ber_read_content_characterstring<'a>(
+    i: &'a [u8],
+    len: BerSize,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, call!(ber_read_elements, len, depth, st), |l| {
+        BerObjectContent::CharacterString(l)
+    })
+}
+
+#[inline]
+pub(crate) fn ber_read_content_objdescriptor<'a>(
+    i: &'a [u8],
+    len: usize,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, take!(len), BerObjectContent::ObjDescriptor)
 }
 
-// XXX check if constructed, or indefinite length (8.21)
 #[inline]
 pub(crate) fn ber_read_content_numericstring(
     i: &[u8],
@@ -303,7 +385,6 @@ pub(crate) fn ber_read_content_numericstring(
     map!(i, take!(len), |s| BerObjectContent::NumericString(s))
 }
 
-// XXX check if constructed, or indefinite length (8.21)
 #[inline]
 pub(crate) fn ber_read_content_printablestring(
     i: &[u8],
@@ -312,7 +393,6 @@ pub(crate) fn ber_read_content_printablestring(
     map!(i, take!(len), |s| BerObjectContent::PrintableString(s))
 }
 
-// XXX check if constructed, or indefinite length (8.21)
 #[inline]
 pub(crate) fn ber_read_content_t61string(
     i: &[u8],
@@ -321,7 +401,14 @@ pub(crate) fn ber_read_content_t61string(
     map!(i, take!(len), |s| BerObjectContent::T61String(s))
 }
 
-// XXX check if constructed, or indefinite length (8.21)
+#[inline]
+pub(crate) fn ber_read_content_videotexstring<'a>(
+    i: &'a [u8],
+    len: usize,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, take!(len), BerObjectContent::VideotexString)
+}
+
 #[inline]
 pub(crate) fn ber_read_content_ia5string(
     i: &[u8],
@@ -346,7 +433,62 @@ pub(crate) fn ber_read_content_generalizedtime(
     map!(i, take!(len), |s| BerObjectContent::GeneralizedTime(s))
 }
 
-// XXX check if constructed, or indefinite length (8.21)
+#[inline]
+pub(crate) fn ber_read_content_time<'a>(
+    i: &'a [u8],
+    len: usize,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, take!(len), BerObjectContent::Time)
+}
+
+#[inline]
+pub(crate) fn ber_read_content_date<'a>(
+    i: &'a [u8],
+    len: usize,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, take!(len), BerObjectContent::Date)
+}
+
+#[inline]
+pub(crate) fn ber_read_content_timeofday<'a>(
+    i: &'a [u8],
+    len: usize,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, take!(len), BerObjectContent::TimeOfDay)
+}
+
+#[inline]
+pub(crate) fn ber_read_content_datetime<'a>(
+    i: &'a [u8],
+    len: usize,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, take!(len), BerObjectContent::DateTime)
+}
+
+#[inline]
+pub(crate) fn ber_read_content_duration<'a>(
+    i: &'a [u8],
+    len: usize,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, take!(len), BerObjectContent::Duration)
+}
+
+#[inline]
+pub(crate) fn ber_read_content_graphicstring<'a>(
+    i: &'a [u8],
+    len: usize,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, take!(len), BerObjectContent::GraphicString)
+}
+
+#[inline]
+pub(crate) fn ber_read_content_visiblestring<'a>(
+    i: &'a [u8],
+    len: usize,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(i, take!(len), BerObjectContent::VisibleString)
+}
+
 #[inline]
 pub(crate) fn ber_read_content_generalstring(
     i: &[u8],
@@ -355,7 +497,14 @@ pub(crate) fn ber_read_content_generalstring(
     map!(i, take!(len), |s| BerObjectContent::GeneralString(s))
 }
 
-// XXX check if constructed, or indefinite length (8.21)
+#[inline]
+pub(crate) fn ber_read_content_universalstring(
+    i: &[u8],
+    len: usize,
+) -> IResult<&[u8], BerObjectContent<'_>, BerError> {
+    map!(i, take!(len), BerObjectContent::UniversalString)
+}
+
 #[inline]
 pub(crate) fn ber_read_content_bmpstring(
     i: &[u8],
@@ -364,114 +513,321 @@ pub(crate) fn ber_read_content_bmpstring(
     map!(i, take!(len), |s| BerObjectContent::BmpString(s))
 }
 
+/// Read the objects contained in a constructed object (for ex. the segments of a string), until
+/// the end of `i` (definite length) or until an end-of-content marker (indefinite length).
+fn ber_read_objects<'a>(
+    i: &'a [u8],
+    indefinite: bool,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], Vec<BerObject<'a>>, BerError> {
+    let mut v = Vec::new();
+    let mut i = i;
+    loop {
+        if indefinite {
+            if let Ok((rem, _)) = parse_ber_endofcontent(i) {
+                return Ok((rem, v));
+            }
+        } else if i.is_empty() {
+            return Ok((i, v));
+        }
+        let (rem, obj) = parse_ber_recursive(i, depth + 1, st)
+            .map_err(|e| e.map(|e| e.with_index(v.len(), i)))?;
+        v.push(obj);
+        st.check_elements(v.len())?;
+        i = rem;
+    }
+}
+
+/// Read the content of a string type (or bitstring) encoded using the constructed form
+/// (X.690 sections 8.6.4, 8.7.3 and 8.23.6), and concatenate the segments.
+pub(crate) fn ber_read_content_segmented<'a>(
+    i: &'a [u8],
+    tag: BerTag,
+    len: BerSize,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    let (rem, segments) = match len {
+        BerSize::Indefinite => ber_read_objects(i, true, depth, st)?,
+        BerSize::Definite(len) => {
+            let (content, rem) = (&i[..len], &i[len..]);
+            let (_, segments) = match ber_read_objects(content, false, depth, st) {
+                Ok(r) => r,
+                Err(Err::Incomplete(_)) => return Err(Err::Error(BerError::ObjectTooShort)),
+                Err(e) => return Err(e),
+            };
+            (rem, segments)
+        }
+    };
+    let segment_tag = match tag {
+        BerTag::BitString => BerTag::BitString,
+        _ => BerTag::OctetString,
+    };
+    let mut data = Vec::new();
+    let mut unused_bits = 0;
+    for seg in &segments {
+        custom_check!(
+            i,
+            !seg.is_universal() || seg.tag != segment_tag,
+            BerError::InvalidTag
+        )?;
+        // only the last segment of a bitstring can have unused bits (8.6.4.2)
+        custom_check!(i, unused_bits != 0, BerError::BerValueError)?;
+        match seg.content {
+            BerObjectContent::OctetString(s) => data.extend_from_slice(s),
+            BerObjectContent::BitString(u, ref b) => {
+                data.extend_from_slice(b.data);
+                unused_bits = u;
+            }
+            BerObjectContent::Segmented(_, ref s) => {
+                data.extend_from_slice(&s.data);
+                unused_bits = s.unused_bits;
+            }
+            _ => return Err(Err::Error(BerError::BerTypeError)),
+        }
+        st.check_string_size(data.len())?;
+    }
+    let s = SegmentedString {
+        segments,
+        data,
+        unused_bits,
+    };
+    Ok((rem, BerObjectContent::Segmented(tag, s)))
+}
+
 /// Parse the next bytes as the content of a BER object.
 ///
 /// Content type is *not* checked, caller is reponsible of providing the correct tag
 pub fn ber_read_element_content_as(
     i: &[u8],
     tag: BerTag,
-    len: usize,
+    len: BerSize,
     constructed: bool,
     depth: usize,
 ) -> IResult<&[u8], BerObjectContent, BerError> {
-    if i.len() < len {
-        return Err(Err::Incomplete(Needed::Size(len)));
+    ber_read_element_content_with_state(i, tag, len, constructed, depth, &ParserState::default())
+}
+
+pub(crate) fn ber_read_element_content_with_state<'a>(
+    i: &'a [u8],
+    tag: BerTag,
+    len: BerSize,
+    constructed: bool,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    if let BerSize::Definite(l) = len {
+        if i.len() < l {
+            return Err(Err::Incomplete(Needed::Size(l)));
+        }
+        if !constructed {
+            st.check_primitive(tag, &i[..l])?;
+        }
     }
     match tag {
         // 0x00 end-of-content
         BerTag::EndOfContent => {
-            custom_check!(i, len != 0, BerError::InvalidLength)?;
+            custom_check!(i, !len.is_null(), BerError::InvalidLength)?;
             ber_read_content_eoc(i)
         }
         // 0x01 bool
         BerTag::Boolean => {
-            custom_check!(i, len != 1, BerError::InvalidLength)?;
+            custom_check!(i, len != BerSize::Definite(1), BerError::InvalidLength)?;
             ber_read_content_bool(i)
         }
         // 0x02
         BerTag::Integer => {
             custom_check!(i, constructed, BerError::ConstructUnexpected)?;
-            ber_read_content_integer(i, len)
+            ber_read_content_integer(i, len.primitive()?)
         }
         // 0x03: bitstring
         BerTag::BitString => {
-            custom_check!(i, constructed, BerError::Unsupported)?; // XXX valid in BER
-            ber_read_content_bitstring(i, len)
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_bitstring(i, len.primitive()?)
         }
         // 0x04: octetstring
         BerTag::OctetString => {
-            custom_check!(i, constructed, BerError::Unsupported)?; // XXX valid in BER
-            ber_read_content_octetstring(i, len)
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_octetstring(i, len.primitive()?)
         }
         // 0x05: null
         BerTag::Null => {
             custom_check!(i, constructed, BerError::ConstructUnexpected)?;
-            custom_check!(i, len != 0, BerError::InvalidLength)?;
+            custom_check!(i, !len.is_null(), BerError::InvalidLength)?;
             ber_read_content_null(i)
         }
         // 0x06: object identified
         BerTag::Oid => {
             custom_check!(i, constructed, BerError::ConstructUnexpected)?;
-            ber_read_content_oid(i, len)
+            ber_read_content_oid(i, len.primitive()?)
+        }
+        // 0x07: object descriptor
+        BerTag::ObjDescriptor => {
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_objdescriptor(i, len.primitive()?)
+        }
+        // 0x08: external
+        BerTag::External => {
+            custom_check!(i, !constructed, BerError::ConstructExpected)?;
+            ber_read_content_external(i, len, depth, st)
+        }
+        // 0x09: real
+        BerTag::RealType => {
+            custom_check!(i, constructed, BerError::ConstructUnexpected)?;
+            ber_read_content_real(i, len.primitive()?)
         }
         // 0x0a: enumerated
         BerTag::Enumerated => {
             custom_check!(i, constructed, BerError::ConstructUnexpected)?;
-            ber_read_content_enum(i, len)
+            ber_read_content_enum(i, len.primitive()?)
+        }
+        // 0x0b: embedded pdv
+        BerTag::EmbeddedPdv => {
+            custom_check!(i, !constructed, BerError::ConstructExpected)?;
+            ber_read_content_embeddedpdv(i, len, depth, st)
         }
         // 0x0c: UTF8String
         BerTag::Utf8String => {
-            custom_check!(i, constructed, BerError::Unsupported)?; // XXX valid in BER
-            ber_read_content_utf8string(i, len)
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_utf8string(i, len.primitive()?)
         }
         // 0x0d: relative object identified
         BerTag::RelativeOid => {
             custom_check!(i, constructed, BerError::ConstructUnexpected)?;
-            ber_read_content_relativeoid(i, len)
+            ber_read_content_relativeoid(i, len.primitive()?)
+        }
+        // 0x0e: time
+        BerTag::Time => {
+            custom_check!(i, constructed, BerError::ConstructUnexpected)?;
+            ber_read_content_time(i, len.primitive()?)
         }
         // 0x10: sequence
         BerTag::Sequence => {
             custom_check!(i, !constructed, BerError::ConstructExpected)?;
-            ber_read_content_sequence(i, len, depth)
+            ber_read_content_sequence(i, len, depth, st)
         }
         // 0x11: set
         BerTag::Set => {
             custom_check!(i, !constructed, BerError::ConstructExpected)?;
-            ber_read_content_set(i, len, depth)
+            ber_read_content_set(i, len, depth, st)
         }
         // 0x12: numericstring
         BerTag::NumericString => {
-            custom_check!(i, constructed, BerError::Unsupported)?; // XXX valid in BER
-            ber_read_content_numericstring(i, len)
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_numericstring(i, len.primitive()?)
         }
         // 0x13: printablestring
         BerTag::PrintableString => {
-            custom_check!(i, constructed, BerError::Unsupported)?; // XXX valid in BER
-            ber_read_content_printablestring(i, len)
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_printablestring(i, len.primitive()?)
         }
         // 0x14: t61string
         BerTag::T61String => {
-            custom_check!(i, constructed, BerError::Unsupported)?; // XXX valid in BER
-            ber_read_content_t61string(i, len)
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_t61string(i, len.primitive()?)
+        }
+        // 0x15: videotexstring
+        BerTag::VideotexString => {
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_videotexstring(i, len.primitive()?)
         }
         // 0x16: ia5string
         BerTag::Ia5String => {
-            custom_check!(i, constructed, BerError::Unsupported)?; // XXX valid in BER
-            ber_read_content_ia5string(i, len)
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_ia5string(i, len.primitive()?)
         }
         // 0x17: utctime
-        BerTag::UtcTime => ber_read_content_utctime(i, len),
+        BerTag::UtcTime => ber_read_content_utctime(i, len.primitive()?),
         // 0x18: generalizedtime
-        BerTag::GeneralizedTime => ber_read_content_generalizedtime(i, len),
+        BerTag::GeneralizedTime => ber_read_content_generalizedtime(i, len.primitive()?),
+        // 0x19: graphicstring
+        BerTag::GraphicString => {
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_graphicstring(i, len.primitive()?)
+        }
+        // 0x1a: visiblestring
+        BerTag::VisibleString => {
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_visiblestring(i, len.primitive()?)
+        }
         // 0x1b: generalstring
         BerTag::GeneralString => {
-            custom_check!(i, constructed, BerError::Unsupported)?; // XXX valid in BER
-            ber_read_content_generalstring(i, len)
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_generalstring(i, len.primitive()?)
+        }
+        // 0x1c: universalstring
+        BerTag::UniversalString => {
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_universalstring(i, len.primitive()?)
+        }
+        // 0x1d: character string
+        BerTag::CharacterString => {
+            custom_check!(i, !constructed, BerError::ConstructExpected)?;
+            ber_read_content_characterstring(i, len, depth, st)
         }
         // 0x1e: bmpstring
         BerTag::BmpString => {
-            custom_check!(i, constructed, BerError::Unsupported)?; // XXX valid in BER
-            ber_read_content_bmpstring(i, len)
+            if constructed {
+                return ber_read_content_segmented(i, tag, len, depth, st);
+            }
+            ber_read_content_bmpstring(i, len.primitive()?)
+        }
+        // 0x1f: date
+        BerTag::Date => {
+            custom_check!(i, constructed, BerError::ConstructUnexpected)?;
+            ber_read_content_date(i, len.primitive()?)
+        }
+        // 0x20: time-of-day
+        BerTag::TimeOfDay => {
+            custom_check!(i, constructed, BerError::ConstructUnexpected)?;
+            ber_read_content_timeofday(i, len.primitive()?)
+        }
+        // 0x21: date-time
+        BerTag::DateTime => {
+            custom_check!(i, constructed, BerError::ConstructUnexpected)?;
+            ber_read_content_datetime(i, len.primitive()?)
+        }
+        // 0x22: duration
+        BerTag::Duration => {
+            custom_check!(i, constructed, BerError::ConstructUnexpected)?;
+            ber_read_content_duration(i, len.primitive()?)
+        }
+        // 0x23: OID-IRI
+        BerTag::OidIri => {
+            custom_check!(i, constructed, BerError::ConstructUnexpected)?;
+            ber_read_content_oid_iri(i, len.primitive()?, false)
+        }
+        // 0x24: RELATIVE-OID-IRI
+        BerTag::RelativeOidIri => {
+            custom_check!(i, constructed, BerError::ConstructUnexpected)?;
+            ber_read_content_oid_iri(i, len.primitive()?, true)
         }
         // all unknown values
         _ => Err(Err::Error(BerError::UnknownTag)),
@@ -480,13 +836,18 @@ pub fn ber_read_element_content_as(
 //
 /// Parse a BER object, expecting a value with specificed tag
 pub fn parse_ber_with_tag(i: &[u8], tag: BerTag) -> IResult<&[u8], BerObject, BerError> {
-    do_parse! {
-        i,
-        hdr: ber_read_element_header >>
-             custom_check!(hdr.tag != tag, BerError::InvalidTag) >>
-        o:   call!(ber_read_element_content_as, hdr.tag, hdr.len as usize, hdr.is_constructed(), 0) >>
-        ( BerObject::from_header_and_content(hdr, o) )
+    let (rem, hdr) = ber_read_element_header(i)?;
+    let header_len = i.len() - rem.len();
+    if hdr.tag != tag {
+        let e = BerError::InvalidTag.with_frame(i, i, hdr.class, tag, None);
+        return Err(Err::Error(e));
     }
+    let (rem, o) = ber_read_element_content_as(rem, hdr.tag, hdr.len, hdr.is_constructed(), 0)
+        .map_err(|e| e.map(|e| e.with_frame(i, rem, hdr.class, tag, None)))?;
+    let mut obj =
+        BerObject::from_header_and_content(hdr, o).with_raw(&i[..i.len() - rem.len()], header_len);
+    obj.set_offsets(i);
+    Ok((rem, obj))
 }
 
 /// Read end of content marker
@@ -566,12 +927,47 @@ pub fn parse_ber_oid(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
     parse_ber_with_tag(i, BerTag::Oid)
 }
 
+/// Read an object descriptor value
+#[inline]
+pub fn parse_ber_objectdescriptor(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::ObjDescriptor)
+}
+
+/// Read an EXTERNAL value
+///
+/// The components of the associated sequence type are parsed recursively, and can be accessed
+/// using [`as_sequence`](struct.BerObject.html#method.as_sequence). To decode the value, use
+/// [`as_external`](struct.BerObject.html#method.as_external).
+#[inline]
+pub fn parse_ber_external(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::External)
+}
+
+/// Read a real value
+///
+/// To access the content, use the [`as_f64`](struct.BerObject.html#method.as_f64) or
+/// [`as_real`](struct.BerObject.html#method.as_real) methods.
+#[inline]
+pub fn parse_ber_real(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::RealType)
+}
+
 /// Read an enumerated value
 #[inline]
 pub fn parse_ber_enum(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
     parse_ber_with_tag(i, BerTag::Enumerated)
 }
 
+/// Read an EMBEDDED PDV value
+///
+/// The components of the associated sequence type are parsed recursively, and can be accessed
+/// using [`as_sequence`](struct.BerObject.html#method.as_sequence). To decode the value, use
+/// [`as_embedded_pdv`](struct.BerObject.html#method.as_embedded_pdv).
+#[inline]
+pub fn parse_ber_embedded_pdv(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::EmbeddedPdv)
+}
+
 /// Read a UTF-8 string value
 #[inline]
 pub fn parse_ber_utf8string(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
@@ -584,6 +980,30 @@ pub fn parse_ber_relative_oid(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
     parse_ber_with_tag(i, BerTag::RelativeOid)
 }
 
+/// Read a TIME value
+///
+/// The value (in ISO 8601 format) can be read using
+/// [`as_str`](struct.BerObject.html#method.as_str).
+#[inline]
+pub fn parse_ber_time(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::Time)
+}
+
+/// Read an OID-IRI value
+///
+/// The syntax of the IRI is verified. To convert it to an OID, use an
+/// [`IriRegistry`](../oid_iri/struct.IriRegistry.html).
+#[inline]
+pub fn parse_ber_oid_iri(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::OidIri)
+}
+
+/// Read a RELATIVE-OID-IRI value
+#[inline]
+pub fn parse_ber_relative_oid_iri(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::RelativeOidIri)
+}
+
 /// Parse a sequence of BER elements
 ///
 /// Read a sequence of BER objects, without any constraint on the types.
@@ -628,6 +1048,12 @@ pub fn parse_ber_t61string(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
     parse_ber_with_tag(i, BerTag::T61String)
 }
 
+/// Read a Videotex string value
+#[inline]
+pub fn parse_ber_videotexstring(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::VideotexString)
+}
+
 /// Read an IA5 string value
 #[inline]
 pub fn parse_ber_ia5string(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
@@ -646,18 +1072,74 @@ pub fn parse_ber_generalizedtime(i: &[u8]) -> IResult<&[u8], BerObject, BerError
     parse_ber_with_tag(i, BerTag::GeneralizedTime)
 }
 
+/// Read a GraphicString value
+#[inline]
+pub fn parse_ber_graphicstring(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::GraphicString)
+}
+
+/// Read a VisibleString value
+#[inline]
+pub fn parse_ber_visiblestring(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::VisibleString)
+}
+
 /// Read a GeneralString value
 #[inline]
 pub fn parse_ber_generalstring(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
     parse_ber_with_tag(i, BerTag::GeneralString)
 }
 
+/// Read a UniversalString value
+#[inline]
+pub fn parse_ber_universalstring(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::UniversalString)
+}
+
+/// Read a CHARACTER STRING value
+///
+/// The components of the associated sequence type are parsed recursively, and can be accessed
+/// using [`as_sequence`](struct.BerObject.html#method.as_sequence). To decode the value, use
+/// [`as_character_string`](struct.BerObject.html#method.as_character_string).
+#[inline]
+pub fn parse_ber_characterstring(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::CharacterString)
+}
+
 /// Read a BmpString value
 #[inline]
 pub fn parse_ber_bmpstring(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
     parse_ber_with_tag(i, BerTag::BmpString)
 }
 
+/// Read a DATE value
+///
+/// To access the content, use the [`as_time`](struct.BerObject.html#method.as_time) method.
+#[inline]
+pub fn parse_ber_date(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::Date)
+}
+
+/// Read a TIME-OF-DAY value
+#[inline]
+pub fn parse_ber_timeofday(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::TimeOfDay)
+}
+
+/// Read a DATE-TIME value
+///
+/// To access the content, use the [`as_time`](struct.BerObject.html#method.as_time) method.
+#[inline]
+pub fn parse_ber_datetime(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::DateTime)
+}
+
+/// Read a DURATION value
+#[inline]
+pub fn parse_ber_duration(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_ber_with_tag(i, BerTag::Duration)
+}
+
 pub fn parse_ber_explicit_failed(i: &[u8], tag: BerTag) -> IResult<&[u8], BerObject, BerError> {
     Ok((
         i,
@@ -669,7 +1151,7 @@ pub fn parse_ber_explicit<F>(i: &[u8], tag: BerTag, f: F) -> IResult<&[u8], BerO
 where
     F: Fn(&[u8]) -> IResult<&[u8], BerObject, BerError>,
 {
-    alt! {
+    let (rem, mut obj) = alt! {
         i,
         complete!(do_parse!(
             hdr:     ber_read_element_header >>
@@ -683,20 +1165,22 @@ where
             )
         )) |
         complete!(call!(parse_ber_explicit_failed, tag))
-    }
+    }?;
+    ber_set_location(i, rem, &mut obj);
+    Ok((rem, obj))
 }
 
 /// call der *content* parsing function
 pub fn parse_ber_implicit<F>(i: &[u8], tag: BerTag, f: F) -> IResult<&[u8], BerObject, BerError>
 where
-    F: Fn(&[u8], BerTag, usize) -> IResult<&[u8], BerObjectContent, BerError>,
+    F: Fn(&[u8], BerTag, BerSize) -> IResult<&[u8], BerObjectContent, BerError>,
 {
-    alt! {
+    let (rem, mut obj) = alt! {
         i,
         complete!(do_parse!(
             hdr:     ber_read_element_header >>
                      custom_check!(hdr.tag != tag, BerError::InvalidTag) >>
-            content: call!(f, tag, hdr.len as usize) >>
+            content: call!(f, tag, hdr.len) >>
             (
                 BerObject::from_header_and_content(
                     hdr,
@@ -705,17 +1189,118 @@ where
             )
         )) |
         complete!(call!(parse_ber_explicit_failed, tag))
+    }?;
+    ber_set_location(i, rem, &mut obj);
+    Ok((rem, obj))
+}
+
+/// Set the location of an object, parsed from the start of `i` up to `rem`, and the offsets of the
+/// objects it contains (relative to `i`)
+///
+/// The header length is not known to the tagged parsers and to the `parse_der_*` macros, so it is
+/// read again from the input.
+/// Nothing is recorded if the optional tagged value was absent.
+#[doc(hidden)]
+pub fn ber_set_location<'a>(i: &'a [u8], rem: &'a [u8], obj: &mut BerObject<'a>) {
+    if i.len() == rem.len() {
+        return;
+    }
+    if let Ok((r, _)) = ber_read_element_header(i) {
+        let raw = &i[..i.len() - rem.len()];
+        obj.location = Some(BerSpan {
+            raw,
+            header_len: i.len() - r.len(),
+            offset: 0,
+        });
+        obj.set_offsets(i);
+    }
+}
+
+/// Skip the content of an object, returning `true` if the object is an end-of-content marker
+fn ber_skip_object_content<'a>(
+    i: &'a [u8],
+    hdr: &BerObjectHeader,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], bool, BerError> {
+    st.check_depth(depth)?;
+    match hdr.len {
+        BerSize::Definite(l) => {
+            if l == 0 && hdr.is_universal() && hdr.tag == BerTag::EndOfContent {
+                return Ok((i, true));
+            }
+            let (rem, _) = take!(i, l)?;
+            Ok((rem, false))
+        }
+        BerSize::Indefinite => {
+            // read objects until end-of-content
+            let mut i = i;
+            loop {
+                let (rem, hdr) = ber_read_element_header(i)?;
+                let (rem, eoc) = ber_skip_object_content(rem, &hdr, depth + 1, st)?;
+                if eoc {
+                    return Ok((rem, false));
+                }
+                i = rem;
+            }
+        }
     }
 }
 
-fn parse_ber_recursive(i: &[u8], depth: usize) -> IResult<&[u8], BerObject, BerError> {
-    custom_check!(i, depth > MAX_RECURSION, BerError::BerMaxDepth)?;
+/// Get the raw content of an object, given its header
+///
+/// For the definite length form, this returns the next `len` bytes. For the indefinite form, the
+/// objects are skipped until the end-of-content marker, which is consumed but not included in the
+/// returned content.
+pub fn ber_get_object_content<'a>(
+    i: &'a [u8],
+    hdr: &BerObjectHeader,
+    depth: usize,
+) -> IResult<&'a [u8], &'a [u8], BerError> {
+    ber_get_object_content_with_state(i, hdr, depth, &ParserState::default())
+}
+
+fn ber_get_object_content_with_state<'a>(
+    i: &'a [u8],
+    hdr: &BerObjectHeader,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], &'a [u8], BerError> {
+    let (rem, _) = ber_skip_object_content(i, hdr, depth, st)?;
+    let len = i.len() - rem.len();
+    match hdr.len {
+        // the end-of-content marker is 2 bytes long
+        BerSize::Indefinite => Ok((rem, &i[..len - 2])),
+        BerSize::Definite(_) => Ok((rem, &i[..len])),
+    }
+}
+
+fn parse_ber_recursive<'a>(
+    i: &'a [u8],
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], BerObject<'a>, BerError> {
+    st.check_depth(depth)?;
     let (rem, hdr) = ber_read_element_header(i)?;
-    custom_check!(
-        i,
-        hdr.len as usize > i.len() || hdr.len > ::std::u32::MAX as u64,
-        BerError::InvalidLength
-    )?;
+    if let BerSize::Definite(l) = hdr.len {
+        custom_check!(i, l > i.len(), BerError::InvalidLength)?;
+        st.check_length(l)?;
+    }
+    st.add_object()?;
+    let header_len = i.len() - rem.len();
+    let (rem, obj) = ber_read_object_content(rem, hdr, depth, st)
+        .map_err(|e| e.map(|e| e.with_frame(i, rem, hdr.class, hdr.tag, None)))?;
+    let raw = &i[..i.len() - rem.len()];
+    Ok((rem, obj.with_raw(raw, header_len)))
+}
+
+fn ber_read_object_content<'a>(
+    i: &'a [u8],
+    hdr: BerObjectHeader,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], BerObject<'a>, BerError> {
+    let rem = i;
     match hdr.class {
         // universal
         0b00 |
@@ -726,22 +1311,51 @@ fn parse_ber_recursive(i: &[u8], depth: usize) -> IResult<&[u8], BerObject, BerE
         // context-specific
         0b10 => return map!(
             rem,
-            take!(hdr.len),
+            call!(ber_get_object_content_with_state, &hdr, depth, st),
             |b| { BerObject::from_header_and_content(hdr,BerObjectContent::Unknown(hdr.tag, b)) }
         ),
         _    => { return Err(Err::Error(BerError::InvalidClass)); },
     }
-    match ber_read_element_content_as(rem, hdr.tag, hdr.len as usize, hdr.is_constructed(), depth) {
+    match ber_read_element_content_with_state(
+        rem,
+        hdr.tag,
+        hdr.len,
+        hdr.is_constructed(),
+        depth,
+        st,
+    ) {
         Ok((rem, content)) => Ok((rem, BerObject::from_header_and_content(hdr, content))),
-        Err(Err::Error(BerError::UnknownTag)) => map!(rem, take!(hdr.len), |b| {
-            BerObject::from_header_and_content(hdr, BerObjectContent::Unknown(hdr.tag, b))
-        }),
+        Err(Err::Error(BerError::UnknownTag)) => {
+            map!(
+                rem,
+                call!(ber_get_object_content_with_state, &hdr, depth, st),
+                |b| {
+                    BerObject::from_header_and_content(hdr, BerObjectContent::Unknown(hdr.tag, b))
+                }
+            )
+        }
         Err(e) => Err(e),
     }
 }
 
 /// Parse BER object
+///
+/// The location of the object and of all the objects it contains is recorded, relative to `i`.
 #[inline]
 pub fn parse_ber(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
-    parse_ber_recursive(i, 0)
+    parse_ber_with_options(i, &ParserOptions::default())
+}
+
+/// Parse BER object, using the given limits
+///
+/// Like [`parse_ber`](fn.parse_ber.html), but the parser fails with a specific error (for ex.
+/// `MaxObjectsExceeded`) if the object exceeds one of the limits set in `options`.
+pub fn parse_ber_with_options<'a>(
+    i: &'a [u8],
+    options: &ParserOptions,
+) -> IResult<&'a [u8], BerObject<'a>, BerError> {
+    let st = ParserState::new(options);
+    let (rem, mut obj) = parse_ber_recursive(i, 0, &st)?;
+    obj.set_offsets(i);
+    Ok((rem, obj))
 }
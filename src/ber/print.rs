@@ -1,5 +1,10 @@
 use crate::ber::BitStringObject;
-use crate::ber::{BerObject, BerObjectContent, BerTag};
+use crate::ber::{ber_read_element_header, universal_tag_name, BerObject, BerObjectContent};
+use crate::ber::{BerSize, BerTag};
+use crate::oid::Oid;
+use crate::oid_iri::IriRegistry;
+#[cfg(feature = "oid-registry")]
+use crate::oid_registry::{lookup, OidEntry, OidRegistry};
 use std::fmt;
 use std::str;
 
@@ -7,13 +12,47 @@ use rusticata_macros::debug;
 
 #[derive(Clone, PartialEq)]
 pub enum PrettyPrinterFlag {
+    /// Show the class, structured flag and tag of objects (`Debug` only)
     ShowHeader,
+    /// Show a hex dump of the content of primitive objects (`Display` only)
+    HexDump,
+    /// Do not truncate long values (`Display` only)
+    NoTruncate,
 }
 
+/// Default maximum length of the values shown by `Display`, in bytes or characters
+pub const PRETTY_MAX_LEN: usize = 64;
+
+/// Pretty printer for BER objects
+///
+/// `Debug` shows the objects as an indented tree. `Display` shows an annotated dump, similar
+/// to `openssl asn1parse`: offset, depth, header length, content length, form and type of each
+/// object, followed by the decoded value. The offsets and lengths are only known for parsed
+/// objects. With the `oid-registry` feature, the names of known OIDs are also shown.
+///
+/// ```rust
+/// # extern crate der_parser;
+/// # use der_parser::parse_der;
+/// # fn main() {
+/// let bytes = [0x30, 0x08, 0x02, 0x01, 0x2a, 0x06, 0x03, 0x2a, 0x03, 0x04];
+/// let (_, obj) = parse_der(&bytes).expect("parsing failed");
+/// let dump = format!("{}", obj.as_pretty(0, 2));
+/// assert_eq!(
+///     dump,
+///     "    0:d=0  hl=2 l=   8 cons: SEQUENCE\n\
+///      \x20   2:d=1  hl=2 l=   1 prim:   INTEGER           :42\n\
+///      \x20   5:d=1  hl=2 l=   3 prim:   OBJECT IDENTIFIER :1.2.3.4\n"
+/// );
+/// # }
+/// ```
 pub struct PrettyBer<'a> {
     obj: &'a BerObject<'a>,
     indent: usize,
     inc: usize,
+    depth: usize,
+    max_len: usize,
+    #[cfg(feature = "oid-registry")]
+    registry: Option<&'a OidRegistry>,
 
     flags: Vec<PrettyPrinterFlag>,
 }
@@ -24,6 +63,10 @@ impl<'a> BerObject<'a> {
             obj: self,
             indent: indent,
             inc: increment,
+            depth: 0,
+            max_len: PRETTY_MAX_LEN,
+            #[cfg(feature = "oid-registry")]
+            registry: None,
 
             flags: Vec::new(),
         }
@@ -37,14 +80,201 @@ impl<'a> PrettyBer<'a> {
         }
     }
 
-    pub fn next_indent<'b>(&self, obj: &'b BerObject) -> PrettyBer<'b> {
+    /// Set the maximum length of the values shown by `Display` (default: `PRETTY_MAX_LEN`)
+    pub fn set_max_len(&mut self, max_len: usize) {
+        self.max_len = max_len;
+    }
+
+    /// Set the registry used to name the OIDs shown by `Display` (default: the built-in entries)
+    #[cfg(feature = "oid-registry")]
+    pub fn set_registry(&mut self, registry: &'a OidRegistry) {
+        self.registry = Some(registry);
+    }
+
+    pub fn next_indent<'b>(&self, obj: &'b BerObject) -> PrettyBer<'b>
+    where
+        'a: 'b,
+    {
         PrettyBer {
             obj: obj,
             indent: self.indent + self.inc,
             inc: self.inc,
+            depth: self.depth + 1,
+            max_len: self.max_len,
+            #[cfg(feature = "oid-registry")]
+            registry: self.registry,
             flags: self.flags.to_vec(),
         }
     }
+
+    fn max_len(&self) -> usize {
+        if self.flags.contains(&PrettyPrinterFlag::NoTruncate) {
+            usize::MAX
+        } else {
+            self.max_len
+        }
+    }
+
+    /// Format bytes as hexadecimal, truncated if needed
+    fn hex(&self, data: &[u8]) -> String {
+        let n = data.len().min(self.max_len());
+        let mut s: String = data[..n].iter().map(|b| format!("{:02X}", b)).collect();
+        if n < data.len() {
+            s.push_str("...");
+        }
+        s
+    }
+
+    /// Format a string, truncated if needed
+    fn quoted(&self, s: &str) -> String {
+        let max_len = self.max_len();
+        if s.chars().count() > max_len {
+            let t: String = s.chars().take(max_len).collect();
+            format!("'{}'...", t)
+        } else {
+            format!("'{}'", s)
+        }
+    }
+
+    /// Name of an OID, if known
+    #[cfg(feature = "oid-registry")]
+    fn oid_name(&self, oid: &Oid) -> Option<&str> {
+        let entry = match self.registry {
+            Some(registry) => registry.get(oid),
+            None => lookup(oid),
+        };
+        entry.map(OidEntry::long_name)
+    }
+
+    #[cfg(not(feature = "oid-registry"))]
+    fn oid_name(&self, _oid: &Oid) -> Option<&str> {
+        None
+    }
+
+    /// Decoded value of the object, if any
+    fn value(&self) -> Option<String> {
+        let obj = self.obj;
+        let v = match obj.content {
+            BerObjectContent::Boolean(b) => (if b { "TRUE" } else { "FALSE" }).to_string(),
+            BerObjectContent::Integer(i) => match obj.as_i64() {
+                Ok(n) => n.to_string(),
+                Err(_) => self.hex(i),
+            },
+            BerObjectContent::Enum(n) => n.to_string(),
+            BerObjectContent::Real(ref r) => match r.as_f64() {
+                Ok(f) => f.to_string(),
+                Err(_) => format!("{:?}", r),
+            },
+            BerObjectContent::OID(ref oid) => match self.oid_name(oid) {
+                Some(name) => format!("{} ({})", oid, name),
+                None => oid.to_string(),
+            },
+            BerObjectContent::RelativeOID(ref oid) => oid.to_string(),
+            BerObjectContent::OidIri(s) => match IriRegistry::builtin().to_oid(s) {
+                Ok(oid) => format!("{} ({})", self.quoted(s), oid),
+                Err(_) => self.quoted(s),
+            },
+            BerObjectContent::UTCTime(s) | BerObjectContent::GeneralizedTime(s) => {
+                match obj.as_time() {
+                    Ok(t) => t.to_string(),
+                    Err(_) => self.quoted(&String::from_utf8_lossy(s)),
+                }
+            }
+            BerObjectContent::BitString(u, BitStringObject { data }) => {
+                format!("unused={} {}", u, self.hex(data))
+            }
+            BerObjectContent::OctetString(s) | BerObjectContent::Unknown(_, s) => self.hex(s),
+            BerObjectContent::Segmented(BerTag::OctetString, ref s) => self.hex(&s.data),
+            BerObjectContent::Segmented(BerTag::BitString, ref s) => {
+                format!("unused={} {}", s.unused_bits, self.hex(&s.data))
+            }
+            _ => match obj.to_string_lossy() {
+                Ok(s) => self.quoted(&s),
+                Err(_) => return None,
+            },
+        };
+        Some(v)
+    }
+
+    /// Content of primitive objects, for the hex dump
+    fn content(&self) -> Option<&'a [u8]> {
+        match self.obj.content {
+            BerObjectContent::Sequence(_)
+            | BerObjectContent::Set(_)
+            | BerObjectContent::External(_)
+            | BerObjectContent::EmbeddedPdv(_)
+            | BerObjectContent::CharacterString(_)
+            | BerObjectContent::Segmented(..)
+            | BerObjectContent::ContextSpecific(..) => None,
+            _ => self.obj.raw_content(),
+        }
+    }
+}
+
+impl<'a> fmt::Display for PrettyBer<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let obj = self.obj;
+        match obj.location {
+            Some(loc) => {
+                write!(
+                    f,
+                    "{:5}:d={:<2} hl={} ",
+                    loc.offset, self.depth, loc.header_len
+                )?;
+                match ber_read_element_header(loc.raw) {
+                    Ok((_, hdr)) if hdr.len == BerSize::Indefinite => write!(f, "l= inf ")?,
+                    _ => write!(f, "l={:4} ", loc.raw.len() - loc.header_len)?,
+                }
+            }
+            None => write!(f, "    -:d={:<2} hl=- l=   - ", self.depth)?,
+        }
+        let form = if obj.is_constructed() { "cons" } else { "prim" };
+        let name = match (obj.class, universal_tag_name(obj.tag)) {
+            (0b00, Some(name)) => name.to_string(),
+            (0b00, None) => format!("univ [ {} ]", obj.tag.0),
+            (0b01, _) => format!("appl [ {} ]", obj.tag.0),
+            (0b10, _) => format!("cont [ {} ]", obj.tag.0),
+            _ => format!("priv [ {} ]", obj.tag.0),
+        };
+        write!(f, "{}: {:2$}", form, "", self.indent)?;
+        match self.value() {
+            Some(v) => writeln!(f, "{:<18}:{}", name, v)?,
+            None => writeln!(f, "{}", name)?,
+        }
+        if self.flags.contains(&PrettyPrinterFlag::HexDump) {
+            if let Some(data) = self.content() {
+                let n = data.len().min(self.max_len());
+                for chunk in data[..n].chunks(16) {
+                    let hex: Vec<_> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+                    writeln!(f, "{:1$}{2}", "", self.indent + 14, hex.join(" "))?;
+                }
+                if n < data.len() {
+                    writeln!(f, "{:1$}...", "", self.indent + 14)?;
+                }
+            }
+        }
+        match obj.content {
+            BerObjectContent::Sequence(ref v)
+            | BerObjectContent::Set(ref v)
+            | BerObjectContent::External(ref v)
+            | BerObjectContent::EmbeddedPdv(ref v)
+            | BerObjectContent::CharacterString(ref v) => {
+                for o in v {
+                    write!(f, "{}", self.next_indent(o))?;
+                }
+            }
+            BerObjectContent::Segmented(_, ref s) => {
+                for o in &s.segments {
+                    write!(f, "{}", self.next_indent(o))?;
+                }
+            }
+            BerObjectContent::ContextSpecific(_, Some(ref o)) => {
+                write!(f, "{}", self.next_indent(o))?
+            }
+            _ => (),
+        }
+        Ok(())
+    }
 }
 
 impl<'a> fmt::Debug for PrettyBer<'a> {
@@ -62,26 +292,56 @@ impl<'a> fmt::Debug for PrettyBer<'a> {
                 Err(e) => writeln!(f, "{}({:?}) <error decoding utf8 string: {:?}>", ty, s, e),
             }
         }
+        fn print_string_with_type(f: &mut fmt::Formatter, c: &BerObjectContent, s: &[u8], ty: &str) -> fmt::Result {
+            match c.as_string() {
+                Ok(b)  => writeln!(f, "{}(\"{}\")", ty, b),
+                Err(e) => writeln!(f, "{}({:?}) <error decoding string: {:?}>", ty, debug::HexSlice(s), e),
+            }
+        }
         match self.obj.content {
             BerObjectContent::EndOfContent           => writeln!(f, "EndOfContent"),
             BerObjectContent::Boolean(b)             => writeln!(f, "Boolean({:?})", b),
             BerObjectContent::Integer(i)             => writeln!(f, "Integer({:?})", debug::HexSlice(i)),
             BerObjectContent::Enum(i)                => writeln!(f, "Enum({})", i),
+            BerObjectContent::Real(ref r)            => writeln!(f, "Real({:?})", r),
             BerObjectContent::OID(ref v)             => writeln!(f, "OID({:?})", v),
             BerObjectContent::RelativeOID(ref v)     => writeln!(f, "RelativeOID({:?})", v),
+            BerObjectContent::OidIri(s)              => writeln!(f, "OidIri({:?})", s),
+            BerObjectContent::RelativeOidIri(s)      => writeln!(f, "RelativeOidIri({:?})", s),
             BerObjectContent::Null                   => writeln!(f, "Null"),
             BerObjectContent::OctetString(v)         => writeln!(f, "OctetString({:?})", debug::HexSlice(v)),
             BerObjectContent::BitString(u,BitStringObject{data:v})
                                                      => writeln!(f, "BitString({},{:?})", u, debug::HexSlice(v)),
             BerObjectContent::GeneralizedTime(s)     => print_utf8_string_with_type(f, s, "GeneralizedTime"),
             BerObjectContent::UTCTime(s)             => print_utf8_string_with_type(f, s, "UTCTime"),
-            BerObjectContent::PrintableString(s)     => print_utf8_string_with_type(f, s, "PrintableString"),
-            BerObjectContent::NumericString(s)       => print_utf8_string_with_type(f, s, "NumericString"),
-            BerObjectContent::UTF8String(s)          => print_utf8_string_with_type(f, s, "UTF8String"),
-            BerObjectContent::IA5String(s)           => print_utf8_string_with_type(f, s, "IA5String"),
-            BerObjectContent::T61String(s)           => print_utf8_string_with_type(f, s, "T61String"),
-            BerObjectContent::BmpString(s)           => print_utf8_string_with_type(f, s, "BmpString"),
-            BerObjectContent::GeneralString(s)       => print_utf8_string_with_type(f, s, "GeneralString"),
+            BerObjectContent::Time(s)                => print_string_with_type(f, &self.obj.content, s, "Time"),
+            BerObjectContent::Date(s)                => print_string_with_type(f, &self.obj.content, s, "Date"),
+            BerObjectContent::TimeOfDay(s)           => print_string_with_type(f, &self.obj.content, s, "TimeOfDay"),
+            BerObjectContent::DateTime(s)            => print_string_with_type(f, &self.obj.content, s, "DateTime"),
+            BerObjectContent::Duration(s)            => print_string_with_type(f, &self.obj.content, s, "Duration"),
+            BerObjectContent::PrintableString(s)     => print_string_with_type(f, &self.obj.content, s, "PrintableString"),
+            BerObjectContent::NumericString(s)       => print_string_with_type(f, &self.obj.content, s, "NumericString"),
+            BerObjectContent::UTF8String(s)          => print_string_with_type(f, &self.obj.content, s, "UTF8String"),
+            BerObjectContent::IA5String(s)           => print_string_with_type(f, &self.obj.content, s, "IA5String"),
+            BerObjectContent::T61String(s)           => print_string_with_type(f, &self.obj.content, s, "T61String"),
+            BerObjectContent::VideotexString(s)      => print_string_with_type(f, &self.obj.content, s, "VideotexString"),
+            BerObjectContent::GraphicString(s)       => print_string_with_type(f, &self.obj.content, s, "GraphicString"),
+            BerObjectContent::VisibleString(s)       => print_string_with_type(f, &self.obj.content, s, "VisibleString"),
+            BerObjectContent::ObjDescriptor(s)       => print_string_with_type(f, &self.obj.content, s, "ObjDescriptor"),
+            BerObjectContent::UniversalString(s)     => print_string_with_type(f, &self.obj.content, s, "UniversalString"),
+            BerObjectContent::BmpString(s)           => print_string_with_type(f, &self.obj.content, s, "BmpString"),
+            BerObjectContent::GeneralString(s)       => print_string_with_type(f, &self.obj.content, s, "GeneralString"),
+            BerObjectContent::Segmented(tag,ref s)   => {
+                writeln!(f, "Segmented({:?},{},{:?}) [", tag, s.unused_bits, debug::HexSlice(&s.data))?;
+                for o in &s.segments {
+                    write!(f, "{:?}", self.next_indent(o))?;
+                };
+                if self.indent > 0 {
+                    write!(f, "{:1$}", " ", self.indent)?;
+                };
+                writeln!(f, "]")?;
+                Ok(())
+            },
             BerObjectContent::ContextSpecific(n,ref o) => {
                 let new_indent = self.indent + self.inc;
                 writeln!(f, "ContextSpecific [{}] {{", n)?;
@@ -108,6 +368,19 @@ impl<'a> fmt::Debug for PrettyBer<'a> {
                 writeln!(f, "]")?;
                 Ok(())
             },
+            BerObjectContent::External(ref v) |
+            BerObjectContent::EmbeddedPdv(ref v) |
+            BerObjectContent::CharacterString(ref v) => {
+                writeln!(f, "{:?}[", self.obj.content.tag())?;
+                for o in v {
+                    write!(f, "{:?}", self.next_indent(o))?;
+                };
+                if self.indent > 0 {
+                    write!(f, "{:1$}", " ", self.indent)?;
+                };
+                writeln!(f, "]")?;
+                Ok(())
+            },
             BerObjectContent::Unknown(tag,o)         => writeln!(f, "Unknown({:?},{:x?})", tag, o),
         }
     }
@@ -136,4 +409,59 @@ mod tests {
         println!("{:?}", pp);
     }
 
+    #[test]
+    fn test_pretty_display() {
+        let bytes =
+            hex_literal::hex!("30 80 31 03 01 01 ff 04 05 00 01 02 03 04 0c 02 61 62 00 00");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let mut pp = obj.as_pretty(0, 1);
+        pp.set_max_len(4);
+        pp.set_flag(PrettyPrinterFlag::HexDump);
+        let expected = "    0:d=0  hl=2 l= inf cons: SEQUENCE\n\
+                        \x20   2:d=1  hl=2 l=   3 cons:  SET\n\
+                        \x20   4:d=2  hl=2 l=   1 prim:   BOOLEAN           :TRUE\n\
+                        \x20               ff\n\
+                        \x20   7:d=1  hl=2 l=   5 prim:  OCTET STRING      :00010203...\n\
+                        \x20              00 01 02 03\n\
+                        \x20              ...\n\
+                        \x20  14:d=1  hl=2 l=   2 prim:  UTF8String        :'ab'\n\
+                        \x20              61 62\n";
+        assert_eq!(format!("{}", pp), expected);
+        // definite length of 128 bytes, using the long form
+        let mut bytes = hex_literal::hex!("30 81 80 04 7e").to_vec();
+        bytes.extend_from_slice(&[0; 126]);
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let s = format!("{}", obj.as_pretty(0, 1));
+        assert!(s.starts_with("    0:d=0  hl=3 l= 128 cons: SEQUENCE\n"));
+        let obj = BerObject::from_int_slice(b"\x01\x00\x01");
+        assert_eq!(
+            format!("{}", obj.as_pretty(0, 2)),
+            "    -:d=0  hl=- l=   - prim: INTEGER           :65537\n"
+        );
+    }
+
+    #[cfg(feature = "oid-registry")]
+    #[test]
+    fn test_pretty_oid_names() {
+        use crate::oid_registry::{OidEntry, OidRegistry};
+        let bytes = hex_literal::hex!("30 0a 06 03 55 04 03 06 03 2a 03 04");
+        let (_, obj) = crate::parse_der(&bytes).expect("parsing failed");
+        let expected = "    0:d=0  hl=2 l=  10 cons: SEQUENCE\n\
+                        \x20   2:d=1  hl=2 l=   3 prim:  OBJECT IDENTIFIER :2.5.4.3 (commonName)\n\
+                        \x20   7:d=1  hl=2 l=   3 prim:  OBJECT IDENTIFIER :1.2.3.4\n";
+        assert_eq!(format!("{}", obj.as_pretty(0, 1)), expected);
+        let mut registry = OidRegistry::new();
+        registry.insert(OidEntry::new(
+            crate::oid::Oid::from(&[1, 2, 3, 4]).unwrap(),
+            "test",
+            "testOid",
+            "Test",
+        ));
+        let mut pp = obj.as_pretty(0, 1);
+        pp.set_registry(&registry);
+        let expected = "    0:d=0  hl=2 l=  10 cons: SEQUENCE\n\
+                        \x20   2:d=1  hl=2 l=   3 prim:  OBJECT IDENTIFIER :2.5.4.3\n\
+                        \x20   7:d=1  hl=2 l=   3 prim:  OBJECT IDENTIFIER :1.2.3.4 (testOid)\n";
+        assert_eq!(format!("{}", pp), expected);
+    }
 }
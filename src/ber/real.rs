@@ -0,0 +1,387 @@
+use crate::error::BerError;
+use std::str;
+
+/// Number representation of a decimal REAL value (ISO 6093)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecimalForm {
+    /// Integer, for ex. `42` or `-7`
+    NR1 = 1,
+    /// Number with a decimal mark, for ex. `4.2` or `-0,5`
+    NR2 = 2,
+    /// Number with a decimal mark and an exponent, for ex. `42.E-1`
+    NR3 = 3,
+}
+
+/// Value of a REAL object (X.690 section 8.5)
+///
+/// Values are kept in the representation used by the encoding, so no precision is lost. To get an
+/// approximation as a native type, use [`as_f64`](#method.as_f64).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RealValue<'a> {
+    /// Zero (encoded with no content octets)
+    Zero,
+    /// Binary encoding: the value is `mantissa * 2^scale * base^exponent`, negated if `negative`
+    Binary {
+        negative: bool,
+        /// Mantissa, as an unsigned big-endian integer of any size
+        mantissa: &'a [u8],
+        /// Base of the exponent (2, 8 or 16)
+        base: u8,
+        /// Binary scaling factor (0 to 3)
+        scale: u8,
+        exponent: i32,
+    },
+    /// Decimal encoding, as an ISO 6093 number representation
+    Decimal(DecimalForm, &'a str),
+    PlusInfinity,
+    MinusInfinity,
+    NotANumber,
+    MinusZero,
+}
+
+impl<'a> RealValue<'a> {
+    /// Get the value as a 64-bits floating point number
+    ///
+    /// The conversion may be inexact (rounding), or overflow to infinity.
+    pub fn as_f64(&self) -> Result<f64, BerError> {
+        match *self {
+            RealValue::Zero => Ok(0.0),
+            RealValue::MinusZero => Ok(-0.0),
+            RealValue::PlusInfinity => Ok(f64::INFINITY),
+            RealValue::MinusInfinity => Ok(f64::NEG_INFINITY),
+            RealValue::NotANumber => Ok(f64::NAN),
+            RealValue::Binary {
+                negative,
+                mantissa,
+                base,
+                scale,
+                exponent,
+            } => {
+                let bits_per_digit = match base {
+                    2 => 1,
+                    8 => 3,
+                    16 => 4,
+                    _ => return Err(BerError::BerValueError),
+                };
+                // only the 8 most significant octets of the mantissa fit in a u64, the others
+                // are accounted for in the exponent
+                let skip = mantissa.iter().take_while(|&&b| b == 0).count();
+                let mantissa = &mantissa[skip..];
+                let n = mantissa.len().min(8);
+                let m = mantissa[..n]
+                    .iter()
+                    .fold(0u64, |acc, &b| (acc << 8) | b as u64);
+                let e = scale as i64
+                    + exponent as i64 * bits_per_digit
+                    + 8 * (mantissa.len() - n) as i64;
+                // saturate: anything larger overflows (or underflows) an f64 anyway
+                let e = e.clamp(-2098, 2098);
+                // multiply in two steps to avoid intermediate overflows
+                let h = (e / 2) as i32;
+                let v = m as f64 * 2f64.powi(h) * 2f64.powi(e as i32 - h);
+                Ok(if negative { -v } else { v })
+            }
+            RealValue::Decimal(_, s) => s
+                .trim_start_matches(' ')
+                .replace(',', ".")
+                .parse::<f64>()
+                .or(Err(BerError::BerValueError)),
+        }
+    }
+}
+
+/// Decode the content octets of a REAL value (X.690 section 8.5)
+pub(crate) fn ber_decode_real(s: &[u8]) -> Result<RealValue<'_>, BerError> {
+    let (first, rest) = match s.split_first() {
+        None => return Ok(RealValue::Zero),
+        Some((&first, rest)) => (first, rest),
+    };
+    match first >> 6 {
+        // binary encoding (8.5.7)
+        0b10 | 0b11 => {
+            let base = match (first >> 4) & 0b11 {
+                0b00 => 2,
+                0b01 => 8,
+                0b10 => 16,
+                _ => return Err(BerError::BerValueError),
+            };
+            let (exp_len, rest) = match first & 0b11 {
+                0b11 => match rest.split_first() {
+                    Some((&n, rest)) => (n as usize, rest),
+                    None => return Err(BerError::InvalidLength),
+                },
+                n => (n as usize + 1, rest),
+            };
+            if exp_len == 0 || rest.len() <= exp_len {
+                return Err(BerError::InvalidLength);
+            }
+            let (exp, mantissa) = rest.split_at(exp_len);
+            Ok(RealValue::Binary {
+                negative: first & 0x40 != 0,
+                mantissa,
+                base,
+                scale: (first >> 2) & 0b11,
+                exponent: read_exponent(exp)?,
+            })
+        }
+        // decimal encoding (8.5.8)
+        0b00 => {
+            let form = match first & 0x3f {
+                1 => DecimalForm::NR1,
+                2 => DecimalForm::NR2,
+                3 => DecimalForm::NR3,
+                _ => return Err(BerError::BerValueError),
+            };
+            if !check_decimal(rest, form) {
+                return Err(BerError::BerValueError);
+            }
+            // check_decimal only accepts ASCII characters
+            let repr = str::from_utf8(rest).or(Err(BerError::BerValueError))?;
+            Ok(RealValue::Decimal(form, repr))
+        }
+        // special real values (8.5.9)
+        _ => {
+            if !rest.is_empty() {
+                return Err(BerError::InvalidLength);
+            }
+            match first {
+                0x40 => Ok(RealValue::PlusInfinity),
+                0x41 => Ok(RealValue::MinusInfinity),
+                0x42 => Ok(RealValue::NotANumber),
+                0x43 => Ok(RealValue::MinusZero),
+                _ => Err(BerError::BerValueError),
+            }
+        }
+    }
+}
+
+/// Read the exponent of a binary REAL, encoded as a two's complement binary number
+fn read_exponent(s: &[u8]) -> Result<i32, BerError> {
+    if s.len() > 4 {
+        // only accept larger encodings if they are sign extensions
+        let (pad, rest) = s.split_at(s.len() - 4);
+        let sign = if rest[0] & 0x80 != 0 { 0xff } else { 0x00 };
+        if pad.iter().any(|&b| b != sign) {
+            return Err(BerError::IntegerTooLarge);
+        }
+        return read_exponent(rest);
+    }
+    let init = if s[0] & 0x80 != 0 { -1 } else { 0 };
+    Ok(s.iter().fold(init, |acc, &b| (acc << 8) | b as i32))
+}
+
+/// Test if the remaining bytes are ASCII digits, starting at `idx`, and return the index of the
+/// first non-digit byte
+fn skip_digits(s: &[u8], idx: usize) -> usize {
+    idx + s[idx..].iter().take_while(|c| c.is_ascii_digit()).count()
+}
+
+/// Verify the syntax of an ISO 6093 number representation
+fn check_decimal(s: &[u8], form: DecimalForm) -> bool {
+    let mut idx = s.iter().take_while(|&&c| c == b' ').count();
+    if idx < s.len() && (s[idx] == b'+' || s[idx] == b'-') {
+        idx += 1;
+    }
+    let start = idx;
+    idx = skip_digits(s, idx);
+    let int_digits = idx - start;
+    if form == DecimalForm::NR1 {
+        return int_digits > 0 && idx == s.len();
+    }
+    // decimal mark is mandatory for NR2 and NR3
+    if idx == s.len() || (s[idx] != b'.' && s[idx] != b',') {
+        return false;
+    }
+    let start = idx + 1;
+    idx = skip_digits(s, start);
+    if int_digits == 0 && idx == start {
+        return false;
+    }
+    if form == DecimalForm::NR2 {
+        return idx == s.len();
+    }
+    if idx == s.len() || (s[idx] != b'E' && s[idx] != b'e') {
+        return false;
+    }
+    idx += 1;
+    if idx < s.len() && (s[idx] == b'+' || s[idx] == b'-') {
+        idx += 1;
+    }
+    let start = idx;
+    idx = skip_digits(s, idx);
+    idx > start && idx == s.len()
+}
+
+/// Verify the DER (and CER) constraints on the content octets of a REAL value (X.690 section
+/// 11.3)
+///
+/// Binary encodings must use base 2, no scaling factor, an odd mantissa and the minimum number
+/// of octets. Decimal encodings must use the canonical NR3 form, for ex. `-12.E+0` or `1.E-5`.
+pub(crate) fn der_check_real(s: &[u8]) -> bool {
+    let (first, rest) = match s.split_first() {
+        None => return true,
+        Some((&first, rest)) => (first, rest),
+    };
+    match first >> 6 {
+        0b10 | 0b11 => {
+            // base 2, scaling factor 0
+            if first & 0b0011_1100 != 0 {
+                return false;
+            }
+            let (exp_len, rest) = match first & 0b11 {
+                0b11 => match rest.split_first() {
+                    // long form is only needed for more than 3 octets
+                    Some((&n, rest)) if n > 3 => (n as usize, rest),
+                    _ => return false,
+                },
+                n => (n as usize + 1, rest),
+            };
+            if rest.len() <= exp_len {
+                return false;
+            }
+            let (exp, mantissa) = rest.split_at(exp_len);
+            // exponent is encoded in the minimum number of octets
+            if exp.len() > 1
+                && ((exp[0] == 0 && exp[1] & 0x80 == 0) || (exp[0] == 0xff && exp[1] & 0x80 != 0))
+            {
+                return false;
+            }
+            // mantissa is odd (hence, has no leading zero octet)
+            mantissa[0] != 0 && mantissa[mantissa.len() - 1] & 1 == 1
+        }
+        0b00 => first == DecimalForm::NR3 as u8 && der_check_decimal(rest),
+        _ => true,
+    }
+}
+
+/// Verify the canonical NR3 form: `[-]M.E[-]X` where the mantissa `M` has no leading or trailing
+/// zeros, and the exponent `X` has no leading zeros (and is written `+0` if zero)
+fn der_check_decimal(s: &[u8]) -> bool {
+    let mut idx = if s.first() == Some(&b'-') { 1 } else { 0 };
+    let start = idx;
+    idx = skip_digits(s, idx);
+    if idx == start || s[start] == b'0' || s[idx - 1] == b'0' {
+        return false;
+    }
+    let exp = match s[idx..].split_first() {
+        Some((b'.', r)) => match r.split_first() {
+            Some((b'E', e)) => e,
+            _ => return false,
+        },
+        _ => return false,
+    };
+    match exp {
+        b"+0" => true,
+        _ => {
+            let digits = if exp.first() == Some(&b'-') {
+                &exp[1..]
+            } else {
+                exp
+            };
+            !digits.is_empty() && digits[0] != b'0' && skip_digits(digits, 0) == digits.len()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_real_binary() {
+        // 0.15625 = 5 * 2^-5
+        let r = ber_decode_real(&[0x80, 0xfb, 0x05]).expect("decoding failed");
+        assert_eq!(
+            r,
+            RealValue::Binary {
+                negative: false,
+                mantissa: &[5],
+                base: 2,
+                scale: 0,
+                exponent: -5,
+            }
+        );
+        assert_eq!(r.as_f64(), Ok(0.15625));
+        // -3 * 2^1 * 16^1
+        let r = ber_decode_real(&[0xe4, 0x01, 0x03]).expect("decoding failed");
+        assert_eq!(r.as_f64(), Ok(-96.0));
+        // long form exponent, with sign extension
+        let r = ber_decode_real(&[0x83, 0x05, 0xff, 0xff, 0xff, 0xff, 0xfe, 0x01]);
+        assert_eq!(r.and_then(|r| r.as_f64()), Ok(0.25));
+        // mantissa larger than 64 bits: (2^64 + 1) * 2^-64
+        let bytes = [0x80, 0xc0, 0x01, 0, 0, 0, 0, 0, 0, 0, 0x01];
+        let r = ber_decode_real(&bytes).expect("decoding failed");
+        assert_eq!(
+            r,
+            RealValue::Binary {
+                negative: false,
+                mantissa: &bytes[2..],
+                base: 2,
+                scale: 0,
+                exponent: -64,
+            }
+        );
+        assert_eq!(r.as_f64(), Ok(1.0));
+        // reserved base
+        assert_eq!(
+            ber_decode_real(&[0xb0, 0x00, 0x01]),
+            Err(BerError::BerValueError)
+        );
+        // missing mantissa
+        assert_eq!(ber_decode_real(&[0x81, 0x00]), Err(BerError::InvalidLength));
+    }
+
+    #[test]
+    fn test_decode_real_decimal() {
+        let r = ber_decode_real(b"\x01  -42").expect("decoding failed");
+        assert_eq!(r, RealValue::Decimal(DecimalForm::NR1, "  -42"));
+        assert_eq!(r.as_f64(), Ok(-42.0));
+        let r = ber_decode_real(b"\x020,5").expect("decoding failed");
+        assert_eq!(r.as_f64(), Ok(0.5));
+        let r = ber_decode_real(b"\x03+12.E-1").expect("decoding failed");
+        assert_eq!(r.as_f64(), Ok(1.2));
+        assert!(ber_decode_real(b"\x011.5").is_err());
+        assert!(ber_decode_real(b"\x0215").is_err());
+        assert!(ber_decode_real(b"\x031.5").is_err());
+        assert!(ber_decode_real(b"\x03.E1").is_err());
+        assert!(ber_decode_real(b"\x0412").is_err());
+    }
+
+    #[test]
+    fn test_decode_real_special() {
+        assert_eq!(ber_decode_real(b""), Ok(RealValue::Zero));
+        assert_eq!(ber_decode_real(&[0x40]), Ok(RealValue::PlusInfinity));
+        assert_eq!(ber_decode_real(&[0x41]), Ok(RealValue::MinusInfinity));
+        assert!(ber_decode_real(&[0x42])
+            .and_then(|r| r.as_f64())
+            .map(f64::is_nan)
+            .unwrap_or(false));
+        let r = ber_decode_real(&[0x43]).and_then(|r| r.as_f64());
+        assert!(r.map(|f| f == 0.0 && f.is_sign_negative()).unwrap_or(false));
+        assert_eq!(ber_decode_real(&[0x44]), Err(BerError::BerValueError));
+        assert_eq!(ber_decode_real(&[0x40, 0x00]), Err(BerError::InvalidLength));
+    }
+
+    #[test]
+    fn test_der_check_real() {
+        assert!(der_check_real(&[]));
+        assert!(der_check_real(&[0x80, 0xfb, 0x05]));
+        assert!(der_check_real(&[0x43]));
+        assert!(der_check_real(b"\x03-12.E+0"));
+        assert!(der_check_real(b"\x031.E-5"));
+        // base 8, scaling factor, even mantissa, non-minimal exponent
+        assert!(!der_check_real(&[0x90, 0xfb, 0x05]));
+        assert!(!der_check_real(&[0x84, 0xfb, 0x05]));
+        assert!(!der_check_real(&[0x80, 0xfb, 0x0a]));
+        assert!(!der_check_real(&[0x81, 0xff, 0xfb, 0x05]));
+        assert!(!der_check_real(&[0x83, 0x01, 0xfb, 0x05]));
+        // non-canonical decimal forms
+        assert!(!der_check_real(b"\x0112"));
+        assert!(!der_check_real(b"\x03+12.E+0"));
+        assert!(!der_check_real(b"\x0310.E+0"));
+        assert!(!der_check_real(b"\x031.5E+0"));
+        assert!(!der_check_real(b"\x031.E+1"));
+        assert!(!der_check_real(b"\x031.E0"));
+        assert!(!der_check_real(b"\x031.e1"));
+    }
+}
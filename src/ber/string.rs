@@ -0,0 +1,296 @@
+use crate::ber::BerTag;
+use crate::error::BerError;
+use std::borrow::Cow;
+use std::char::REPLACEMENT_CHARACTER;
+use std::str;
+
+/// Test if a byte is in the alphabet of the NumericString type (X.680 section 41.2)
+fn is_numeric(b: u8) -> bool {
+    b.is_ascii_digit() || b == b' '
+}
+
+/// Test if a byte is in the alphabet of the PrintableString type (X.680 section 41.4)
+fn is_printable(b: u8) -> bool {
+    b.is_ascii_alphanumeric()
+        || matches!(
+            b,
+            b' ' | b'\'' | b'(' | b')' | b'+' | b',' | b'-' | b'.' | b'/' | b':' | b'=' | b'?'
+        )
+}
+
+/// Test if a byte is in the alphabet of the IA5String type (ASCII)
+fn is_ia5(b: u8) -> bool {
+    b < 0x80
+}
+
+/// Test if a byte is in the alphabet of the VisibleString type (printing ASCII characters and
+/// space, X.680 section 41.1)
+///
+/// This alphabet is also used by the time types (TIME, DATE, TIME-OF-DAY, DATE-TIME and
+/// DURATION), which are encoded as strings.
+fn is_visible(b: u8) -> bool {
+    (0x20..=0x7e).contains(&b)
+}
+
+/// Decode a string using a single-byte charset included in ASCII
+fn decode_ascii_subset(s: &[u8], f: fn(u8) -> bool, lossy: bool) -> Result<Cow<'_, str>, BerError> {
+    if s.iter().all(|&b| f(b)) {
+        // all allowed characters are ASCII
+        return str::from_utf8(s)
+            .map(Cow::Borrowed)
+            .or(Err(BerError::StringInvalidCharset));
+    }
+    if !lossy {
+        return Err(BerError::StringInvalidCharset);
+    }
+    let s = s
+        .iter()
+        .map(|&b| {
+            if f(b) {
+                b as char
+            } else {
+                REPLACEMENT_CHARACTER
+            }
+        })
+        .collect();
+    Ok(Cow::Owned(s))
+}
+
+/// Decode a string using fixed-size big-endian code units (UCS-2 or UCS-4)
+fn decode_ucs(s: &[u8], unit: usize, lossy: bool) -> Result<Cow<'static, str>, BerError> {
+    let mut out = String::with_capacity(s.len() / unit);
+    for chunk in s.chunks(unit) {
+        let c = if chunk.len() == unit {
+            let v = chunk.iter().fold(0u32, |acc, &b| (acc << 8) | b as u32);
+            // surrogates are rejected, UCS-2 can only represent the Basic Multilingual Plane
+            std::char::from_u32(v)
+        } else {
+            None
+        };
+        match c {
+            Some(c) => out.push(c),
+            None if lossy => out.push(REPLACEMENT_CHARACTER),
+            None => return Err(BerError::StringInvalidCharset),
+        }
+    }
+    Ok(Cow::Owned(out))
+}
+
+/// Map the T.61 characters from the upper half of the code table to Unicode
+///
+/// Non-spacing diacritical marks (0xc1 to 0xcf) are handled separately.
+#[rustfmt::skip]
+fn t61_upper_char(b: u8) -> Option<char> {
+    let c = match b {
+        0x80..=0x9f => return std::char::from_u32(b as u32),
+        0xa0 => '\u{a0}', 0xa1 => '¡', 0xa2 => '¢', 0xa3 => '£', 0xa4 => '$', 0xa5 => '¥',
+        0xa6 => '#', 0xa7 => '§', 0xa8 => '¤', 0xab => '«', 0xb0 => '°', 0xb1 => '±',
+        0xb2 => '²', 0xb3 => '³', 0xb4 => '×', 0xb5 => 'µ', 0xb6 => '¶', 0xb7 => '·',
+        0xb8 => '÷', 0xbb => '»', 0xbc => '¼', 0xbd => '½', 0xbe => '¾', 0xbf => '¿',
+        0xe0 => 'Ω', 0xe1 => 'Æ', 0xe2 => 'Đ', 0xe3 => 'ª', 0xe4 => 'Ħ', 0xe6 => 'Ĳ',
+        0xe7 => 'Ŀ', 0xe8 => 'Ł', 0xe9 => 'Ø', 0xea => 'Œ', 0xeb => 'º', 0xec => 'Þ',
+        0xed => 'Ŧ', 0xee => 'Ŋ', 0xef => 'ŉ', 0xf0 => 'ĸ', 0xf1 => 'æ', 0xf2 => 'đ',
+        0xf3 => 'ð', 0xf4 => 'ħ', 0xf5 => 'ı', 0xf6 => 'ĳ', 0xf7 => 'ŀ', 0xf8 => 'ł',
+        0xf9 => 'ø', 0xfa => 'œ', 0xfb => 'ß', 0xfc => 'þ', 0xfd => 'ŧ', 0xfe => 'ŋ',
+        _ => return None,
+    };
+    Some(c)
+}
+
+/// Map a T.61 non-spacing diacritical mark to the Unicode combining character
+#[rustfmt::skip]
+fn t61_diacritic(b: u8) -> Option<char> {
+    let c = match b {
+        0xc1 => '\u{300}', 0xc2 => '\u{301}', 0xc3 => '\u{302}', 0xc4 => '\u{303}',
+        0xc5 => '\u{304}', 0xc6 => '\u{306}', 0xc7 => '\u{307}', 0xc8 => '\u{308}',
+        0xc9 => '\u{308}', 0xca => '\u{30a}', 0xcb => '\u{327}', 0xcd => '\u{30b}',
+        0xce => '\u{328}', 0xcf => '\u{30c}',
+        _ => return None,
+    };
+    Some(c)
+}
+
+/// Transcode a T.61 (Teletex) string to Unicode
+///
+/// In T.61, diacritical marks precede the base character. They are converted to Unicode
+/// combining characters, which follow the base character. The result is not normalized.
+fn decode_t61(s: &[u8], lossy: bool) -> Result<Cow<'_, str>, BerError> {
+    if s.is_ascii() {
+        return str::from_utf8(s)
+            .map(Cow::Borrowed)
+            .or(Err(BerError::StringInvalidCharset));
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut iter = s.iter();
+    while let Some(&b) = iter.next() {
+        let c = if b < 0x80 {
+            Some(b as char)
+        } else if let Some(mark) = t61_diacritic(b) {
+            // the base character must be a letter (or a space, to get the spacing mark)
+            match iter.next() {
+                Some(&base) if base.is_ascii_alphabetic() || base == b' ' => {
+                    out.push(base as char);
+                    Some(mark)
+                }
+                _ => None,
+            }
+        } else {
+            t61_upper_char(b)
+        };
+        match c {
+            Some(c) => out.push(c),
+            None if lossy => out.push(REPLACEMENT_CHARACTER),
+            None => return Err(BerError::StringInvalidCharset),
+        }
+    }
+    Ok(Cow::Owned(out))
+}
+
+/// Decode the content of a character string object to Unicode, using the charset defined by the
+/// tag.
+///
+/// If `lossy` is true, invalid characters are replaced by `U+FFFD`. Otherwise, the decoding fails
+/// with error `StringInvalidCharset`.
+///
+/// The content of a GeneralString, GraphicString, ObjectDescriptor or VideotexString is decoded
+/// as UTF-8 (ISO 2022 escape sequences and T.101 control functions are not interpreted).
+pub(crate) fn ber_decode_string(
+    tag: BerTag,
+    s: &[u8],
+    lossy: bool,
+) -> Result<Cow<'_, str>, BerError> {
+    match tag {
+        BerTag::NumericString => decode_ascii_subset(s, is_numeric, lossy),
+        BerTag::PrintableString => decode_ascii_subset(s, is_printable, lossy),
+        BerTag::Ia5String => decode_ascii_subset(s, is_ia5, lossy),
+        BerTag::VisibleString
+        | BerTag::Time
+        | BerTag::Date
+        | BerTag::TimeOfDay
+        | BerTag::DateTime
+        | BerTag::Duration => decode_ascii_subset(s, is_visible, lossy),
+        BerTag::Utf8String
+        | BerTag::GeneralString
+        | BerTag::GraphicString
+        | BerTag::ObjDescriptor
+        | BerTag::VideotexString
+        | BerTag::OidIri
+        | BerTag::RelativeOidIri => {
+            if lossy {
+                Ok(String::from_utf8_lossy(s))
+            } else {
+                str::from_utf8(s)
+                    .map(Cow::Borrowed)
+                    .or(Err(BerError::StringInvalidCharset))
+            }
+        }
+        BerTag::T61String => decode_t61(s, lossy),
+        BerTag::BmpString => decode_ucs(s, 2, lossy),
+        BerTag::UniversalString => decode_ucs(s, 4, lossy),
+        _ => Err(BerError::BerTypeError),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_ascii_strings() {
+        assert_eq!(
+            ber_decode_string(BerTag::NumericString, b"01 23", false),
+            Ok(Cow::Borrowed("01 23"))
+        );
+        assert_eq!(
+            ber_decode_string(BerTag::NumericString, b"01a", false),
+            Err(BerError::StringInvalidCharset)
+        );
+        assert_eq!(
+            ber_decode_string(BerTag::PrintableString, b"Test (a=b)?", false),
+            Ok(Cow::Borrowed("Test (a=b)?"))
+        );
+        assert_eq!(
+            ber_decode_string(BerTag::PrintableString, b"a@b", false),
+            Err(BerError::StringInvalidCharset)
+        );
+        assert_eq!(
+            ber_decode_string(BerTag::PrintableString, b"a@b*", true),
+            Ok(Cow::Owned("a\u{fffd}b\u{fffd}".to_string()))
+        );
+        assert_eq!(
+            ber_decode_string(BerTag::Ia5String, b"a@b", false),
+            Ok(Cow::Borrowed("a@b"))
+        );
+        assert_eq!(
+            ber_decode_string(BerTag::Ia5String, b"\xe9", false),
+            Err(BerError::StringInvalidCharset)
+        );
+        assert_eq!(
+            ber_decode_string(BerTag::VisibleString, b"a@b ~", false),
+            Ok(Cow::Borrowed("a@b ~"))
+        );
+        assert_eq!(
+            ber_decode_string(BerTag::VisibleString, b"a\tb", false),
+            Err(BerError::StringInvalidCharset)
+        );
+        assert_eq!(
+            ber_decode_string(BerTag::OctetString, b"abc", true),
+            Err(BerError::BerTypeError)
+        );
+    }
+
+    #[test]
+    fn test_decode_ucs_strings() {
+        assert_eq!(
+            ber_decode_string(BerTag::BmpString, b"\x00U\x00s\x00\xe9\x20\xac", false),
+            Ok(Cow::Owned("Usé€".to_string()))
+        );
+        // odd length, and surrogate
+        assert_eq!(
+            ber_decode_string(BerTag::BmpString, b"\x00U\x00", false),
+            Err(BerError::StringInvalidCharset)
+        );
+        assert_eq!(
+            ber_decode_string(BerTag::BmpString, b"\xd8\x3d\xde\x00", true),
+            Ok(Cow::Owned("\u{fffd}\u{fffd}".to_string()))
+        );
+        assert_eq!(
+            ber_decode_string(
+                BerTag::UniversalString,
+                b"\x00\x00\x00U\x00\x01\xf6\x00",
+                false
+            ),
+            Ok(Cow::Owned("U\u{1f600}".to_string()))
+        );
+        assert_eq!(
+            ber_decode_string(BerTag::UniversalString, b"\x00\x11\x00\x00", false),
+            Err(BerError::StringInvalidCharset)
+        );
+    }
+
+    #[test]
+    fn test_decode_t61() {
+        assert_eq!(
+            ber_decode_string(BerTag::T61String, b"abc", false),
+            Ok(Cow::Borrowed("abc"))
+        );
+        // "Müller £5", with a diaeresis before the base letter
+        assert_eq!(
+            ber_decode_string(BerTag::T61String, b"M\xc8uller \xa35", false),
+            Ok(Cow::Owned("Mu\u{308}ller £5".to_string()))
+        );
+        assert_eq!(
+            ber_decode_string(BerTag::T61String, b"\xe8\xf9d\xfb", false),
+            Ok(Cow::Owned("Łødß".to_string()))
+        );
+        // undefined code, and diacritic without base letter
+        assert_eq!(
+            ber_decode_string(BerTag::T61String, b"\xa9", false),
+            Err(BerError::StringInvalidCharset)
+        );
+        assert_eq!(
+            ber_decode_string(BerTag::T61String, b"a\xc2", true),
+            Ok(Cow::Owned("a\u{fffd}".to_string()))
+        );
+    }
+}
@@ -0,0 +1,645 @@
+use crate::error::BerError;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Time zone of an `ASN1Time` value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ASN1TimeZone {
+    /// No time zone indication: the value is a local time (GeneralizedTime, DATE and DATE-TIME)
+    Undefined,
+    /// Coordinated Universal Time (UTC)
+    Z,
+    /// Local time, with the given offset (hours and minutes, with the same sign) from UTC
+    Offset(i8, i8),
+}
+
+/// Decoded value of an UTCTime, GeneralizedTime, DATE or DATE-TIME object
+///
+/// Fractions of hours or minutes (allowed by the GeneralizedTime syntax) are converted to minutes,
+/// seconds and nanoseconds. Absent elements are set to 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ASN1Time {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    /// Seconds, between 0 and 60 (for leap seconds)
+    pub second: u8,
+    /// Fraction of seconds, in nanoseconds
+    pub nanosecond: u32,
+    pub tz: ASN1TimeZone,
+}
+
+/// Simple cursor over the bytes of a time value
+struct TimeReader<'a> {
+    s: &'a [u8],
+    idx: usize,
+}
+
+impl<'a> TimeReader<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.s.get(self.idx).cloned()
+    }
+
+    /// Test if the next two bytes are digits
+    fn has_digits2(&self) -> bool {
+        self.s.len() >= self.idx + 2 && is_digits(&self.s[self.idx..self.idx + 2])
+    }
+
+    /// Read a number written using `n` decimal digits
+    fn digits(&mut self, n: usize) -> Result<u32, BerError> {
+        if self.s.len() < self.idx + n {
+            return Err(BerError::BerValueError);
+        }
+        let d = &self.s[self.idx..self.idx + n];
+        if !is_digits(d) {
+            return Err(BerError::BerValueError);
+        }
+        self.idx += n;
+        Ok(d.iter().fold(0, |acc, &c| acc * 10 + (c - b'0') as u32))
+    }
+
+    /// Read the separator `c`
+    fn separator(&mut self, c: u8) -> Result<(), BerError> {
+        if self.peek() != Some(c) {
+            return Err(BerError::BerValueError);
+        }
+        self.idx += 1;
+        Ok(())
+    }
+
+    /// Read a date, using the `YYYY-MM-DD` format
+    fn date(&mut self) -> Result<(u32, u32, u32), BerError> {
+        let year = self.digits(4)?;
+        self.separator(b'-')?;
+        let month = self.digits(2)?;
+        self.separator(b'-')?;
+        let day = self.digits(2)?;
+        Ok((year, month, day))
+    }
+
+    /// Read a time of day, using the `hh:mm:ss` format
+    fn time_of_day(&mut self) -> Result<(u32, u32, u32), BerError> {
+        let hour = self.digits(2)?;
+        self.separator(b':')?;
+        let minute = self.digits(2)?;
+        self.separator(b':')?;
+        let second = self.digits(2)?;
+        Ok((hour, minute, second))
+    }
+
+    /// Check that all the input has been read
+    fn end(&self) -> Result<(), BerError> {
+        if self.idx != self.s.len() {
+            return Err(BerError::BerValueError);
+        }
+        Ok(())
+    }
+
+    /// Read a time zone (`Z` or a `+hh[mm]`/`-hh[mm]` offset) and the end of input
+    fn time_zone(
+        &mut self,
+        allow_undefined: bool,
+        offset_minutes: bool,
+    ) -> Result<ASN1TimeZone, BerError> {
+        let tz = match self.peek() {
+            None if allow_undefined => ASN1TimeZone::Undefined,
+            Some(b'Z') => {
+                self.idx += 1;
+                ASN1TimeZone::Z
+            }
+            Some(c) if c == b'+' || c == b'-' => {
+                self.idx += 1;
+                let hh = self.digits(2)?;
+                let mm = if offset_minutes || self.peek().is_some() {
+                    self.digits(2)?
+                } else {
+                    0
+                };
+                if hh > 23 || mm > 59 {
+                    return Err(BerError::BerValueError);
+                }
+                let sign = if c == b'-' { -1 } else { 1 };
+                ASN1TimeZone::Offset(sign * hh as i8, sign * mm as i8)
+            }
+            _ => return Err(BerError::BerValueError),
+        };
+        if self.idx != self.s.len() {
+            return Err(BerError::BerValueError);
+        }
+        Ok(tz)
+    }
+}
+
+/// Test if a string of bytes only contains ASCII digits
+fn is_digits(s: &[u8]) -> bool {
+    s.iter().all(|c| c.is_ascii_digit())
+}
+
+fn is_leap_year(year: u32) -> bool {
+    match (year % 400, year % 100, year % 4) {
+        (0, _, _) => true,
+        (_, 0, _) => false,
+        (_, _, 0) => true,
+        _ => false,
+    }
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Number of days since 1970-01-01 (proleptic Gregorian calendar)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+impl ASN1Time {
+    /// Decode the content of an UTCTime object (X.680 section 47)
+    ///
+    /// The syntax is `YYMMDDhhmm[ss]` followed by `Z` or an offset `+hhmm`/`-hhmm`. Following
+    /// X.509, years are in the range 1950 to 2049.
+    pub fn from_utctime(s: &[u8]) -> Result<ASN1Time, BerError> {
+        let mut r = TimeReader { s, idx: 0 };
+        let yy = r.digits(2)?;
+        let month = r.digits(2)?;
+        let day = r.digits(2)?;
+        let hour = r.digits(2)?;
+        let minute = r.digits(2)?;
+        let second = if r.has_digits2() { r.digits(2)? } else { 0 };
+        let tz = r.time_zone(false, true)?;
+        let year = if yy >= 50 { 1900 + yy } else { 2000 + yy };
+        ASN1Time::new(year, month, day, hour, minute, second, 0, tz)
+    }
+
+    /// Decode the content of a GeneralizedTime object (X.680 section 46)
+    ///
+    /// The syntax is `YYYYMMDDhh[mm[ss]]`, with an optional fraction (using `.` or `,`) of the last
+    /// element, followed by `Z`, an offset `+hh[mm]`/`-hh[mm]`, or nothing (local time).
+    pub fn from_generalizedtime(s: &[u8]) -> Result<ASN1Time, BerError> {
+        let mut r = TimeReader { s, idx: 0 };
+        let year = r.digits(4)?;
+        let month = r.digits(2)?;
+        let day = r.digits(2)?;
+        let hour = r.digits(2)?;
+        // duration (in nanoseconds) of the last element, for the fraction
+        let mut unit: u64 = 3_600_000_000_000;
+        let mut minute = 0;
+        let mut second = 0;
+        if r.has_digits2() {
+            minute = r.digits(2)?;
+            unit = 60_000_000_000;
+            if r.has_digits2() {
+                second = r.digits(2)?;
+                unit = 1_000_000_000;
+            }
+        }
+        let mut fraction = 0;
+        if r.peek() == Some(b'.') || r.peek() == Some(b',') {
+            r.idx += 1;
+            let n = s[r.idx..].iter().take_while(|c| c.is_ascii_digit()).count();
+            if n == 0 {
+                return Err(BerError::BerValueError);
+            }
+            // digits after the 12th are below the nanosecond for all units
+            let digits = n.min(12);
+            let num = s[r.idx..r.idx + digits]
+                .iter()
+                .fold(0u64, |acc, &c| acc * 10 + (c - b'0') as u64);
+            r.idx += n;
+            fraction = (num as u128 * unit as u128 / 10u128.pow(digits as u32)) as u64;
+        }
+        let tz = r.time_zone(true, false)?;
+        // fraction is less than the last element, so it does not overflow to the hour
+        let minute = minute + (fraction / 60_000_000_000) as u32;
+        let second = second + (fraction % 60_000_000_000 / 1_000_000_000) as u32;
+        let nanosecond = (fraction % 1_000_000_000) as u32;
+        ASN1Time::new(year, month, day, hour, minute, second, nanosecond, tz)
+    }
+
+    /// Decode the content of a DATE object (X.680 section 38.4.1)
+    ///
+    /// The syntax is `YYYY-MM-DD`. The time is set to midnight, and the time zone is undefined.
+    pub fn from_date(s: &[u8]) -> Result<ASN1Time, BerError> {
+        let mut r = TimeReader { s, idx: 0 };
+        let (year, month, day) = r.date()?;
+        r.end()?;
+        ASN1Time::new(year, month, day, 0, 0, 0, 0, ASN1TimeZone::Undefined)
+    }
+
+    /// Decode the content of a DATE-TIME object (X.680 section 38.4.3)
+    ///
+    /// The syntax is `YYYY-MM-DDThh:mm:ss`. The time zone is undefined (local time).
+    pub fn from_date_time(s: &[u8]) -> Result<ASN1Time, BerError> {
+        let mut r = TimeReader { s, idx: 0 };
+        let (year, month, day) = r.date()?;
+        r.separator(b'T')?;
+        let (hour, minute, second) = r.time_of_day()?;
+        r.end()?;
+        ASN1Time::new(
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            0,
+            ASN1TimeZone::Undefined,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        year: u32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+        nanosecond: u32,
+        tz: ASN1TimeZone,
+    ) -> Result<ASN1Time, BerError> {
+        if !(1..=12).contains(&month)
+            || day < 1
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 60
+        {
+            return Err(BerError::BerValueError);
+        }
+        Ok(ASN1Time {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: hour as u8,
+            minute: minute as u8,
+            second: second as u8,
+            nanosecond,
+            tz,
+        })
+    }
+
+    /// Get the number of seconds since the UNIX epoch (1970-01-01 00:00:00 UTC)
+    ///
+    /// This fails if the time zone is not defined (local time).
+    pub fn timestamp(&self) -> Result<i64, BerError> {
+        let offset = match self.tz {
+            ASN1TimeZone::Undefined => return Err(BerError::BerValueError),
+            ASN1TimeZone::Z => 0,
+            ASN1TimeZone::Offset(hh, mm) => hh as i64 * 3600 + mm as i64 * 60,
+        };
+        let days = days_from_civil(self.year as i64, self.month as i64, self.day as i64);
+        let seconds = self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64;
+        Ok(days * 86400 + seconds - offset)
+    }
+
+    /// Convert to a `SystemTime`
+    ///
+    /// This fails if the time zone is not defined (local time).
+    pub fn to_system_time(&self) -> Result<SystemTime, BerError> {
+        let ts = self.timestamp()?;
+        let t = if ts >= 0 {
+            UNIX_EPOCH.checked_add(Duration::from_secs(ts as u64))
+        } else {
+            UNIX_EPOCH.checked_sub(Duration::from_secs(ts.unsigned_abs()))
+        };
+        t.and_then(|t| t.checked_add(Duration::from_nanos(self.nanosecond as u64)))
+            .ok_or(BerError::BerValueError)
+    }
+}
+
+/// Check the content of a TIME-OF-DAY object (X.680 section 38.4.2)
+///
+/// The syntax is `hh:mm:ss` (local time).
+pub(crate) fn is_valid_time_of_day(s: &[u8]) -> bool {
+    let mut r = TimeReader { s, idx: 0 };
+    match r.time_of_day() {
+        Ok((hour, minute, second)) => r.end().is_ok() && hour <= 23 && minute <= 59 && second <= 60,
+        Err(_) => false,
+    }
+}
+
+/// Check the content of a DURATION object (X.680 section 38.4.4)
+///
+/// The syntax is the ISO 8601 duration format: `PnW`, or `PnYnMnDTnHnMnS` where absent
+/// components are omitted (as well as `T` if there is no time component). Only the last
+/// component can have a fraction (using `.` or `,`).
+pub(crate) fn is_valid_duration(s: &[u8]) -> bool {
+    if s.len() < 3 || s[0] != b'P' {
+        return false;
+    }
+    let mut designators: &[u8] = b"YMWD";
+    let mut idx = 1;
+    let mut count = 0;
+    let mut time = false;
+    let mut fraction = false;
+    while idx < s.len() {
+        // only the last component can have a fraction
+        if fraction {
+            return false;
+        }
+        if s[idx] == b'T' {
+            if time {
+                return false;
+            }
+            time = true;
+            designators = b"HMS";
+            idx += 1;
+            // at least one time component is required after the separator
+            if idx == s.len() {
+                return false;
+            }
+            continue;
+        }
+        let n = s[idx..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if n == 0 {
+            return false;
+        }
+        idx += n;
+        if idx < s.len() && (s[idx] == b'.' || s[idx] == b',') {
+            let n = s[idx + 1..]
+                .iter()
+                .take_while(|c| c.is_ascii_digit())
+                .count();
+            if n == 0 {
+                return false;
+            }
+            idx += n + 1;
+            fraction = true;
+        }
+        let pos = match s
+            .get(idx)
+            .and_then(|d| designators.iter().position(|x| x == d))
+        {
+            Some(pos) => pos,
+            None => return false,
+        };
+        // weeks cannot be combined with other components
+        if designators[pos] == b'W' && (count > 0 || idx + 1 != s.len()) {
+            return false;
+        }
+        designators = &designators[pos + 1..];
+        idx += 1;
+        count += 1;
+    }
+    count > 0
+}
+
+impl fmt::Display for ASN1Time {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )?;
+        if self.nanosecond != 0 {
+            let frac = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", frac.trim_end_matches('0'))?;
+        }
+        match self.tz {
+            ASN1TimeZone::Undefined => Ok(()),
+            ASN1TimeZone::Z => write!(f, " Z"),
+            ASN1TimeZone::Offset(hh, mm) => {
+                let sign = if hh < 0 || mm < 0 { '-' } else { '+' };
+                write!(f, " {}{:02}{:02}", sign, hh.abs(), mm.abs())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_conv {
+    use super::{ASN1Time, ASN1TimeZone};
+    use crate::error::BerError;
+    use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
+
+    impl ASN1Time {
+        /// Convert to a `chrono` date and time, ignoring the time zone
+        pub fn to_chrono_naive(&self) -> Result<NaiveDateTime, BerError> {
+            // chrono represents leap seconds using the nanoseconds
+            let (second, leap) = if self.second == 60 {
+                (59, 1_000_000_000)
+            } else {
+                (self.second as u32, 0)
+            };
+            NaiveDate::from_ymd_opt(self.year as i32, self.month as u32, self.day as u32)
+                .and_then(|d| {
+                    d.and_hms_nano_opt(
+                        self.hour as u32,
+                        self.minute as u32,
+                        second,
+                        self.nanosecond + leap,
+                    )
+                })
+                .ok_or(BerError::BerValueError)
+        }
+
+        /// Convert to a `chrono` date and time, with a fixed offset
+        ///
+        /// This fails if the time zone is not defined (local time).
+        pub fn to_chrono_datetime(&self) -> Result<DateTime<FixedOffset>, BerError> {
+            let offset = match self.tz {
+                ASN1TimeZone::Undefined => None,
+                ASN1TimeZone::Z => FixedOffset::east_opt(0),
+                ASN1TimeZone::Offset(hh, mm) => {
+                    FixedOffset::east_opt(hh as i32 * 3600 + mm as i32 * 60)
+                }
+            };
+            let offset = offset.ok_or(BerError::BerValueError)?;
+            offset
+                .from_local_datetime(&self.to_chrono_naive()?)
+                .single()
+                .ok_or(BerError::BerValueError)
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_conv {
+    use super::{ASN1Time, ASN1TimeZone};
+    use crate::error::BerError;
+    use std::convert::TryFrom;
+    use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+    impl ASN1Time {
+        /// Convert to a `time` date and time, ignoring the time zone
+        ///
+        /// This fails for leap seconds, which are not supported by `time`.
+        pub fn to_primitive_datetime(&self) -> Result<PrimitiveDateTime, BerError> {
+            let month = Month::try_from(self.month).or(Err(BerError::BerValueError))?;
+            let date = Date::from_calendar_date(self.year as i32, month, self.day)
+                .or(Err(BerError::BerValueError))?;
+            let time = Time::from_hms_nano(self.hour, self.minute, self.second, self.nanosecond)
+                .or(Err(BerError::BerValueError))?;
+            Ok(PrimitiveDateTime::new(date, time))
+        }
+
+        /// Convert to a `time` date and time, with an offset
+        ///
+        /// This fails if the time zone is not defined (local time).
+        pub fn to_offset_datetime(&self) -> Result<OffsetDateTime, BerError> {
+            let offset = match self.tz {
+                ASN1TimeZone::Undefined => return Err(BerError::BerValueError),
+                ASN1TimeZone::Z => UtcOffset::UTC,
+                ASN1TimeZone::Offset(hh, mm) => {
+                    UtcOffset::from_hms(hh, mm, 0).or(Err(BerError::BerValueError))?
+                }
+            };
+            Ok(self.to_primitive_datetime()?.assume_offset(offset))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utctime() {
+        let t = ASN1Time::from_utctime(b"021213142923Z").expect("decoding failed");
+        assert_eq!(
+            t,
+            ASN1Time {
+                year: 2002,
+                month: 12,
+                day: 13,
+                hour: 14,
+                minute: 29,
+                second: 23,
+                nanosecond: 0,
+                tz: ASN1TimeZone::Z,
+            }
+        );
+        assert_eq!(t.timestamp(), Ok(1_039_789_763));
+        // pivot year, no seconds, offset
+        let t = ASN1Time::from_utctime(b"5001010000-0130").expect("decoding failed");
+        assert_eq!(t.year, 1950);
+        assert_eq!(t.tz, ASN1TimeZone::Offset(-1, -30));
+        assert_eq!(t.timestamp(), Ok(-631_152_000 + 5400));
+        assert_eq!(
+            ASN1Time::from_utctime(b"491231235959Z").map(|t| t.year),
+            Ok(2049)
+        );
+        // local time is not allowed
+        assert!(ASN1Time::from_utctime(b"021213142923").is_err());
+        assert!(ASN1Time::from_utctime(b"021213142923+01").is_err());
+        assert!(ASN1Time::from_utctime(b"021313142923Z").is_err());
+        assert!(ASN1Time::from_utctime(b"020229142923Z").is_err());
+        assert!(ASN1Time::from_utctime(b"021213142923Zz").is_err());
+    }
+
+    #[test]
+    fn test_generalizedtime() {
+        let t = ASN1Time::from_generalizedtime(b"20000229235960.25Z").expect("decoding failed");
+        assert_eq!(
+            t,
+            ASN1Time {
+                year: 2000,
+                month: 2,
+                day: 29,
+                hour: 23,
+                minute: 59,
+                second: 60,
+                nanosecond: 250_000_000,
+                tz: ASN1TimeZone::Z,
+            }
+        );
+        // fraction of hour, local time
+        let t = ASN1Time::from_generalizedtime(b"1985110621,5").expect("decoding failed");
+        assert_eq!((t.hour, t.minute, t.second), (21, 30, 0));
+        assert_eq!(t.tz, ASN1TimeZone::Undefined);
+        assert_eq!(t.timestamp(), Err(BerError::BerValueError));
+        // fraction of minute, offset with hours only
+        let t = ASN1Time::from_generalizedtime(b"198511062101.1234567891234+05")
+            .expect("decoding failed");
+        assert_eq!((t.minute, t.second, t.nanosecond), (1, 7, 407_407_347));
+        assert_eq!(t.tz, ASN1TimeZone::Offset(5, 0));
+        assert!(ASN1Time::from_generalizedtime(b"19851106210.Z").is_err());
+        assert!(ASN1Time::from_generalizedtime(b"19851106Z").is_err());
+        assert!(ASN1Time::from_generalizedtime(b"19001106240000Z").is_err());
+        assert!(ASN1Time::from_generalizedtime(b"19000229000000Z").is_err());
+    }
+
+    #[test]
+    fn test_date_time_types() {
+        let t = ASN1Time::from_date(b"2000-02-29").expect("decoding failed");
+        assert_eq!((t.year, t.month, t.day, t.hour), (2000, 2, 29, 0));
+        assert_eq!(t.tz, ASN1TimeZone::Undefined);
+        assert!(ASN1Time::from_date(b"2001-02-29").is_err());
+        assert!(ASN1Time::from_date(b"20000229").is_err());
+        let t = ASN1Time::from_date_time(b"1985-11-06T21:06:27").expect("decoding failed");
+        assert_eq!((t.day, t.hour, t.minute, t.second), (6, 21, 6, 27));
+        assert!(ASN1Time::from_date_time(b"1985-11-06 21:06:27").is_err());
+        assert!(ASN1Time::from_date_time(b"1985-11-06T21:06:27Z").is_err());
+        assert!(is_valid_time_of_day(b"23:59:60"));
+        assert!(!is_valid_time_of_day(b"24:00:00"));
+        assert!(!is_valid_time_of_day(b"12:00"));
+    }
+
+    #[test]
+    fn test_duration() {
+        for s in &[
+            "P1Y2M10DT2H30M",
+            "P1YT1S",
+            "P3W",
+            "PT0.5S",
+            "P1DT12H",
+            "PT36H",
+            "P0,5Y",
+        ] {
+            assert!(is_valid_duration(s.as_bytes()), "{}", s);
+        }
+        for s in &[
+            "P", "PT", "P1", "P1DT", "P2M1Y", "P1W2D", "PT1.5H2M", "P1.S", "1D",
+        ] {
+            assert!(!is_valid_duration(s.as_bytes()), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_time_conversions() {
+        let t = ASN1Time::from_generalizedtime(b"19691231235959.5Z").expect("decoding failed");
+        assert_eq!(t.timestamp(), Ok(-1));
+        assert_eq!(
+            t.to_system_time(),
+            Ok(UNIX_EPOCH - Duration::from_millis(500))
+        );
+        assert_eq!(t.to_string(), "1969-12-31 23:59:59.5 Z");
+        let t = ASN1Time::from_utctime(b"7001010100+0100").expect("decoding failed");
+        assert_eq!(t.to_system_time(), Ok(UNIX_EPOCH));
+        assert_eq!(t.to_string(), "1970-01-01 01:00:00 +0100");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_time_chrono() {
+        let t = ASN1Time::from_utctime(b"021213142923+0200").expect("decoding failed");
+        let dt = t.to_chrono_datetime().expect("conversion failed");
+        assert_eq!(dt.timestamp(), t.timestamp().unwrap());
+        assert_eq!(dt.offset().local_minus_utc(), 7200);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time_time() {
+        let t = ASN1Time::from_generalizedtime(b"20021213142923.5-0130").expect("decoding failed");
+        let dt = t.to_offset_datetime().expect("conversion failed");
+        assert_eq!(dt.unix_timestamp(), t.timestamp().unwrap());
+        assert_eq!(dt.nanosecond(), 500_000_000);
+        let t = ASN1Time::from_generalizedtime(b"20021213142923").expect("decoding failed");
+        assert!(t.to_primitive_datetime().is_ok());
+        assert!(t.to_offset_datetime().is_err());
+    }
+}
@@ -0,0 +1,298 @@
+//! Dump BER/DER objects
+//!
+//! Reads an object from a file or from the standard input, and prints it as an annotated tree.
+//! The input can be raw bytes, PEM, base64 or hexadecimal, and is detected automatically.
+
+use der_parser::ber::*;
+use der_parser::der::*;
+use der_parser::error::BerError;
+use der_parser::pem::*;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+
+const USAGE: &str = "\
+Usage: der-dump [OPTIONS] [FILE]
+
+Dump the BER/DER object read from FILE, or from the standard input.
+
+Options:
+    -s, --strict          check the DER constraints, and report the violations
+    -x, --hex             show a hex dump of the content of primitive objects
+    -f, --full            do not truncate long values
+    -e, --extract PATH    write the raw encoding of an element to the standard output,
+                          PATH being a list of indexes separated by dots (e.g. 0.2.1)
+    -i, --input FORMAT    input format: auto (default), pem, base64, hex or der
+    -h, --help            show this help";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum InputFormat {
+    Auto,
+    Pem,
+    Base64,
+    Hex,
+    Der,
+}
+
+#[derive(Debug)]
+struct Options {
+    strict: bool,
+    hex: bool,
+    full: bool,
+    extract: Option<Vec<usize>>,
+    format: InputFormat,
+    file: Option<String>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        strict: false,
+        hex: false,
+        full: false,
+        extract: None,
+        format: InputFormat::Auto,
+        file: None,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "-s" | "--strict" => options.strict = true,
+            "-x" | "--hex" => options.hex = true,
+            "-f" | "--full" => options.full = true,
+            "-e" | "--extract" => {
+                let path = args.next().ok_or("missing path")?;
+                options.extract = Some(parse_path(&path)?);
+            }
+            "-i" | "--input" => {
+                let format = args.next().ok_or("missing input format")?;
+                options.format = match format.as_ref() {
+                    "auto" => InputFormat::Auto,
+                    "pem" => InputFormat::Pem,
+                    "base64" => InputFormat::Base64,
+                    "hex" => InputFormat::Hex,
+                    "der" | "ber" | "raw" => InputFormat::Der,
+                    _ => return Err(format!("invalid input format '{}'", format)),
+                };
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option '{}'", arg));
+            }
+            _ => {
+                if options.file.is_some() {
+                    return Err("only one input file can be given".to_string());
+                }
+                options.file = Some(arg);
+            }
+        }
+    }
+    Ok(options)
+}
+
+/// Parse a path of the form `0.2.1`
+fn parse_path(s: &str) -> Result<Vec<usize>, String> {
+    if s.is_empty() {
+        return Ok(Vec::new());
+    }
+    s.split('.')
+        .map(|n| n.parse().map_err(|_| format!("invalid path '{}'", s)))
+        .collect()
+}
+
+fn read_input(file: &Option<String>) -> io::Result<Vec<u8>> {
+    match file {
+        Some(f) if f != "-" => fs::read(f),
+        _ => {
+            let mut data = Vec::new();
+            io::stdin().read_to_end(&mut data)?;
+            Ok(data)
+        }
+    }
+}
+
+fn detect_format(data: &[u8]) -> InputFormat {
+    let start = data.iter().take_while(|b| b.is_ascii_whitespace()).count();
+    if data[start..].starts_with(b"-----BEGIN ") {
+        InputFormat::Pem
+    } else if data.is_empty() {
+        InputFormat::Der
+    } else if data
+        .iter()
+        .all(|b| b.is_ascii_hexdigit() || b.is_ascii_whitespace())
+    {
+        InputFormat::Hex
+    } else if decode_base64(data, PemMode::Lax).is_ok() {
+        InputFormat::Base64
+    } else {
+        InputFormat::Der
+    }
+}
+
+/// Decode the input according to its format
+fn decode_input(data: Vec<u8>, format: InputFormat) -> Result<Vec<u8>, String> {
+    match format {
+        InputFormat::Auto => decode_input(data.clone(), detect_format(&data)),
+        InputFormat::Pem => parse_pem(&data, PemMode::Lax)
+            .map(|pem| pem.contents)
+            .map_err(|e| e.to_string()),
+        InputFormat::Base64 => decode_base64(&data, PemMode::Lax).map_err(|e| e.to_string()),
+        InputFormat::Hex => decode_hex(&data),
+        InputFormat::Der => Ok(data),
+    }
+}
+
+fn decode_hex(data: &[u8]) -> Result<Vec<u8>, String> {
+    let digits: Vec<u8> = data
+        .iter()
+        .filter(|b| !b.is_ascii_whitespace())
+        .map(|&b| (b as char).to_digit(16).map(|d| d as u8))
+        .collect::<Option<_>>()
+        .ok_or("invalid hex data")?;
+    let pairs = digits.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return Err("odd number of hex digits".to_string());
+    }
+    Ok(pairs.map(|c| (c[0] << 4) | c[1]).collect())
+}
+
+/// Parse the content of constructed context-specific and application objects, which is not
+/// parsed by `parse_ber`, and shift the offsets of the objects by `base`
+///
+/// Like the parsers, the recursion is limited to `MAX_RECURSION` levels: deeper objects are not
+/// expanded.
+fn expand(obj: &mut BerObject, base: usize, depth: usize) {
+    if let Some(ref mut loc) = obj.location {
+        loc.offset += base;
+    }
+    let start = obj.location.map_or(0, |loc| loc.offset + loc.header_len);
+    match obj.content {
+        BerObjectContent::Sequence(ref mut v)
+        | BerObjectContent::Set(ref mut v)
+        | BerObjectContent::External(ref mut v)
+        | BerObjectContent::EmbeddedPdv(ref mut v)
+        | BerObjectContent::CharacterString(ref mut v) => {
+            v.iter_mut().for_each(|o| expand(o, base, depth + 1))
+        }
+        BerObjectContent::Segmented(_, ref mut s) => s
+            .segments
+            .iter_mut()
+            .for_each(|o| expand(o, base, depth + 1)),
+        BerObjectContent::ContextSpecific(_, Some(ref mut o)) => expand(o, base, depth + 1),
+        BerObjectContent::Unknown(_, data) if obj.is_constructed() && depth < MAX_RECURSION => {
+            let options = ParserOptions {
+                max_depth: MAX_RECURSION - depth - 1,
+                ..ParserOptions::default()
+            };
+            let mut v = Vec::new();
+            let mut rem = data;
+            while !rem.is_empty() {
+                // the offsets are relative to the start of each element
+                let offset = start + data.len() - rem.len();
+                match parse_ber_with_options(rem, &options) {
+                    Ok((r, mut o)) => {
+                        expand(&mut o, offset, depth + 1);
+                        v.push(o);
+                        rem = r;
+                    }
+                    Err(_) => return,
+                }
+            }
+            obj.content = BerObjectContent::Sequence(v);
+        }
+        _ => (),
+    }
+}
+
+/// Elements of a constructed object
+fn children<'a, 'b>(obj: &'b BerObject<'a>) -> Result<&'b [BerObject<'a>], String> {
+    match obj.content {
+        BerObjectContent::Sequence(ref v)
+        | BerObjectContent::Set(ref v)
+        | BerObjectContent::External(ref v)
+        | BerObjectContent::EmbeddedPdv(ref v)
+        | BerObjectContent::CharacterString(ref v) => Ok(v),
+        BerObjectContent::Segmented(_, ref s) => Ok(&s.segments),
+        BerObjectContent::ContextSpecific(_, Some(ref o)) => Ok(std::slice::from_ref(o)),
+        _ => Err("not a constructed object".to_string()),
+    }
+}
+
+fn error_message(e: nom::Err<BerError>) -> String {
+    match e {
+        nom::Err::Error(e) | nom::Err::Failure(e) => e.to_string(),
+        nom::Err::Incomplete(_) => "incomplete data".to_string(),
+    }
+}
+
+/// Find the element at `path`, and return its raw encoding
+fn extract<'a>(obj: &BerObject<'a>, path: &[usize]) -> Result<&'a [u8], String> {
+    match path.split_first() {
+        None => obj.raw().ok_or_else(|| "missing raw encoding".to_string()),
+        Some((&idx, rest)) => {
+            let v = children(obj)?;
+            let child = v
+                .get(idx)
+                .ok_or_else(|| format!("index {} out of range ({} elements)", idx, v.len()))?;
+            extract(child, rest)
+        }
+    }
+}
+
+fn dump(data: &[u8], options: &Options) -> Result<bool, String> {
+    let (rem, mut obj) =
+        parse_ber(data).map_err(|e| format!("parsing failed: {}", error_message(e)))?;
+    expand(&mut obj, 0, 0);
+    if let Some(ref path) = options.extract {
+        let raw = extract(&obj, path).map_err(|e| format!("cannot extract {:?}: {}", path, e))?;
+        io::stdout().write_all(raw).map_err(|e| e.to_string())?;
+        return Ok(true);
+    }
+    let mut pp = obj.as_pretty(0, 2);
+    if options.hex {
+        pp.set_flag(PrettyPrinterFlag::HexDump);
+    }
+    if options.full {
+        pp.set_flag(PrettyPrinterFlag::NoTruncate);
+    }
+    print!("{}", pp);
+    if !rem.is_empty() {
+        println!("{} trailing bytes", rem.len());
+    }
+    if !options.strict {
+        return Ok(true);
+    }
+    let mut valid = rem.is_empty();
+    if let Err(e) = parse_der(data) {
+        println!("DER parsing failed: {}", error_message(e));
+        valid = false;
+    }
+    for v in der_violations(&obj) {
+        println!("DER violation: {}", v);
+        valid = false;
+    }
+    if valid {
+        println!("DER encoding is valid");
+    }
+    Ok(valid)
+}
+
+fn run() -> Result<bool, String> {
+    let options = parse_args().map_err(|e| format!("{}\n\n{}", e, USAGE))?;
+    let data = read_input(&options.file).map_err(|e| format!("cannot read input: {}", e))?;
+    let data = decode_input(data, options.format)?;
+    dump(&data, &options)
+}
+
+fn main() {
+    match run() {
+        Ok(true) => (),
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            eprintln!("der-dump: {}", e);
+            process::exit(2);
+        }
+    }
+}
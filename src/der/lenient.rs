@@ -0,0 +1,320 @@
+use crate::ber::*;
+use crate::der::*;
+use crate::error::BerError;
+use nom::IResult;
+use std::fmt;
+
+/// DER constraints (X.690 sections 10 and 11) verified by the lenient parser
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DerRule {
+    /// The definite form of length encoding shall be used
+    IndefiniteLength,
+    /// The length shall be encoded in the minimum number of octets
+    NonMinimalLength,
+    /// The tag number shall be encoded in the minimum number of octets
+    NonMinimalTag,
+    /// The end-of-content marker is only used with the indefinite form of length
+    EndOfContent,
+    /// Primitive types shall use the primitive form
+    ConstructedPrimitive,
+    /// The content of a constructed object shall be a series of encodings
+    ConstructedContent,
+    /// The value TRUE of a BOOLEAN shall be encoded as `0xff`
+    BooleanValue,
+    /// An INTEGER or ENUMERATED shall have at least one content octet
+    EmptyInteger,
+    /// An INTEGER or ENUMERATED shall be encoded in the minimum number of octets
+    NonMinimalInteger,
+    /// The subidentifiers of an OBJECT IDENTIFIER or RELATIVE-OID shall be encoded in the minimum
+    /// number of octets
+    NonMinimalOid,
+    /// The number of unused bits of a BIT STRING shall be between 0 and 7
+    BitStringUnusedBits,
+    /// The unused bits of a BIT STRING shall be set to zero
+    BitStringPadding,
+    /// String types shall use the primitive form
+    ConstructedString,
+    /// The elements of a SET or SET OF shall be sorted
+    SetOrder,
+    /// A REAL shall use the canonical encoding
+    RealEncoding,
+    /// A UTCTime shall be expressed in UTC, with seconds
+    UtcTimeFormat,
+    /// A GeneralizedTime shall be expressed in UTC, with seconds and no trailing zeroes
+    GeneralizedTimeFormat,
+    /// A DATE shall use the `YYYY-MM-DD` format
+    DateFormat,
+    /// A TIME-OF-DAY shall use the `hh:mm:ss` format
+    TimeOfDayFormat,
+    /// A DATE-TIME shall use the `YYYY-MM-DDThh:mm:ss` format
+    DateTimeFormat,
+    /// A DURATION shall use the ISO 8601 duration format
+    DurationFormat,
+}
+
+impl DerRule {
+    /// Section of X.690 defining the rule
+    pub fn reference(self) -> &'static str {
+        match self {
+            DerRule::IndefiniteLength | DerRule::NonMinimalLength => "10.1",
+            DerRule::EndOfContent => "8.1.5",
+            DerRule::NonMinimalTag => "8.1.2.4.2",
+            DerRule::ConstructedPrimitive => "8.1.2.5",
+            DerRule::ConstructedContent => "8.1.4",
+            DerRule::BooleanValue => "11.1",
+            DerRule::EmptyInteger => "8.3.1",
+            DerRule::NonMinimalInteger => "8.3.2",
+            DerRule::NonMinimalOid => "8.19.2",
+            DerRule::BitStringUnusedBits => "8.6.2.2",
+            DerRule::BitStringPadding => "11.2",
+            DerRule::ConstructedString => "10.2",
+            DerRule::SetOrder => "11.6",
+            DerRule::RealEncoding => "11.3",
+            DerRule::UtcTimeFormat => "11.8",
+            DerRule::GeneralizedTimeFormat => "11.7",
+            DerRule::DateFormat
+            | DerRule::TimeOfDayFormat
+            | DerRule::DateTimeFormat
+            | DerRule::DurationFormat => "8.26",
+        }
+    }
+}
+
+impl fmt::Display for DerRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            DerRule::IndefiniteLength => "indefinite length",
+            DerRule::NonMinimalLength => "length not minimally encoded",
+            DerRule::NonMinimalTag => "tag not minimally encoded",
+            DerRule::EndOfContent => "unexpected end-of-content",
+            DerRule::ConstructedPrimitive => "constructed primitive type",
+            DerRule::ConstructedContent => "invalid content of constructed object",
+            DerRule::BooleanValue => "invalid boolean value",
+            DerRule::EmptyInteger => "empty integer",
+            DerRule::NonMinimalInteger => "integer not minimally encoded",
+            DerRule::NonMinimalOid => "OID subidentifier not minimally encoded",
+            DerRule::BitStringUnusedBits => "invalid number of unused bits",
+            DerRule::BitStringPadding => "unused bits not set to zero",
+            DerRule::ConstructedString => "constructed string",
+            DerRule::SetOrder => "set elements not sorted",
+            DerRule::RealEncoding => "non-canonical real",
+            DerRule::UtcTimeFormat => "invalid UTCTime",
+            DerRule::GeneralizedTimeFormat => "invalid GeneralizedTime",
+            DerRule::DateFormat => "invalid DATE",
+            DerRule::TimeOfDayFormat => "invalid TIME-OF-DAY",
+            DerRule::DateTimeFormat => "invalid DATE-TIME",
+            DerRule::DurationFormat => "invalid DURATION",
+        };
+        write!(f, "{} (X.690 {})", s, self.reference())
+    }
+}
+
+/// Violation of a DER constraint by an object
+#[derive(Clone, Debug, PartialEq)]
+pub struct DerViolation {
+    /// Offset of the object, relative to the input
+    pub offset: usize,
+    pub class: u8,
+    pub tag: BerTag,
+    pub rule: DerRule,
+}
+
+impl fmt::Display for DerViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.rule, self.offset)
+    }
+}
+
+/// Parse a DER object leniently
+///
+/// The object is parsed using the BER rules, and the violations of the DER constraints are
+/// returned with the object, instead of causing an error.
+///
+/// ```rust
+/// # extern crate der_parser;
+/// # use der_parser::der::*;
+/// # fn main() {
+/// // boolean TRUE is encoded as 0x01, and the length of the sequence uses the long form
+/// let bytes = [0x30, 0x81, 0x03, 0x01, 0x01, 0x01];
+/// let (_, (obj, violations)) = parse_der_lenient(&bytes).expect("parsing failed");
+/// assert_eq!(obj.as_sequence().map(|v| v.len()), Ok(1));
+/// assert_eq!(violations.len(), 2);
+/// assert_eq!(violations[0].rule, DerRule::NonMinimalLength);
+/// assert_eq!(violations[1].offset, 3);
+/// assert_eq!(violations[1].rule.reference(), "11.1");
+/// # }
+/// ```
+pub fn parse_der_lenient(i: &[u8]) -> IResult<&[u8], (DerObject<'_>, Vec<DerViolation>), BerError> {
+    let (rem, obj) = parse_ber(i)?;
+    let violations = der_violations(&obj);
+    Ok((rem, (obj, violations)))
+}
+
+/// Check the DER constraints of an object returned by a BER parser, and of all the objects it
+/// contains
+///
+/// Like [`parse_der`](fn.parse_der.html), the content of constructed objects of unknown type (for
+/// ex. EXPLICIT tagged values) is checked as a series of DER objects. Objects built without
+/// parsing are not checked.
+pub fn der_violations(obj: &BerObject) -> Vec<DerViolation> {
+    let mut v = Vec::new();
+    der_check_object(obj, 0, &mut v);
+    v
+}
+
+fn der_check_object(obj: &BerObject, depth: usize, v: &mut Vec<DerViolation>) {
+    let loc = match obj.location {
+        Some(loc) => loc,
+        None => return,
+    };
+    let mut add = |rule| {
+        v.push(DerViolation {
+            offset: loc.offset,
+            class: obj.class,
+            tag: obj.tag,
+            rule,
+        })
+    };
+    let (hdr, content) = loc.raw.split_at(loc.header_len);
+    // identifier octets
+    let mut idx = 1;
+    if hdr[0] & 0x1f == 0x1f {
+        if obj.tag.0 < 0x1f || hdr[1] == 0x80 {
+            add(DerRule::NonMinimalTag);
+        }
+        idx += hdr[1..].iter().take_while(|&&b| b & 0x80 != 0).count() + 1;
+    }
+    // length octets
+    match hdr[idx] {
+        0x80 => add(DerRule::IndefiniteLength),
+        b if b > 0x80 => {
+            let llen = &hdr[idx + 1..];
+            if llen[0] == 0 || (llen.len() == 1 && llen[0] < 0x80) {
+                add(DerRule::NonMinimalLength);
+            }
+        }
+        _ => (),
+    }
+    if obj.is_constructed() {
+        match obj.content {
+            BerObjectContent::Sequence(_)
+            | BerObjectContent::Set(_)
+            | BerObjectContent::External(_)
+            | BerObjectContent::EmbeddedPdv(_)
+            | BerObjectContent::CharacterString(_)
+            | BerObjectContent::Segmented(..)
+            | BerObjectContent::ContextSpecific(..)
+            | BerObjectContent::Unknown(..) => (),
+            _ => add(DerRule::ConstructedPrimitive),
+        }
+    }
+    match obj.content {
+        BerObjectContent::EndOfContent if obj.is_universal() => add(DerRule::EndOfContent),
+        BerObjectContent::Boolean(_) if content[0] != 0 && content[0] != 0xff => {
+            add(DerRule::BooleanValue)
+        }
+        BerObjectContent::Integer(_) | BerObjectContent::Enum(_) if content.is_empty() => {
+            add(DerRule::EmptyInteger)
+        }
+        BerObjectContent::Integer(_) | BerObjectContent::Enum(_) if !der_check_integer(content) => {
+            add(DerRule::NonMinimalInteger)
+        }
+        BerObjectContent::OID(_) | BerObjectContent::RelativeOID(_) if !der_check_oid(content) => {
+            add(DerRule::NonMinimalOid)
+        }
+        BerObjectContent::BitString(unused, _) if unused > 7 => add(DerRule::BitStringUnusedBits),
+        BerObjectContent::BitString(unused, ref b) => {
+            let mask = (1u8 << unused) - 1;
+            let padding = match b.data.last() {
+                Some(last) => last & mask,
+                None => unused,
+            };
+            if padding != 0 {
+                add(DerRule::BitStringPadding);
+            }
+        }
+        BerObjectContent::Segmented(..) => add(DerRule::ConstructedString),
+        BerObjectContent::Real(_) if !der_check_real(content) => add(DerRule::RealEncoding),
+        BerObjectContent::UTCTime(s) if !der_check_utctime(s) => add(DerRule::UtcTimeFormat),
+        BerObjectContent::GeneralizedTime(s) if !der_check_generalizedtime(s) => {
+            add(DerRule::GeneralizedTimeFormat)
+        }
+        BerObjectContent::Date(s) if ASN1Time::from_date(s).is_err() => add(DerRule::DateFormat),
+        BerObjectContent::TimeOfDay(s) if !is_valid_time_of_day(s) => add(DerRule::TimeOfDayFormat),
+        BerObjectContent::DateTime(s) if ASN1Time::from_date_time(s).is_err() => {
+            add(DerRule::DateTimeFormat)
+        }
+        BerObjectContent::Duration(s) if !is_valid_duration(s) => add(DerRule::DurationFormat),
+        _ => (),
+    }
+    match obj.content {
+        BerObjectContent::Set(ref l) => {
+            let sorted = l.windows(2).all(|w| match (w[0].raw(), w[1].raw()) {
+                (Some(a), Some(b)) => der_check_set_order(a, b),
+                _ => true,
+            });
+            if !sorted {
+                add(DerRule::SetOrder);
+            }
+            for o in l {
+                der_check_object(o, depth + 1, v);
+            }
+        }
+        BerObjectContent::Sequence(ref l)
+        | BerObjectContent::External(ref l)
+        | BerObjectContent::EmbeddedPdv(ref l)
+        | BerObjectContent::CharacterString(ref l) => {
+            for o in l {
+                der_check_object(o, depth + 1, v);
+            }
+        }
+        BerObjectContent::ContextSpecific(_, Some(ref o)) => der_check_object(o, depth + 1, v),
+        BerObjectContent::Unknown(_, data) if obj.is_constructed() => {
+            let offset = loc.offset + loc.header_len;
+            if !der_check_unknown_content(data, depth + 1, offset, v) {
+                v.push(DerViolation {
+                    offset: loc.offset,
+                    class: obj.class,
+                    tag: obj.tag,
+                    rule: DerRule::ConstructedContent,
+                });
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Check the content of a constructed object of unknown type, located at `offset`, as a series
+/// of objects
+///
+/// Returns `false` if the content cannot be parsed.
+fn der_check_unknown_content(
+    data: &[u8],
+    depth: usize,
+    offset: usize,
+    v: &mut Vec<DerViolation>,
+) -> bool {
+    if depth > MAX_RECURSION {
+        return false;
+    }
+    let options = ParserOptions {
+        max_depth: MAX_RECURSION - depth,
+        ..ParserOptions::default()
+    };
+    let mut rem = data;
+    while !rem.is_empty() {
+        let (r, o) = match parse_ber_with_options(rem, &options) {
+            Ok(res) => res,
+            Err(_) => return false,
+        };
+        let base = offset + data.len() - rem.len();
+        let mut inner = Vec::new();
+        der_check_object(&o, depth, &mut inner);
+        v.extend(inner.into_iter().map(|x| DerViolation {
+            offset: x.offset + base,
+            ..x
+        }));
+        rem = r;
+    }
+    true
+}
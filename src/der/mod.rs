@@ -2,7 +2,9 @@
 
 use crate::ber::{BerObject, BerTag};
 
+mod lenient;
 mod parser;
+pub use crate::der::lenient::*;
 pub use crate::der::parser::*;
 
 /// DER tag (same as BER tag)
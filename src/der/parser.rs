@@ -3,17 +3,53 @@ use crate::der::DerObject;
 use crate::error::*;
 use nom::number::streaming::be_u8;
 use nom::{Err, IResult, Needed};
+use std::cmp::Ordering;
 
 /// Parse DER object
+///
+/// Read an object recursively, verifying the DER constraints (X.690 sections 10 and 11) for the
+/// object and all the objects it contains.
+///
+/// The location of the object and of all the objects it contains is recorded, relative to `i`.
+#[inline]
 pub fn parse_der(i: &[u8]) -> IResult<&[u8], DerObject, BerError> {
-    do_parse! {
+    parse_der_with_options(i, &ParserOptions::default())
+}
+
+/// Parse DER object, using the given limits
+///
+/// Like [`parse_der`](fn.parse_der.html), but the parser fails with a specific error (for ex.
+/// `MaxObjectsExceeded`) if the object exceeds one of the limits set in `options`.
+pub fn parse_der_with_options<'a>(
+    i: &'a [u8],
+    options: &ParserOptions,
+) -> IResult<&'a [u8], DerObject<'a>, BerError> {
+    let st = ParserState::new(options);
+    let (rem, mut obj) = parse_der_recursive(i, 0, &st)?;
+    obj.set_offsets(i);
+    Ok((rem, obj))
+}
+
+fn parse_der_recursive<'a>(
+    i: &'a [u8],
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], DerObject<'a>, BerError> {
+    st.check_depth(depth)?;
+    let (rem, hdr) = der_read_element_header(i)?;
+    // DER(10.1): only the definite form of length is used, so there is no end-of-content marker
+    custom_check!(
         i,
-        hdr:     der_read_element_header >>
-                 // XXX safety check: length cannot be more than 2^32 bytes
-                 custom_check!(hdr.len > ::std::u32::MAX as u64, BerError::InvalidLength) >>
-        content: call!(der_read_element_content,hdr) >>
-        ( content )
-    }
+        hdr.is_universal() && hdr.tag == BerTag::EndOfContent,
+        BerError::DerConstraintFailed
+    )?;
+    st.check_length(der_get_length(hdr.len)?)?;
+    st.add_object()?;
+    let header_len = i.len() - rem.len();
+    let (rem, obj) = der_read_element_content_recursive(rem, hdr, depth, st)
+        .map_err(|e| e.map(|e| e.with_frame(i, rem, hdr.class, hdr.tag, None)))?;
+    let raw = &i[..i.len() - rem.len()];
+    Ok((rem, obj.with_raw(raw, header_len)))
 }
 
 #[doc(hidden)]
@@ -30,13 +66,18 @@ macro_rules! der_constraint_fail_if(
 
 /// Parse a DER object, expecting a value with specificed tag
 pub fn parse_der_with_tag(i: &[u8], tag: BerTag) -> IResult<&[u8], BerObject, BerError> {
-    do_parse! {
-        i,
-        hdr: der_read_element_header >>
-             custom_check!(hdr.tag != tag, BerError::InvalidTag) >>
-        o:   call!(der_read_element_content_as, hdr.tag, hdr.len as usize, hdr.is_constructed(), 0) >>
-        ( BerObject::from_header_and_content(hdr, o) )
+    let (rem, hdr) = der_read_element_header(i)?;
+    let header_len = i.len() - rem.len();
+    if hdr.tag != tag {
+        let e = BerError::InvalidTag.with_frame(i, i, hdr.class, tag, None);
+        return Err(Err::Error(e));
     }
+    let (rem, o) = der_read_element_content_as(rem, hdr.tag, hdr.len, hdr.is_constructed(), 0)
+        .map_err(|e| e.map(|e| e.with_frame(i, rem, hdr.class, tag, None)))?;
+    let mut obj =
+        BerObject::from_header_and_content(hdr, o).with_raw(&i[..i.len() - rem.len()], header_len);
+    obj.set_offsets(i);
+    Ok((rem, obj))
 }
 
 /// Read end of content marker
@@ -62,7 +103,11 @@ pub fn parse_der_bool(i: &[u8]) -> IResult<&[u8], DerObject, BerError> {
 /// The encoding of a boolean value shall be primitive. The contents octets shall consist of one or
 /// more octets.
 ///
+/// In DER, the value must be encoded using the minimum number of octets: the first octet cannot
+/// be `0x00` (or `0xff`) if the high bit of the second octet is cleared (or set).
+///
 /// To access the content, use the [`as_u64`](struct.BerObject.html#method.as_u64),
+/// [`as_i64`](struct.BerObject.html#method.as_i64),
 /// [`as_u32`](struct.BerObject.html#method.as_u32),
 /// [`as_biguint`](struct.BerObject.html#method.as_biguint) or
 /// [`as_bigint`](struct.BerObject.html#method.as_bigint) methods.
@@ -94,14 +139,7 @@ pub fn parse_der_integer(i: &[u8]) -> IResult<&[u8], DerObject, BerError> {
 
 /// Read an bitstring value
 pub fn parse_der_bitstring(i: &[u8]) -> IResult<&[u8], DerObject, BerError> {
-    do_parse! {
-        i,
-        hdr: der_read_element_header >>
-             custom_check!(hdr.tag != BerTag::BitString, BerError::InvalidTag) >>
-             custom_check!(hdr.is_constructed(), BerError::DerConstraintFailed) >>
-        b:   call!(der_read_content_bitstring, hdr.len as usize) >>
-        ( DerObject::from_header_and_content(hdr, b) )
-    }
+    parse_der_with_tag(i, BerTag::BitString)
 }
 
 /// Read an octetstring value
@@ -122,12 +160,38 @@ pub fn parse_der_oid(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
     parse_der_with_tag(i, BerTag::Oid)
 }
 
+/// Read an object descriptor value
+#[inline]
+pub fn parse_der_objectdescriptor(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::ObjDescriptor)
+}
+
+/// Read an EXTERNAL value
+#[inline]
+pub fn parse_der_external(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::External)
+}
+
+/// Read a real value
+///
+/// The value must use one of the canonical encodings defined in X.690 section 11.3.
+#[inline]
+pub fn parse_der_real(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::RealType)
+}
+
 /// Read an enumerated value
 #[inline]
 pub fn parse_der_enum(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
     parse_der_with_tag(i, BerTag::Enumerated)
 }
 
+/// Read an EMBEDDED PDV value
+#[inline]
+pub fn parse_der_embedded_pdv(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::EmbeddedPdv)
+}
+
 /// Read a UTF-8 string value
 #[inline]
 pub fn parse_der_utf8string(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
@@ -140,6 +204,24 @@ pub fn parse_der_relative_oid(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
     parse_der_with_tag(i, BerTag::RelativeOid)
 }
 
+/// Read a TIME value
+#[inline]
+pub fn parse_der_time(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::Time)
+}
+
+/// Read an OID-IRI value
+#[inline]
+pub fn parse_der_oid_iri(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::OidIri)
+}
+
+/// Read a RELATIVE-OID-IRI value
+#[inline]
+pub fn parse_der_relative_oid_iri(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::RelativeOidIri)
+}
+
 /// Parse a sequence of DER elements
 ///
 /// Read a sequence of DER objects, without any constraint on the types.
@@ -184,6 +266,12 @@ pub fn parse_der_t61string(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
     parse_der_with_tag(i, BerTag::T61String)
 }
 
+/// Read a Videotex string value
+#[inline]
+pub fn parse_der_videotexstring(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::VideotexString)
+}
+
 /// Read an IA5 string value
 #[inline]
 pub fn parse_der_ia5string(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
@@ -202,18 +290,74 @@ pub fn parse_der_generalizedtime(i: &[u8]) -> IResult<&[u8], BerObject, BerError
     parse_der_with_tag(i, BerTag::GeneralizedTime)
 }
 
+/// Read a GraphicString value
+#[inline]
+pub fn parse_der_graphicstring(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::GraphicString)
+}
+
+/// Read a VisibleString value
+#[inline]
+pub fn parse_der_visiblestring(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::VisibleString)
+}
+
 /// Read a GeneralString value
 #[inline]
 pub fn parse_der_generalstring(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
     parse_der_with_tag(i, BerTag::GeneralString)
 }
 
+/// Read a UniversalString value
+#[inline]
+pub fn parse_der_universalstring(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::UniversalString)
+}
+
+/// Read a CHARACTER STRING value
+#[inline]
+pub fn parse_der_characterstring(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::CharacterString)
+}
+
 /// Read a BmpString value
 #[inline]
 pub fn parse_der_bmpstring(i: &[u8]) -> IResult<&[u8], BerObject, BerError> {
     parse_der_with_tag(i, BerTag::BmpString)
 }
 
+/// Read a DATE value
+///
+/// The value must use the `YYYY-MM-DD` format, and be a valid date.
+#[inline]
+pub fn parse_der_date(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::Date)
+}
+
+/// Read a TIME-OF-DAY value
+///
+/// The value must use the `hh:mm:ss` format.
+#[inline]
+pub fn parse_der_timeofday(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::TimeOfDay)
+}
+
+/// Read a DATE-TIME value
+///
+/// The value must use the `YYYY-MM-DDThh:mm:ss` format.
+#[inline]
+pub fn parse_der_datetime(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::DateTime)
+}
+
+/// Read a DURATION value
+///
+/// The value must use the ISO 8601 duration format (for ex. `P1Y2M10DT2H30M`).
+#[inline]
+pub fn parse_der_duration(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+    parse_der_with_tag(i, BerTag::Duration)
+}
+
 #[inline]
 pub fn parse_der_explicit<F>(i: &[u8], tag: BerTag, f: F) -> IResult<&[u8], DerObject, BerError>
 where
@@ -225,46 +369,23 @@ where
 #[inline]
 pub fn parse_der_implicit<F>(i: &[u8], tag: BerTag, f: F) -> IResult<&[u8], DerObject, BerError>
 where
-    F: Fn(&[u8], BerTag, usize) -> IResult<&[u8], BerObjectContent, BerError>,
+    F: Fn(&[u8], BerTag, BerSize) -> IResult<&[u8], BerObjectContent, BerError>,
 {
     parse_ber_implicit(i, tag, f)
 }
 
 /// Parse DER object and try to decode it as a 32-bits unsigned integer
 pub fn parse_der_u32(i: &[u8]) -> IResult<&[u8], u32, BerError> {
-    match parse_ber_integer(i) {
-        Ok((rem, ref obj)) => match obj.content {
-            BerObjectContent::Integer(i) => match i.len() {
-                1 => Ok((rem, i[0] as u32)),
-                2 => Ok((rem, (i[0] as u32) << 8 | (i[1] as u32))),
-                3 => Ok((
-                    rem,
-                    (i[0] as u32) << 16 | (i[1] as u32) << 8 | (i[2] as u32),
-                )),
-                4 => Ok((
-                    rem,
-                    (i[0] as u32) << 24 | (i[1] as u32) << 16 | (i[2] as u32) << 8 | (i[3] as u32),
-                )),
-                _ => Err(Err::Error(BerError::IntegerTooLarge)),
-            },
-            _ => Err(Err::Error(BerError::InvalidTag)),
-        },
-        Err(e) => Err(e),
-    }
+    let (rem, obj) = parse_der_integer(i)?;
+    let v = obj.as_u32()?;
+    Ok((rem, v))
 }
 
 /// Parse DER object and try to decode it as a 64-bits unsigned integer
 pub fn parse_der_u64(i: &[u8]) -> IResult<&[u8], u64, BerError> {
-    match parse_ber_integer(i) {
-        Ok((rem, ref obj)) => match obj.content {
-            BerObjectContent::Integer(i) => match bytes_to_u64(i) {
-                Ok(l) => Ok((rem, l)),
-                Err(_) => Err(Err::Error(BerError::IntegerTooLarge)),
-            },
-            _ => Err(Err::Error(BerError::InvalidTag)),
-        },
-        Err(e) => Err(e),
-    }
+    let (rem, obj) = parse_der_integer(i)?;
+    let v = obj.as_u64()?;
+    Ok((rem, v))
 }
 
 // --------- end of parse_der_xxx functions ----------
@@ -275,47 +396,155 @@ pub fn parse_der_u64(i: &[u8]) -> IResult<&[u8], u64, BerError> {
 pub fn der_read_element_content_as(
     i: &[u8],
     tag: BerTag,
-    len: usize,
+    len: BerSize,
     constructed: bool,
     depth: usize,
 ) -> IResult<&[u8], BerObjectContent, BerError> {
+    der_read_element_content_with_state(i, tag, len, constructed, depth, &ParserState::default())
+}
+
+fn der_read_element_content_with_state<'a>(
+    i: &'a [u8],
+    tag: BerTag,
+    len: BerSize,
+    constructed: bool,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    let len = der_get_length(len)?;
     if i.len() < len {
         return Err(Err::Incomplete(Needed::Size(len)));
     }
     match tag {
+        BerTag::EndOfContent | BerTag::Null | BerTag::OidIri | BerTag::RelativeOidIri
+            if constructed =>
+        {
+            return Err(Err::Error(BerError::DerConstraintFailed));
+        }
         BerTag::Boolean => {
+            der_constraint_fail_if!(i, constructed);
             custom_check!(i, len != 1, BerError::InvalidLength)?;
             der_constraint_fail_if!(i, i[0] != 0 && i[0] != 0xff);
         }
+        BerTag::Integer | BerTag::Enumerated => {
+            der_constraint_fail_if!(i, constructed);
+            // X.690 section 8.3: at least one octet, and no redundant leading octet
+            custom_check!(i, len == 0, BerError::IntegerEmpty)?;
+            custom_check!(
+                i,
+                !der_check_integer(&i[..len]),
+                BerError::IntegerNonMinimal
+            )?;
+        }
+        BerTag::RealType => {
+            der_constraint_fail_if!(i, constructed);
+            der_constraint_fail_if!(i, !der_check_real(&i[..len]));
+        }
+        BerTag::Oid | BerTag::RelativeOid => {
+            der_constraint_fail_if!(i, constructed);
+            der_constraint_fail_if!(i, !der_check_oid(&i[..len]));
+        }
         BerTag::BitString => {
             der_constraint_fail_if!(i, constructed);
+            st.check_primitive(tag, &i[..len])?;
             // exception: read and verify padding bits
             return der_read_content_bitstring(i, len);
         }
+        BerTag::OctetString | BerTag::GeneralString if constructed => {
+            return Err(Err::Error(BerError::DerConstraintFailed));
+        }
         BerTag::NumericString
         | BerTag::PrintableString
         | BerTag::Ia5String
+        | BerTag::VisibleString
         | BerTag::Utf8String
         | BerTag::T61String
+        | BerTag::VideotexString
+        | BerTag::GraphicString
+        | BerTag::ObjDescriptor
+        | BerTag::UniversalString
         | BerTag::BmpString
-        | BerTag::GeneralString => {
+        | BerTag::Time => {
             der_constraint_fail_if!(i, constructed);
-        }
-        BerTag::UtcTime | BerTag::GeneralizedTime => match i.last() {
-            Some(b'Z') => (),
-            _ => {
-                return Err(Err::Error(BerError::DerConstraintFailed));
+            // X.680 restricts the characters allowed in each string type
+            #[cfg(feature = "strict-strings")]
+            {
+                if let Err(e) = ber_decode_string(tag, &i[..len], false) {
+                    return Err(Err::Error(e));
+                }
             }
-        },
+        }
+        BerTag::UtcTime => {
+            der_constraint_fail_if!(i, constructed);
+            der_constraint_fail_if!(i, !der_check_utctime(&i[..len]));
+        }
+        BerTag::GeneralizedTime => {
+            der_constraint_fail_if!(i, constructed);
+            der_constraint_fail_if!(i, !der_check_generalizedtime(&i[..len]));
+        }
+        BerTag::Date => {
+            der_constraint_fail_if!(i, constructed);
+            der_constraint_fail_if!(i, ASN1Time::from_date(&i[..len]).is_err());
+        }
+        BerTag::TimeOfDay => {
+            der_constraint_fail_if!(i, constructed);
+            der_constraint_fail_if!(i, !is_valid_time_of_day(&i[..len]));
+        }
+        BerTag::DateTime => {
+            der_constraint_fail_if!(i, constructed);
+            der_constraint_fail_if!(i, ASN1Time::from_date_time(&i[..len]).is_err());
+        }
+        BerTag::Duration => {
+            der_constraint_fail_if!(i, constructed);
+            der_constraint_fail_if!(i, !is_valid_duration(&i[..len]));
+        }
+        BerTag::Sequence => {
+            custom_check!(i, !constructed, BerError::ConstructExpected)?;
+            return der_read_content_sequence(i, len, depth, st);
+        }
+        BerTag::Set => {
+            custom_check!(i, !constructed, BerError::ConstructExpected)?;
+            return der_read_content_set(i, len, depth, st);
+        }
+        BerTag::External | BerTag::EmbeddedPdv | BerTag::CharacterString => {
+            custom_check!(i, !constructed, BerError::ConstructExpected)?;
+            let (rem, l) = der_read_content_objects(i, len, depth, false, st)?;
+            let content = match tag {
+                BerTag::External => BerObjectContent::External(l),
+                BerTag::EmbeddedPdv => BerObjectContent::EmbeddedPdv(l),
+                _ => BerObjectContent::CharacterString(l),
+            };
+            return Ok((rem, content));
+        }
         _ => (),
     }
-    ber_read_element_content_as(i, tag, len, constructed, depth)
+    ber_read_element_content_with_state(i, tag, BerSize::Definite(len), constructed, depth, st)
 }
 
+/// Parse the next bytes as the content of a DER object (top-level object, at depth 0).
+///
+/// The class of the object is checked, and application or context-specific objects are returned
+/// as `Unknown`.
+///
+/// The header is not part of the input, so the location is only recorded for the objects
+/// contained in the returned object (relative to `i`).
 pub fn der_read_element_content(
     i: &[u8],
     hdr: BerObjectHeader,
 ) -> IResult<&[u8], DerObject, BerError> {
+    let st = ParserState::default();
+    let (rem, mut obj) = der_read_element_content_recursive(i, hdr, 0, &st)?;
+    obj.set_offsets(i);
+    Ok((rem, obj))
+}
+
+fn der_read_element_content_recursive<'a>(
+    i: &'a [u8],
+    hdr: BerObjectHeader,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], DerObject<'a>, BerError> {
+    let len = der_get_length(hdr.len)?;
     match hdr.class {
         // universal
         0b00 |
@@ -324,22 +553,212 @@ pub fn der_read_element_content(
         // application
         0b01 |
         // context-specific
-        0b10 => return map!(
-            i,
-            take!(hdr.len),
-            |b| { DerObject::from_header_and_content(hdr,BerObjectContent::Unknown(hdr.tag, b)) }
-        ),
+        0b10 => return der_read_content_unknown(i, hdr, len, depth, st),
         _    => { return Err(Err::Error(BerError::InvalidClass)); },
     }
-    match der_read_element_content_as(i, hdr.tag, hdr.len as usize, hdr.is_constructed(), 0) {
+    match der_read_element_content_with_state(i, hdr.tag, hdr.len, hdr.is_constructed(), depth, st)
+    {
         Ok((rem, content)) => Ok((rem, DerObject::from_header_and_content(hdr, content))),
-        Err(Err::Error(BerError::UnknownTag)) => map!(i, take!(hdr.len), |b| {
-            DerObject::from_header_and_content(hdr, BerObjectContent::Unknown(hdr.tag, b))
-        }),
+        Err(Err::Error(BerError::UnknownTag)) => der_read_content_unknown(i, hdr, len, depth, st),
         Err(e) => Err(e),
     }
 }
 
+/// Read the content of an object of unknown type, and return it as `Unknown`
+///
+/// The type of a primitive object is not known, so its content cannot be checked. The content of
+/// a constructed object (for ex. an EXPLICIT tagged value) must be a series of DER objects.
+fn der_read_content_unknown<'a>(
+    i: &'a [u8],
+    hdr: BerObjectHeader,
+    len: usize,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], DerObject<'a>, BerError> {
+    if hdr.is_constructed() {
+        der_read_content_objects(i, len, depth, false, st)?;
+    }
+    map!(i, take!(len), |b| {
+        DerObject::from_header_and_content(hdr, BerObjectContent::Unknown(hdr.tag, b))
+    })
+}
+
+/// Get the length of an object
+///
+/// DER(10.1): the definite form of length encoding shall be used
+#[inline]
+fn der_get_length(len: BerSize) -> Result<usize, BerError> {
+    match len {
+        BerSize::Definite(l) => Ok(l),
+        BerSize::Indefinite => Err(BerError::DerConstraintFailed),
+    }
+}
+
+/// Read all the DER objects contained in the next `len` bytes
+///
+/// Unlike the BER sequence parser, all bytes must be consumed: an invalid object is an error.
+///
+/// If `check_order` is true, the objects must be sorted as required for the elements of a SET or
+/// SET OF.
+fn der_read_content_objects<'a>(
+    i: &'a [u8],
+    len: usize,
+    depth: usize,
+    check_order: bool,
+    st: &ParserState,
+) -> IResult<&'a [u8], Vec<DerObject<'a>>, BerError> {
+    let (rem, mut content) = take!(i, len)?;
+    let mut v = Vec::new();
+    let mut previous: Option<&[u8]> = None;
+    while !content.is_empty() {
+        match parse_der_recursive(content, depth + 1, st) {
+            Ok((r, obj)) => {
+                let encoded = &content[..content.len() - r.len()];
+                if check_order {
+                    if let Some(p) = previous {
+                        der_constraint_fail_if!(i, !der_check_set_order(p, encoded));
+                    }
+                    previous = Some(encoded);
+                }
+                v.push(obj);
+                st.check_elements(v.len())?;
+                content = r;
+            }
+            // the object is truncated by the length of the parent
+            Err(Err::Incomplete(_)) => {
+                return Err(Err::Error(
+                    BerError::ObjectTooShort.with_index(v.len(), content),
+                ))
+            }
+            Err(e) => return Err(e.map(|e| e.with_index(v.len(), content))),
+        }
+    }
+    Ok((rem, v))
+}
+
+#[inline]
+fn der_read_content_sequence<'a>(
+    i: &'a [u8],
+    len: usize,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(
+        i,
+        call!(der_read_content_objects, len, depth, false, st),
+        |l| { BerObjectContent::Sequence(l) }
+    )
+}
+
+#[inline]
+fn der_read_content_set<'a>(
+    i: &'a [u8],
+    len: usize,
+    depth: usize,
+    st: &ParserState,
+) -> IResult<&'a [u8], BerObjectContent<'a>, BerError> {
+    map!(
+        i,
+        call!(der_read_content_objects, len, depth, true, st),
+        |l| { BerObjectContent::Set(l) }
+    )
+}
+
+/// Verify that an INTEGER or ENUMERATED value has no redundant leading octet (X.690 section 8.3.2)
+pub(crate) fn der_check_integer(s: &[u8]) -> bool {
+    s.len() < 2 || !((s[0] == 0 && s[1] & 0x80 == 0) || (s[0] == 0xff && s[1] & 0x80 != 0))
+}
+
+/// Verify that the subidentifiers of an OID or relative OID are encoded in the fewest possible
+/// octets, i.e. that no subidentifier starts with `0x80` (X.690 section 8.19.2)
+pub(crate) fn der_check_oid(s: &[u8]) -> bool {
+    s.iter()
+        .enumerate()
+        .all(|(idx, &b)| b != 0x80 || (idx > 0 && s[idx - 1] & 0x80 != 0))
+}
+
+/// Verify the DER ordering of two consecutive encoded elements of a SET OF (X.690 section 11.6)
+///
+/// The elements must be sorted by their encodings, the shorter one being padded with zeroes. This
+/// is the order checked by [`parse_der`](fn.parse_der.html), since the schema is not known.
+pub fn der_check_set_order(a: &[u8], b: &[u8]) -> bool {
+    der_cmp_padded(a, b) != Ordering::Greater
+}
+
+/// Verify the DER ordering of two consecutive encoded components of a SET (X.690 section 8.6)
+///
+/// The components must be sorted by tag (class, then tag number). This differs from the order of
+/// the encodings when primitive and constructed components are mixed, for ex. `[5]` (constructed)
+/// must come before `[6]` (primitive), though its encoding starts with `0xa5`.
+/// Components with the same tag are sorted by encoding, as for a SET OF.
+pub fn der_check_set_component_order(a: &[u8], b: &[u8]) -> bool {
+    match (parse_identifier(a), parse_identifier(b)) {
+        (Ok((_, (class_a, _, tag_a))), Ok((_, (class_b, _, tag_b)))) => {
+            match (class_a, tag_a).cmp(&(class_b, tag_b)) {
+                Ordering::Less => true,
+                Ordering::Equal => der_check_set_order(a, b),
+                Ordering::Greater => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Verify that the elements encoded in `i` (the content of a SET or SET OF) are sorted, using
+/// `check` to compare consecutive elements. The input is not consumed.
+///
+/// Invalid encodings are not reported here, but by the parsers of the elements.
+#[doc(hidden)]
+pub fn der_check_set_content(
+    i: &[u8],
+    check: fn(&[u8], &[u8]) -> bool,
+) -> IResult<&[u8], (), BerError> {
+    let mut previous: Option<&[u8]> = None;
+    let mut rem = i;
+    while let Ok((r, hdr)) = der_read_element_header(rem) {
+        let end = match hdr.len {
+            BerSize::Definite(l) if l <= r.len() => rem.len() - r.len() + l,
+            _ => break,
+        };
+        let encoded = &rem[..end];
+        if let Some(p) = previous {
+            der_constraint_fail_if!(i, !check(p, encoded));
+        }
+        previous = Some(encoded);
+        rem = &rem[end..];
+    }
+    Ok((i, ()))
+}
+
+/// Test if a string of bytes only contains ASCII digits
+fn is_digits(s: &[u8]) -> bool {
+    s.iter().all(|c| c.is_ascii_digit())
+}
+
+/// Verify the DER constraints of a UTCTime (X.690 section 11.8): the value is expressed in UTC
+/// (terminated by `Z`), and the seconds are present.
+pub(crate) fn der_check_utctime(s: &[u8]) -> bool {
+    // YYMMDDhhmmssZ
+    s.len() == 13 && is_digits(&s[..12]) && s[12] == b'Z' && ASN1Time::from_utctime(s).is_ok()
+}
+
+/// Verify the DER constraints of a GeneralizedTime (X.690 section 11.7): the value is expressed in
+/// UTC (terminated by `Z`), the seconds are present, and the fractional part (if any) uses a
+/// full stop and has no trailing zeroes.
+pub(crate) fn der_check_generalizedtime(s: &[u8]) -> bool {
+    // YYYYMMDDhhmmss[.f+]Z
+    if s.len() < 15 || !is_digits(&s[..14]) || s[s.len() - 1] != b'Z' {
+        return false;
+    }
+    let fraction = &s[14..s.len() - 1];
+    let fraction_ok = match fraction.split_first() {
+        None => true,
+        Some((b'.', f)) => !f.is_empty() && is_digits(f) && f[f.len() - 1] != b'0',
+        Some(_) => false,
+    };
+    fraction_ok && ASN1Time::from_generalizedtime(s).is_ok()
+}
+
 #[inline]
 fn der_read_content_bitstring(i: &[u8], len: usize) -> IResult<&[u8], BerObjectContent, BerError> {
     do_parse! {
@@ -347,6 +766,8 @@ fn der_read_content_bitstring(i: &[u8], len: usize) -> IResult<&[u8], BerObjectC
         ignored_bits: be_u8 >>
                       custom_check!(ignored_bits > 7, BerError::DerConstraintFailed) >>
                       custom_check!(len == 0, BerError::InvalidLength) >>
+                      // DER(11.2.2) an empty bitstring has no unused bits
+                      custom_check!(len == 1 && ignored_bits != 0, BerError::DerConstraintFailed) >>
         s:            take!(len - 1) >>
                       call!(|input| {
                           if len > 1 {
@@ -367,30 +788,32 @@ pub fn der_read_element_header(i: &[u8]) -> IResult<&[u8], BerObjectHeader, BerE
     do_parse! {
         i,
         el:   parse_identifier >>
+              // X.690 8.1.2.4: the high tag number form is only used for tags >= 31, and uses
+              // the minimum number of octets
+              custom_check!(
+                  i[0] & 0x1f == 0x1f && (el.2 < 0x1f || i[1] == 0x80),
+                  BerError::DerConstraintFailed
+              ) >>
         len:  parse_ber_length_byte >>
         llen: cond!(len.0 == 1, take!(len.1)) >>
         ( {
-            let len : u64 = match len.0 {
-                0 => len.1 as u64,
+            let len = match len.0 {
+                0 => len.1 as usize,
                 _ => {
                     // if len is 0xff -> error (8.1.3.5)
                     custom_check!(&i[1..], len.1 == 0b0111_1111, BerError::InvalidLength)?;
-                    // if len.1 == 0b0111_1111 {
-                    //     return Err(::nom::Err::Error(error_position!(&i[1..], ErrorKind::Custom(BER_INVALID_LENGTH))));
-                    // }
-                    // DER(9.1) if len is 0 (indefinite form), obj must be constructed
-                    der_constraint_fail_if!(&i[1..], len.1 == 0 && el.1 != 1);
-                    // if len.1 == 0 && el.1 != 1 {
-                    //     return Err(::nom::Err::Error(error_position!(&i[1..], ErrorKind::Custom(BER_INVALID_LENGTH))));
-                    // }
+                    // DER(10.1) the definite form of length encoding shall be used
+                    der_constraint_fail_if!(&i[1..], len.1 == 0);
                     let llen = llen.unwrap(); // safe because we tested len.0 != 0
+                    // DER(10.1) length shall be encoded in the minimum number of octets
+                    der_constraint_fail_if!(&i[1..], llen[0] == 0);
                     match bytes_to_u64(llen) {
-                        Ok(l)  => {
-                            // DER: should have been encoded in short form (< 127)
-                            der_constraint_fail_if!(i, l < 127);
-                            l
+                        Ok(l) if l <= usize::MAX as u64 => {
+                            // DER: should have been encoded in short form (< 128)
+                            der_constraint_fail_if!(i, l < 128);
+                            l as usize
                         },
-                        Err(_) => { return Err(::nom::Err::Error(BerError::InvalidTag)); },
+                        _ => { return Err(::nom::Err::Error(BerError::InvalidTag)); },
                     }
                 },
             };
@@ -398,7 +821,7 @@ pub fn der_read_element_header(i: &[u8]) -> IResult<&[u8], BerObjectHeader, BerE
                 class: el.0,
                 structured: el.1,
                 tag: BerTag(el.2),
-                len,
+                len: BerSize::Definite(len),
             }
         } )
     }
@@ -1,9 +1,17 @@
-//! Error type for BER/DER parsers
+//! Error types for BER/DER parsers and serializers
 
+use crate::ber::{universal_tag_name, BerTag};
 use nom::error::{ErrorKind, ParseError};
+use std::error::Error;
+use std::fmt;
+use std::io;
 
 /// Error for BER/DER parsers
-#[derive(Debug, PartialEq)]
+///
+/// The errors returned by the parsers of this crate carry the location of the error and the
+/// objects being parsed (see [`BerErrorContext`](struct.BerErrorContext.html)). This context is
+/// ignored when comparing errors, use [`kind`](#method.kind) to match on the error itself.
+#[derive(Debug)]
 pub enum BerError {
     /// BER object does not have the expected type
     BerTypeError,
@@ -13,18 +21,40 @@ pub enum BerError {
     InvalidTag,
     InvalidClass,
     InvalidLength,
+    /// The indefinite length form was used for a primitive object (or an object which cannot be
+    /// encoded using this form)
+    IndefiniteLengthUnexpected,
 
     /// DER object was expected to be constructed (and found to be primitive)
     ConstructExpected,
     /// DER object was expected to be primitive (and found to be constructed)
     ConstructUnexpected,
 
+    /// BER string has characters forbidden in the string type
+    StringInvalidCharset,
+
     /// BER integer is too large to fit in a native type. Use `as_bigint()`
     IntegerTooLarge,
+    /// BER integer is negative, while an unsigned integer was requested
+    IntegerNegative,
+    /// DER integer has no content octets
+    IntegerEmpty,
+    /// DER integer is not encoded using the minimum number of octets (X.690 section 8.3.2)
+    IntegerNonMinimal,
 
     /// BER recursive parsing reached maximum depth (See
     /// [MAX_RECURSION](../ber/constant.MAX_RECURSION.html))
     BerMaxDepth,
+    /// Length of object exceeds the limit set in `ParserOptions`
+    MaxLengthExceeded,
+    /// Number of objects exceeds the limit set in `ParserOptions`
+    MaxObjectsExceeded,
+    /// Number of elements of a constructed object exceeds the limit set in `ParserOptions`
+    MaxElementsExceeded,
+    /// Number of arcs of an OID exceeds the limit set in `ParserOptions`
+    MaxOidArcsExceeded,
+    /// Size of a string exceeds the limit set in `ParserOptions`
+    MaxStringSizeExceeded,
 
     /// When parsing a defined sequence, some items could not be found
     ObjectTooShort,
@@ -42,13 +72,374 @@ pub enum BerError {
 
     /// Error raised by the underlying nom parser
     NomError(ErrorKind),
+
+    /// Error with its location and the objects being parsed
+    Context(Box<BerErrorContext>),
+}
+
+/// Object being parsed when an error occurred
+pub struct BerErrorFrame {
+    pub class: u8,
+    pub tag: BerTag,
+    /// Index of the element being parsed, if the object is constructed
+    pub index: Option<usize>,
+    /// Tagging mode (`EXPLICIT` or `IMPLICIT`), if known
+    pub label: Option<&'static str>,
+    /// Offset of the object, relative to the input of the outermost parser
+    pub offset: usize,
+    // address of the object, used to compute the offset
+    position: usize,
+}
+
+/// Location of an error, and objects being parsed when the error occurred
+pub struct BerErrorContext {
+    /// The error (never a `BerError::Context`)
+    pub error: BerError,
+    /// Offset of the error, relative to the input of the outermost parser
+    ///
+    /// This is the start of the object (or object content) which could not be parsed.
+    pub offset: usize,
+    /// Objects being parsed, from the innermost to the outermost
+    pub frames: Vec<BerErrorFrame>,
+    // address of the error, used to compute the offset
+    position: usize,
+    // index of the element being parsed, stored in the next frame
+    index: Option<usize>,
+}
+
+impl BerError {
+    /// Get the error, without its context
+    pub fn kind(&self) -> &BerError {
+        match self {
+            BerError::Context(ctx) => &ctx.error,
+            e => e,
+        }
+    }
+
+    /// Get the context of the error, if any
+    pub fn context(&self) -> Option<&BerErrorContext> {
+        match self {
+            BerError::Context(ctx) => Some(ctx),
+            _ => None,
+        }
+    }
+
+    /// Get the offset of the error, relative to the input of the outermost parser, if known
+    pub fn offset(&self) -> Option<usize> {
+        self.context().map(|ctx| ctx.offset)
+    }
+
+    /// Test if the error was raised because a parser limit was exceeded
+    pub fn is_limit_exceeded(&self) -> bool {
+        matches!(
+            self.kind(),
+            BerError::BerMaxDepth
+                | BerError::MaxLengthExceeded
+                | BerError::MaxObjectsExceeded
+                | BerError::MaxElementsExceeded
+                | BerError::MaxOidArcsExceeded
+                | BerError::MaxStringSizeExceeded
+        )
+    }
+
+    /// Add a context to the error, if it has none, located at the start of `i`
+    #[doc(hidden)]
+    pub fn with_position(self, i: &[u8]) -> Self {
+        match self {
+            BerError::Context(_) => self,
+            e => BerError::Context(Box::new(BerErrorContext {
+                error: e,
+                offset: 0,
+                frames: Vec::new(),
+                position: i.as_ptr() as usize,
+                index: None,
+            })),
+        }
+    }
+
+    /// Record the index of the element being parsed at `i`, in the object of the next frame
+    #[doc(hidden)]
+    pub fn with_index(self, index: usize, i: &[u8]) -> Self {
+        let mut e = self.with_position(i);
+        if let BerError::Context(ref mut ctx) = e {
+            ctx.index = Some(index);
+        }
+        e
+    }
+
+    /// Add a frame for the object starting at `start`, and compute the offsets relative to `start`
+    ///
+    /// If the error has no context, it is located at the start of `i`.
+    #[doc(hidden)]
+    pub fn with_frame(
+        self,
+        start: &[u8],
+        i: &[u8],
+        class: u8,
+        tag: BerTag,
+        label: Option<&'static str>,
+    ) -> Self {
+        let mut e = self.with_position(i);
+        if let BerError::Context(ref mut ctx) = e {
+            let index = ctx.index.take();
+            ctx.frames.push(BerErrorFrame {
+                class,
+                tag,
+                index,
+                label,
+                offset: 0,
+                position: start.as_ptr() as usize,
+            });
+            ctx.locate(start);
+        }
+        e
+    }
+}
+
+impl BerErrorContext {
+    /// Compute the offsets relative to `input`, for all positions inside `input`
+    fn locate(&mut self, input: &[u8]) {
+        let base = input.as_ptr() as usize;
+        let offset = |p: usize| {
+            if p >= base && p <= base + input.len() {
+                Some(p - base)
+            } else {
+                None
+            }
+        };
+        if let Some(o) = offset(self.position) {
+            self.offset = o;
+        }
+        for frame in self.frames.iter_mut() {
+            if let Some(o) = offset(frame.position) {
+                frame.offset = o;
+            }
+        }
+    }
+}
+
+// The addresses are only used to compute the offsets, and depend on the location of the input in
+// memory: they are ignored when comparing or printing frames and contexts.
+
+impl fmt::Debug for BerErrorFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BerErrorFrame")
+            .field("class", &self.class)
+            .field("tag", &self.tag)
+            .field("index", &self.index)
+            .field("label", &self.label)
+            .field("offset", &self.offset)
+            .finish()
+    }
+}
+
+impl PartialEq for BerErrorFrame {
+    fn eq(&self, other: &Self) -> bool {
+        self.class == other.class
+            && self.tag == other.tag
+            && self.index == other.index
+            && self.label == other.label
+            && self.offset == other.offset
+    }
+}
+
+impl fmt::Debug for BerErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BerErrorContext")
+            .field("error", &self.error)
+            .field("offset", &self.offset)
+            .field("frames", &self.frames)
+            .finish()
+    }
+}
+
+impl PartialEq for BerErrorContext {
+    fn eq(&self, other: &Self) -> bool {
+        self.error == other.error && self.offset == other.offset && self.frames == other.frames
+    }
+}
+
+/// Context is ignored when comparing errors
+impl PartialEq for BerError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.kind(), other.kind()) {
+            (BerError::Custom(a), BerError::Custom(b)) => a == b,
+            (BerError::NomError(a), BerError::NomError(b)) => a == b,
+            (a, b) => std::mem::discriminant(a) == std::mem::discriminant(b),
+        }
+    }
+}
+
+impl fmt::Display for BerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            BerError::BerTypeError => "unexpected object type",
+            BerError::BerValueError => "unexpected object value",
+            BerError::InvalidTag => "invalid tag",
+            BerError::InvalidClass => "invalid class",
+            BerError::InvalidLength => "invalid length",
+            BerError::IndefiniteLengthUnexpected => "unexpected indefinite length",
+            BerError::ConstructExpected => "constructed object expected",
+            BerError::ConstructUnexpected => "primitive object expected",
+            BerError::StringInvalidCharset => "invalid character in string",
+            BerError::IntegerTooLarge => "integer too large",
+            BerError::IntegerNegative => "negative integer",
+            BerError::IntegerEmpty => "empty integer",
+            BerError::IntegerNonMinimal => "integer not minimally encoded",
+            BerError::BerMaxDepth => "maximum recursion depth reached",
+            BerError::MaxLengthExceeded => "maximum object length exceeded",
+            BerError::MaxObjectsExceeded => "maximum number of objects exceeded",
+            BerError::MaxElementsExceeded => "maximum number of elements exceeded",
+            BerError::MaxOidArcsExceeded => "maximum number of OID arcs exceeded",
+            BerError::MaxStringSizeExceeded => "maximum string size exceeded",
+            BerError::ObjectTooShort => "object too short",
+            BerError::DerConstraintFailed => "DER constraint failed",
+            BerError::UnknownTag => "unknown tag",
+            BerError::Unsupported => "unsupported feature",
+            BerError::Custom(n) => return write!(f, "custom error {}", n),
+            BerError::NomError(kind) => return write!(f, "parser error ({:?})", kind),
+            BerError::Context(ctx) => return ctx.fmt(f),
+        };
+        f.write_str(s)
+    }
+}
+
+impl Error for BerError {}
+
+impl fmt::Display for BerErrorFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.class, universal_tag_name(self.tag)) {
+            (0b00, Some(name)) => f.write_str(name)?,
+            (0b00, None) => write!(f, "[UNIVERSAL {}]", self.tag.0)?,
+            (0b01, _) => write!(f, "[APPLICATION {}]", self.tag.0)?,
+            (0b10, _) => write!(f, "[{}]", self.tag.0)?,
+            _ => write!(f, "[PRIVATE {}]", self.tag.0)?,
+        }
+        if let Some(label) = self.label {
+            write!(f, " {}", label)?;
+        }
+        if let Some(index) = self.index {
+            write!(f, "[{}]", index)?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays the error, its offset and the objects being parsed, from the outermost to the
+/// innermost (for ex. `invalid tag at offset 12 (in SEQUENCE[2] > [0] EXPLICIT > INTEGER)`)
+impl fmt::Display for BerErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.error, self.offset)?;
+        if !self.frames.is_empty() {
+            f.write_str(" (in ")?;
+            for (n, frame) in self.frames.iter().rev().enumerate() {
+                if n > 0 {
+                    f.write_str(" > ")?;
+                }
+                write!(f, "{}", frame)?;
+            }
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
 }
 
 impl<I> ParseError<I> for BerError {
     fn from_error_kind(_input: I, kind: ErrorKind) -> Self {
         BerError::NomError(kind)
     }
-    fn append(_input: I, kind: ErrorKind, _other: Self) -> Self {
-        BerError::NomError(kind)
+    /// Keep the previous error, which is more precise than the error of the combinator
+    fn append(_input: I, _kind: ErrorKind, other: Self) -> Self {
+        other
     }
 }
+
+impl From<BerError> for nom::Err<BerError> {
+    fn from(e: BerError) -> nom::Err<BerError> {
+        nom::Err::Error(e)
+    }
+}
+
+/// Error for BER/DER serialization
+#[derive(Debug, PartialEq)]
+pub enum SerializeError {
+    /// Object class is not valid (must be between 0 and 3)
+    InvalidClass,
+    /// Object content cannot be encoded (for ex. an OID with less than two arcs)
+    InvalidContent,
+    /// Error raised by the underlying writer
+    IOError(io::ErrorKind),
+}
+
+impl From<io::Error> for SerializeError {
+    fn from(e: io::Error) -> Self {
+        SerializeError::IOError(e.kind())
+    }
+}
+
+/// Error for PEM decoding
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PemError {
+    /// No `-----BEGIN` line was found
+    MissingBegin,
+    /// The `-----END` line is missing
+    MissingEnd,
+    /// The label is not valid, or the encapsulation boundary is malformed
+    InvalidLabel,
+    /// The labels of the `-----BEGIN` and `-----END` lines differ
+    LabelMismatch,
+    /// An encapsulated header is malformed, or headers are present in strict mode
+    InvalidHeader,
+    /// The base64 data contains an invalid character, or has invalid padding
+    InvalidBase64,
+    /// The base64 lines do not have the length required in strict mode (64 characters)
+    InvalidLineLength,
+}
+
+impl fmt::Display for PemError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            PemError::MissingBegin => "missing PEM BEGIN line",
+            PemError::MissingEnd => "missing PEM END line",
+            PemError::InvalidLabel => "invalid PEM label",
+            PemError::LabelMismatch => "PEM BEGIN and END labels differ",
+            PemError::InvalidHeader => "invalid PEM header",
+            PemError::InvalidBase64 => "invalid base64 data",
+            PemError::InvalidLineLength => "invalid base64 line length",
+        };
+        f.write_str(s)
+    }
+}
+
+impl Error for PemError {}
+
+/// Error when building an OID from arcs or from a string
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OidParseError {
+    /// An absolute OID needs at least two arcs, and a relative OID at least one
+    TooShort,
+    /// The first arc must be 0, 1 or 2, and the second one lower than 40 if the first one is 0
+    /// or 1 (X.690 section 8.19.4)
+    FirstComponentsTooLarge,
+    /// An arc is not a valid integer
+    ParseIntError,
+    /// An OID-IRI (or RELATIVE-OID-IRI) does not have a valid syntax
+    InvalidIri,
+    /// An OID-IRI contains a Unicode label which is not registered
+    UnknownLabel,
+}
+
+impl fmt::Display for OidParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            OidParseError::TooShort => "OID has too few arcs",
+            OidParseError::FirstComponentsTooLarge => "invalid first arcs of OID",
+            OidParseError::ParseIntError => "invalid OID arc",
+            OidParseError::InvalidIri => "invalid OID-IRI",
+            OidParseError::UnknownLabel => "unknown Unicode label in OID-IRI",
+        };
+        f.write_str(s)
+    }
+}
+
+impl Error for OidParseError {}
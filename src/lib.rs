@@ -67,15 +67,61 @@
 //!
 //! # Notes
 //!
-//! - The DER constraints are verified if using `parse_der`.
+//! - The DER constraints are verified if using `parse_der`. To accept objects which do not respect
+//!   them, use [`parse_der_lenient`](der/fn.parse_der_lenient.html), which returns the list of
+//!   violations with the object.
 //! - `BerObject` and `DerObject` are the same objects (type alias). The only difference is the
 //!   verification of constraints *during parsing*.
 //! - DER integers can be of any size, so it is not possible to store them as simple integers (they
-//! are stored as raw bytes). To get a simple value, use
-//! [`BerObject::as_u32`](ber/struct.BerObject.html#method.as_u32) (knowning that this method will
-//! return an error if the integer is too large), [`BerObject::as_u64`](ber/struct.BerObject.html#method.as_u64),
-//! or use the `bigint` feature of this crate and use
-//! [`BerObject::as_bigint`](ber/struct.BerObject.html#method.as_bigint).
+//!   are stored as raw bytes). To get a simple value, use
+//!   [`BerObject::as_u32`](ber/struct.BerObject.html#method.as_u32) (knowning that this method will
+//!   return an error if the integer is too large), [`BerObject::as_u64`](ber/struct.BerObject.html#method.as_u64),
+//!   [`BerObject::as_i64`](ber/struct.BerObject.html#method.as_i64) for signed values,
+//!   or use the `bigint` feature of this crate and use
+//!   [`BerObject::as_bigint`](ber/struct.BerObject.html#method.as_bigint).
+//! - Objects can be serialized back to bytes (using DER encoding) with
+//!   [`BerObject::to_der_vec`](ber/struct.BerObject.html#method.to_der_vec) or
+//!   [`BerObject::write_der`](ber/struct.BerObject.html#method.write_der).
+//! - UTCTime, GeneralizedTime, DATE and DATE-TIME values can be decoded using
+//!   [`BerObject::as_time`](ber/struct.BerObject.html#method.as_time), and converted to
+//!   `SystemTime`. Conversions to `chrono` and `time` types are available using the features of
+//!   the same name. The other time types (TIME, TIME-OF-DAY and DURATION) are read as strings, and
+//!   their format is verified by the DER parser.
+//! - EXTERNAL (X.208 and X.680 forms), EMBEDDED PDV and CHARACTER STRING values can be decoded
+//!   using [`BerObject::as_external`](ber/struct.BerObject.html#method.as_external),
+//!   `as_embedded_pdv` and `as_character_string`.
+//! - Character strings can be decoded and validated using
+//!   [`BerObject::as_str`](ber/struct.BerObject.html#method.as_str) or
+//!   [`BerObject::as_string`](ber/struct.BerObject.html#method.as_string). The DER parser
+//!   also rejects characters forbidden in the string type if the `strict-strings` feature is
+//!   enabled.
+//! - OIDs ([`Oid`](oid/struct.Oid.html)) borrow their encoded bytes from the input, and are only
+//!   decoded when needed (for ex. when iterating over the arcs, or formatting them). Constant OIDs
+//!   can be built at compile time with the `oid!` macro. Arcs can be arbitrarily large (for ex.
+//!   UUID-based OIDs), and are always formatted correctly. OIDs are ordered by their arcs, and can
+//!   be navigated as a tree (see [`Oid::starts_with`](oid/struct.Oid.html#method.starts_with),
+//!   `parent`, `child`, `join` and `strip_prefix`).
+//! - OID-IRI and RELATIVE-OID-IRI values are validated when parsed, and can be converted to and from
+//!   OIDs using the Unicode labels of an [`IriRegistry`](oid_iri/struct.IriRegistry.html).
+//! - The `oid-registry` feature adds the [`oid_registry`](oid_registry/index.html) module, to find
+//!   the names and descriptions of common OIDs (or OIDs from user-defined registries), and to show
+//!   them when pretty-printing objects.
+//! - Parsed objects record their raw encoding and their location in the input, see
+//!   [`BerObject::raw`](ber/struct.BerObject.html#method.raw) and
+//!   [`BerObject::span`](ber/struct.BerObject.html#method.span).
+//! - Errors carry the offset of the error and the objects being parsed (see
+//!   [`BerErrorContext`](error/struct.BerErrorContext.html)), and can be displayed. The errors
+//!   returned by the parsers are wrapped in `BerError::Context`: use
+//!   [`BerError::kind`](error/enum.BerError.html#method.kind) to match on the error itself.
+//! - Limits (depth, lengths, number of objects etc.) can be set when parsing untrusted input, using
+//!   [`parse_ber_with_options`](ber/fn.parse_ber_with_options.html) or
+//!   [`parse_der_with_options`](der/fn.parse_der_with_options.html).
+//! - Objects can be pretty-printed as a tree (`Debug`) or as an annotated dump with offsets and
+//!   lengths (`Display`), using
+//!   [`BerObject::as_pretty`](ber/struct.BerObject.html#method.as_pretty).
+//! - PEM-armored data can be decoded (and encoded) using the [`pem`](pem/index.html) module.
+//! - The `der-dump` feature builds a `der-dump` command-line tool, to dump (and optionally check
+//!   the DER encoding of) objects read from PEM, base64, hexadecimal or binary input.
 //!
 //! # References
 //!
@@ -106,10 +152,22 @@ pub mod ber;
 pub mod der;
 pub mod error;
 pub mod oid;
+pub mod oid_iri;
+#[cfg(feature = "oid-registry")]
+pub mod oid_registry;
+pub mod pem;
 
 // compatibility: re-export at crate root
 pub use ber::parse_ber;
 pub use der::parse_der;
 
+/// Build an OID at compile time, see the [`oid`](oid/index.html) module
+pub use der_oid_macro::oid;
+
+// The `oid!` macro expands to absolute paths (`::der_parser::oid::Oid`), which only resolve in
+// dependent crates. Declaring this crate under its own name allows using the macro inside the
+// crate, for ex. in the built-in table of `oid_registry` and in the unit tests.
+extern crate self as der_parser;
+
 #[cfg(feature = "bigint")]
 extern crate num_bigint;
@@ -3,36 +3,40 @@
 #[macro_export]
 macro_rules! fold_der_defined_m(
     (__impl $i:expr, $acc:ident, $f:ident) => ( {
-        match $f($i) {
+        let i = $i;
+        match $f(i) {
             Ok((rem,res)) => { $acc.push(res); Ok((rem,$acc)) },
-            Err(e)        => Err(e)
+            Err(e)        => Err(e.map(|e| e.with_index($acc.len(), i)))
         }
     });
     (__impl $i:expr, $acc:ident, $submac:ident!( $($args:tt)* ) ) => ( {
-        match $submac!($i, $($args)*) {
+        let i = $i;
+        match $submac!(i, $($args)*) {
             Ok((rem,res)) => { $acc.push(res); Ok((rem,$acc)) },
-            Err(e)        => Err(e)
+            Err(e)        => Err(e.map(|e| e.with_index($acc.len(), i)))
         }
     });
     (__impl $i:expr, $acc:ident, $f:ident >> $($rest:tt)*) => (
         {
-            match $f($i) {
+            let i = $i;
+            match $f(i) {
                 Ok((rem,res)) => {
                     $acc.push(res);
                     fold_der_defined_m!(__impl rem, $acc, $($rest)* )
                 },
-                Err(e)        => Err(e)
+                Err(e)        => Err(e.map(|e| e.with_index($acc.len(), i)))
             }
         }
     );
     (__impl $i:expr, $acc:ident, $submac:ident!( $($args:tt)* ) >> $($rest:tt)*) => (
         {
-            match $submac!($i, $($args)*) {
+            let i = $i;
+            match $submac!(i, $($args)*) {
                 Ok((rem,res)) => {
                     $acc.push(res);
                     fold_der_defined_m!(__impl rem, $acc, $($rest)* )
                 },
-                Err(e)        => Err(e)
+                Err(e)        => Err(e.map(|e| e.with_index($acc.len(), i)))
             }
         }
     );
@@ -45,21 +49,82 @@ macro_rules! fold_der_defined_m(
     );
 );
 
+/// Internal parser, do not use directly
+///
+/// Add a frame for the object starting at `$start` (with header `$hdr`) to the context of the
+/// errors returned by the sub-parser.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! ber_error_context(
+    ($i:expr, $start:expr, $hdr:expr, $label:expr, $submac:ident!( $($args:tt)* )) => (
+        {
+            let i = $i;
+            let hdr: &$crate::ber::BerObjectHeader = $hdr;
+            $submac!(i, $($args)*)
+                .map_err(|e| e.map(|e| e.with_frame($start, i, hdr.class, hdr.tag, $label)))
+        }
+    );
+);
+
+/// Internal parser, do not use directly
+///
+/// Record the location of the object returned by the sub-parser, which starts at `$i`. The
+/// offsets of the object and of the objects it contains are relative to `$i`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! ber_with_location(
+    ($i:expr, $submac:ident!( $($args:tt)* )) => (
+        {
+            let start = $i;
+            $submac!(start, $($args)*).map(|(rem, mut obj)| {
+                $crate::ber::ber_set_location(start, rem, &mut obj);
+                (rem, obj)
+            })
+        }
+    );
+);
+
 /// Internal parser, do not use directly
 #[doc(hidden)]
 #[macro_export]
 macro_rules! parse_ber_defined_m(
+    ($i:expr, $tag:expr, ORDER $check:path, $($args:tt)*) => (
+        {
+            use $crate::der::der_read_element_header;
+            use $crate::fold_der_defined_m;
+            let start = $i;
+            do_parse!(
+                start,
+                hdr:     der_read_element_header >>
+                         custom_check!(hdr.class != 0b00, $crate::error::BerError::InvalidClass) >>
+                         custom_check!(hdr.structured != 0b1, $crate::error::BerError::ConstructExpected) >>
+                         custom_check!(hdr.tag != $tag, $crate::error::BerError::InvalidTag) >>
+                content: ber_error_context!(start, &hdr, None,
+                    flat_map!(call!($crate::ber::ber_get_object_content, &hdr, 0),
+                        preceded!(
+                            call!($crate::der::der_check_set_content, $check),
+                            fold_der_defined_m!( $($args)* )
+                        )
+                    )
+                ) >>
+                (hdr,content)
+            )
+        }
+    );
     ($i:expr, $tag:expr, $($args:tt)*) => (
         {
-            use $crate::ber::ber_read_element_header;
+            use $crate::der::der_read_element_header;
             use $crate::fold_der_defined_m;
+            let start = $i;
             do_parse!(
-                $i,
-                hdr:     ber_read_element_header >>
+                start,
+                hdr:     der_read_element_header >>
                          custom_check!(hdr.class != 0b00, $crate::error::BerError::InvalidClass) >>
                          custom_check!(hdr.structured != 0b1, $crate::error::BerError::ConstructExpected) >>
                          custom_check!(hdr.tag != $tag, $crate::error::BerError::InvalidTag) >>
-                content: flat_take!(hdr.len as usize, fold_der_defined_m!( $($args)* )) >>
+                content: ber_error_context!(start, &hdr, None,
+                    flat_map!(call!($crate::ber::ber_get_object_content, &hdr, 0), fold_der_defined_m!( $($args)* ))
+                ) >>
                 (hdr,content)
             )
         }
@@ -101,10 +166,12 @@ macro_rules! parse_ber_defined_m(
 #[deprecated(since = "3.0.0", note = "Use parse_der_sequence_defined")]
 macro_rules! parse_der_sequence_defined_m(
     ($i:expr, $($args:tt)*) => ({
-        map!(
+        ber_with_location!(
             $i,
-            parse_ber_defined_m!($crate::ber::BerTag::Sequence, $($args)*),
-            |(hdr,o)| $crate::ber::BerObject::from_header_and_content(hdr,$crate::ber::BerObjectContent::Sequence(o))
+            map!(
+                parse_ber_defined_m!($crate::ber::BerTag::Sequence, $($args)*),
+                |(hdr,o)| $crate::ber::BerObject::from_header_and_content(hdr,$crate::ber::BerObjectContent::Sequence(o))
+            )
         )
     });
 );
@@ -113,6 +180,9 @@ macro_rules! parse_der_sequence_defined_m(
 ///
 /// Given a list of expected parsers, apply them to build a DER set.
 ///
+/// The components must be sorted by tag, as required for a SET (see
+/// [`der_check_set_component_order`](der/fn.der_check_set_component_order.html)).
+///
 /// ```rust
 /// # #[macro_use] extern crate nom;
 /// # #[macro_use] extern crate rusticata_macros;
@@ -130,12 +200,12 @@ macro_rules! parse_der_sequence_defined_m(
 /// }
 /// let empty = &b""[..];
 /// let bytes = [ 0x31, 0x0a,
-///               0x02, 0x03, 0x01, 0x00, 0x01,
 ///               0x02, 0x03, 0x01, 0x00, 0x00,
+///               0x02, 0x03, 0x01, 0x00, 0x01,
 /// ];
 /// let expected  = BerObject::from_set(vec![
-///     BerObject::from_int_slice(b"\x01\x00\x01"),
 ///     BerObject::from_int_slice(b"\x01\x00\x00"),
+///     BerObject::from_int_slice(b"\x01\x00\x01"),
 /// ]);
 /// assert_eq!(localparse_set(&bytes), Ok((empty, expected)));
 /// # }
@@ -144,10 +214,12 @@ macro_rules! parse_der_sequence_defined_m(
 #[deprecated(since = "3.0.0", note = "Use parse_der_set_defined")]
 macro_rules! parse_der_set_defined_m(
     ($i:expr, $($args:tt)*) => ({
-        map!(
+        ber_with_location!(
             $i,
-            parse_ber_defined_m!($crate::ber::BerTag::Set, $($args)*),
-            |(hdr,o)| $crate::ber::BerObject::from_header_and_content(hdr,$crate::ber::BerObjectContent::Set(o))
+            map!(
+                parse_ber_defined_m!($crate::ber::BerTag::Set, ORDER $crate::der::der_check_set_component_order, $($args)*),
+                |(hdr,o)| $crate::ber::BerObject::from_header_and_content(hdr,$crate::ber::BerObjectContent::Set(o))
+            )
         )
     });
 );
@@ -180,15 +252,15 @@ macro_rules! fold_parsers(
 macro_rules! parse_der_defined(
     ($i:expr, $tag:expr, $($args:tt)*) => (
         {
-            use $crate::ber::ber_read_element_header;
+            use $crate::der::der_read_element_header;
             let res =
             do_parse!(
                 $i,
-                hdr:     ber_read_element_header >>
+                hdr:     der_read_element_header >>
                          custom_check!(hdr.class != 0b00, $crate::error::BerError::InvalidClass) >>
                          custom_check!(hdr.structured != 0b1, $crate::error::BerError::ConstructExpected) >>
                          custom_check!(hdr.tag != $tag, $crate::error::BerError::InvalidTag) >>
-                content: take!(hdr.len) >>
+                content: call!($crate::ber::ber_get_object_content, &hdr, 0) >>
                 (hdr,content)
             );
             match res {
@@ -241,10 +313,12 @@ macro_rules! parse_der_defined(
 #[macro_export]
 macro_rules! parse_der_sequence_defined(
     ($i:expr, $($args:tt)*) => ({
-        map!(
+        ber_with_location!(
             $i,
-            parse_ber_defined_m!($crate::ber::BerTag::Sequence, $($args)*),
-            |(hdr,o)| $crate::ber::BerObject::from_header_and_content(hdr,$crate::ber::BerObjectContent::Sequence(o))
+            map!(
+                parse_ber_defined_m!($crate::ber::BerTag::Sequence, $($args)*),
+                |(hdr,o)| $crate::ber::BerObject::from_header_and_content(hdr,$crate::ber::BerObjectContent::Sequence(o))
+            )
         )
     });
 );
@@ -262,6 +336,9 @@ macro_rules! parse_der_sequence_defined(
 ///
 /// Given a list of expected parsers, apply them to build a DER set.
 ///
+/// The components must be sorted by tag, as required for a SET (see
+/// [`der_check_set_component_order`](der/fn.der_check_set_component_order.html)).
+///
 /// ```rust
 /// # #[macro_use] extern crate nom;
 /// # #[macro_use] extern crate rusticata_macros;
@@ -279,12 +356,12 @@ macro_rules! parse_der_sequence_defined(
 /// }
 /// let empty = &b""[..];
 /// let bytes = [ 0x31, 0x0a,
-///               0x02, 0x03, 0x01, 0x00, 0x01,
 ///               0x02, 0x03, 0x01, 0x00, 0x00,
+///               0x02, 0x03, 0x01, 0x00, 0x01,
 /// ];
 /// let expected  = BerObject::from_set(vec![
-///     BerObject::from_int_slice(b"\x01\x00\x01"),
 ///     BerObject::from_int_slice(b"\x01\x00\x00"),
+///     BerObject::from_int_slice(b"\x01\x00\x01"),
 /// ]);
 /// assert_eq!(localparse_set(&bytes), Ok((empty, expected)));
 /// # }
@@ -292,10 +369,12 @@ macro_rules! parse_der_sequence_defined(
 #[macro_export]
 macro_rules! parse_der_set_defined(
     ($i:expr, $($args:tt)*) => ({
-        map!(
+        ber_with_location!(
             $i,
-            parse_ber_defined_m!($crate::ber::BerTag::Set, $($args)*),
-            |(hdr,o)| $crate::ber::BerObject::from_header_and_content(hdr,$crate::ber::BerObjectContent::Set(o))
+            map!(
+                parse_ber_defined_m!($crate::ber::BerTag::Set, ORDER $crate::der::der_check_set_component_order, $($args)*),
+                |(hdr,o)| $crate::ber::BerObject::from_header_and_content(hdr,$crate::ber::BerObjectContent::Set(o))
+            )
         )
     });
 );
@@ -341,20 +420,22 @@ macro_rules! parse_der_set_defined(
 #[macro_export]
 macro_rules! parse_der_sequence_of(
     ($i:expr, $f:ident) => ({
-        use $crate::ber::ber_read_element_header;
-        do_parse!(
-            $i,
-            hdr:     ber_read_element_header >>
+        use $crate::der::der_read_element_header;
+        let start = $i;
+        ber_with_location!(start, do_parse!(
+            hdr:     der_read_element_header >>
                      custom_check!(hdr.tag != $crate::ber::BerTag::Sequence, $crate::error::BerError::InvalidTag) >>
-            content: flat_take!(hdr.len as usize,
-                do_parse!(
-                    r: many0!(complete!($f)) >>
-                       empty!() >>
-                    ( r )
+            content: ber_error_context!(start, &hdr, None,
+                flat_map!(call!($crate::ber::ber_get_object_content, &hdr, 0),
+                    do_parse!(
+                        r: many0!(complete!($f)) >>
+                           empty!() >>
+                        ( r )
+                    )
                 )
             ) >>
             ( $crate::ber::BerObject::from_header_and_content(hdr, $crate::ber::BerObjectContent::Sequence(content)) )
-        )
+        ))
     })
 );
 
@@ -362,6 +443,9 @@ macro_rules! parse_der_sequence_of(
 ///
 /// Given a subparser for a DER type, parse a set of identical objects.
 ///
+/// The elements must be sorted by their encodings, as required for a SET OF (see
+/// [`der_check_set_order`](der/fn.der_check_set_order.html)).
+///
 /// ```rust
 /// # #[macro_use] extern crate nom;
 /// # #[macro_use] extern crate rusticata_macros;
@@ -376,12 +460,12 @@ macro_rules! parse_der_sequence_of(
 /// };
 /// let empty = &b""[..];
 /// let bytes = [ 0x31, 0x0a,
-///               0x02, 0x03, 0x01, 0x00, 0x01,
 ///               0x02, 0x03, 0x01, 0x00, 0x00,
+///               0x02, 0x03, 0x01, 0x00, 0x01,
 /// ];
 /// let expected  = BerObject::from_set(vec![
-///     BerObject::from_int_slice(b"\x01\x00\x01"),
 ///     BerObject::from_int_slice(b"\x01\x00\x00"),
+///     BerObject::from_int_slice(b"\x01\x00\x01"),
 /// ]);
 /// assert_eq!(parser(&bytes), Ok((empty, expected)));
 /// # }
@@ -389,20 +473,23 @@ macro_rules! parse_der_sequence_of(
 #[macro_export]
 macro_rules! parse_der_set_of(
     ($i:expr, $f:ident) => ({
-        use $crate::ber::ber_read_element_header;
-        do_parse!(
-            $i,
-            hdr:     ber_read_element_header >>
+        use $crate::der::der_read_element_header;
+        let start = $i;
+        ber_with_location!(start, do_parse!(
+            hdr:     der_read_element_header >>
                      custom_check!(hdr.tag != $crate::ber::BerTag::Set, $crate::error::BerError::InvalidTag) >>
-            content: flat_take!(hdr.len as usize,
-                do_parse!(
-                    r: many0!(complete!($f)) >>
-                       empty!() >>
-                    ( r )
+            content: ber_error_context!(start, &hdr, None,
+                flat_map!(call!($crate::ber::ber_get_object_content, &hdr, 0),
+                    do_parse!(
+                           call!($crate::der::der_check_set_content, $crate::der::der_check_set_order) >>
+                        r: many0!(complete!($f)) >>
+                           empty!() >>
+                        ( r )
+                    )
                 )
             ) >>
             ( $crate::ber::BerObject::from_header_and_content(hdr, $crate::ber::BerObjectContent::Set(content)) )
-        )
+        ))
     })
 );
 
@@ -529,7 +616,7 @@ macro_rules! parse_der_optional(
 ///         class: 0,
 ///         structured: 1,
 ///         tag: BerTag::Sequence,
-///         len: 0xa,
+///         len: BerSize::Definite(0xa),
 ///     },
 ///     MyStruct {
 ///         a: BerObject::from_int_slice(b"\x01\x00\x01"),
@@ -571,21 +658,29 @@ macro_rules! parse_der_optional(
 #[macro_export]
 macro_rules! parse_der_struct(
     ($i:expr, TAG $tag:expr, $($rest:tt)*) => ({
-        use $crate::ber::{BerObjectHeader,ber_read_element_header};
+        use $crate::ber::BerObjectHeader;
+        use $crate::der::der_read_element_header;
+        let start = $i;
         do_parse!(
-            $i,
-            hdr: verify!(ber_read_element_header, |hdr: &BerObjectHeader|
+            start,
+            hdr: verify!(der_read_element_header, |hdr: &BerObjectHeader|
                          hdr.structured == 1 && hdr.tag == $tag) >>
-            res: flat_take!(hdr.len as usize, do_parse!( $($rest)* )) >>
+            res: ber_error_context!(start, &hdr, None,
+                flat_map!(call!($crate::ber::ber_get_object_content, &hdr, 0), do_parse!( $($rest)* ))
+            ) >>
             (hdr,res)
         )
     });
     ($i:expr, $($rest:tt)*) => ({
-        use $crate::ber::{BerObjectHeader,ber_read_element_header};
+        use $crate::ber::BerObjectHeader;
+        use $crate::der::der_read_element_header;
+        let start = $i;
         do_parse!(
-            $i,
-            hdr: verify!(ber_read_element_header, |hdr: &BerObjectHeader| hdr.structured == 1) >>
-            res: flat_take!(hdr.len as usize, do_parse!( $($rest)* )) >>
+            start,
+            hdr: verify!(der_read_element_header, |hdr: &BerObjectHeader| hdr.structured == 1) >>
+            res: ber_error_context!(start, &hdr, None,
+                flat_map!(call!($crate::ber::ber_get_object_content, &hdr, 0), do_parse!( $($rest)* ))
+            ) >>
             (hdr,res)
         )
     });
@@ -670,31 +765,42 @@ macro_rules! parse_der_struct(
 #[macro_export]
 macro_rules! parse_der_tagged(
     ($i:expr, EXPLICIT $tag:expr, $f:ident) => ({
-        use $crate::ber::{BerObjectHeader,ber_read_element_header};
+        use $crate::ber::BerObjectHeader;
+        use $crate::der::der_read_element_header;
+        let start = $i;
         do_parse!(
-            $i,
-            hdr: verify!(ber_read_element_header, |hdr: &BerObjectHeader| hdr.tag.0 == $tag) >>
-            res: flat_take!(hdr.len as usize, call!( $f )) >>
+            start,
+            hdr: verify!(der_read_element_header, |hdr: &BerObjectHeader| hdr.tag.0 == $tag) >>
+            res: ber_error_context!(start, &hdr, Some("EXPLICIT"),
+                flat_map!(call!($crate::ber::ber_get_object_content, &hdr, 0), call!( $f ))
+            ) >>
             (res)
         )
     });
     ($i:expr, EXPLICIT $tag:expr, $submac:ident!( $($args:tt)*)) => ({
-        use $crate::ber::{BerObjectHeader,ber_read_element_header};
+        use $crate::ber::BerObjectHeader;
+        use $crate::der::der_read_element_header;
+        let start = $i;
         do_parse!(
-            $i,
-            hdr: verify!(ber_read_element_header, |hdr: &BerObjectHeader| hdr.tag.0 == $tag) >>
-            res: flat_take!(hdr.len as usize, $submac!( $($args)* )) >>
+            start,
+            hdr: verify!(der_read_element_header, |hdr: &BerObjectHeader| hdr.tag.0 == $tag) >>
+            res: ber_error_context!(start, &hdr, Some("EXPLICIT"),
+                flat_map!(call!($crate::ber::ber_get_object_content, &hdr, 0), $submac!( $($args)* ))
+            ) >>
             (res)
         )
     });
     ($i:expr, IMPLICIT $tag:expr, $type:expr) => ({
-        use $crate::ber::{BerObjectHeader,ber_read_element_header,ber_read_element_content_as};
-        do_parse!(
-            $i,
-            hdr: verify!(ber_read_element_header, |hdr: &BerObjectHeader| hdr.tag.0 == $tag) >>
-            res: call!(ber_read_element_content_as, $type, hdr.len as usize, hdr.is_constructed(), 0) >>
+        use $crate::ber::{BerObjectHeader,ber_read_element_content_as};
+        use $crate::der::der_read_element_header;
+        let start = $i;
+        ber_with_location!(start, do_parse!(
+            hdr: verify!(der_read_element_header, |hdr: &BerObjectHeader| hdr.tag.0 == $tag) >>
+            res: ber_error_context!(start, &hdr, Some("IMPLICIT"),
+                call!(ber_read_element_content_as, $type, hdr.len, hdr.is_constructed(), 0)
+            ) >>
             (BerObject::from_obj(res))
-        )
+        ))
     });
     ($i:expr, $tag:expr, $f:ident) => ( parse_der_tagged!($i, EXPLICIT $tag, $f) );
 );
@@ -753,12 +859,16 @@ macro_rules! parse_der_tagged(
 #[macro_export]
 macro_rules! parse_der_application(
     ($i:expr, APPLICATION $tag:expr, $($rest:tt)*) => ({
-        use $crate::ber::{BerObjectHeader,ber_read_element_header};
+        use $crate::ber::BerObjectHeader;
+        use $crate::der::der_read_element_header;
+        let start = $i;
         do_parse!(
-            $i,
-            hdr: verify!(ber_read_element_header, |hdr: &BerObjectHeader|
+            start,
+            hdr: verify!(der_read_element_header, |hdr: &BerObjectHeader|
                          hdr.class == 0b01 && hdr.tag.0 == $tag) >>
-            res: flat_take!(hdr.len as usize, do_parse!( $($rest)* )) >>
+            res: ber_error_context!(start, &hdr, None,
+                flat_map!(call!($crate::ber::ber_get_object_content, &hdr, 0), do_parse!( $($rest)* ))
+            ) >>
             (hdr,res)
         )
     });
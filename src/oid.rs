@@ -1,82 +1,712 @@
 //! Object ID (OID) representation
+//!
+//! An [`Oid`](struct.Oid.html) stores the encoded content octets of the object identifier (X.690
+//! section 8.19), and borrows them from the input when parsed. Comparisons are done on the
+//! encoded bytes, and the arcs are only decoded when needed, using
+//! [`Oid::iter`](struct.Oid.html#method.iter).
+//!
+//! OIDs can be built at compile time using the `oid!` macro, which checks the arcs and computes
+//! the encoding. The result can be used in constants, and `oid!(raw ...)` returns the encoded
+//! bytes, to match parsed OIDs without decoding them:
+//!
+//! ```rust
+//! # extern crate der_parser;
+//! # use der_parser::oid;
+//! # use der_parser::oid::Oid;
+//! # fn main() {
+//! const SHA256_WITH_RSA: Oid<'static> = oid!(1.2.840.113549.1.1.11);
+//!
+//! fn signature_algorithm(oid: &Oid) -> Option<&'static str> {
+//!     match oid.bytes() {
+//!         &oid!(raw 1.2.840.113549.1.1.5) => Some("sha1WithRSAEncryption"),
+//!         &oid!(raw 1.2.840.113549.1.1.11) => Some("sha256WithRSAEncryption"),
+//!         _ => None,
+//!     }
+//! }
+//!
+//! let oid: Oid = "1.2.840.113549.1.1.11".parse().unwrap();
+//! assert_eq!(oid, SHA256_WITH_RSA);
+//! assert_eq!(signature_algorithm(&oid), Some("sha256WithRSAEncryption"));
+//! assert_eq!(oid!(rel 8571.3.2), Oid::from_relative(&[8571, 3, 2]).unwrap());
+//! # }
+//! ```
+//!
+//! OIDs can be compared (in the order of their arcs), and navigated as a tree:
+//!
+//! ```rust
+//! # extern crate der_parser;
+//! # use der_parser::oid;
+//! # use der_parser::oid::Oid;
+//! # fn main() {
+//! let enterprise = oid!(1.3.6.1.4.1.55555);
+//! let oid = enterprise.child(3).child(1);
+//! assert!(oid.starts_with(&enterprise));
+//! let rel = oid.strip_prefix(&enterprise).unwrap();
+//! assert_eq!(rel, oid!(rel 3.1));
+//! assert_eq!(enterprise.join(&rel), Some(oid.clone()));
+//! assert_eq!(oid.parent(), Some(enterprise.child(3)));
+//! assert!(enterprise < oid);
+//! # }
+//! ```
+//!
+//! Invalid OIDs are rejected at compile time:
+//!
+//! ```compile_fail
+//! # extern crate der_parser;
+//! # use der_parser::oid;
+//! # fn main() {
+//! let oid = oid!(1.40.3);
+//! # }
+//! ```
 
+use crate::ber::encode_base128;
+use crate::error::OidParseError;
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::fmt;
-use std::slice;
-
-use std::num::ParseIntError;
 use std::str::FromStr;
 
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
+
 /// Object ID (OID) representation
-#[derive(PartialEq, Eq, Clone)]
-pub struct Oid(Vec<u64>);
+///
+/// ```rust
+/// # extern crate der_parser;
+/// # use der_parser::oid::Oid;
+/// # use std::borrow::Cow;
+/// # fn main() {
+/// // 1.2.840.113549.1.1.5, from the content octets
+/// const SHA1_WITH_RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x05];
+/// let oid = Oid::new(Cow::Borrowed(SHA1_WITH_RSA));
+/// assert_eq!(oid, Oid::from(&[1, 2, 840, 113549, 1, 1, 5]).unwrap());
+/// assert_eq!(oid.iter().and_then(|mut arcs| arcs.nth(2)), Some(840));
+/// assert_eq!(oid.to_string(), "1.2.840.113549.1.1.5");
+/// # }
+/// ```
+#[derive(PartialEq, Eq, Hash, Clone)]
+pub struct Oid<'a> {
+    asn1: Cow<'a, [u8]>,
+    relative: bool,
+}
+
+impl<'a> Oid<'a> {
+    /// Build an OID from its encoded content octets
+    ///
+    /// The encoding is not verified.
+    pub const fn new(asn1: Cow<'a, [u8]>) -> Oid<'a> {
+        Oid {
+            asn1,
+            relative: false,
+        }
+    }
+
+    /// Build a relative OID from its encoded content octets
+    ///
+    /// The encoding is not verified.
+    pub const fn new_relative(asn1: Cow<'a, [u8]>) -> Oid<'a> {
+        Oid {
+            asn1,
+            relative: true,
+        }
+    }
 
-impl Oid {
     /// Build an OID from an array of `u64` integers
-    pub fn from(s: &[u64]) -> Oid {
-        Oid(s.to_owned())
+    ///
+    /// The first two arcs are combined as `X*40 + Y` (X.690 section 8.19.4), so there must be at
+    /// least two arcs, the first one must be 0, 1 or 2, and the second one must be lower than 40
+    /// if the first one is 0 or 1.
+    pub fn from(s: &[u64]) -> Result<Oid<'static>, OidParseError> {
+        if s.len() < 2 {
+            return Err(OidParseError::TooShort);
+        }
+        if s[0] > 2 || (s[0] < 2 && s[1] >= 40) {
+            return Err(OidParseError::FirstComponentsTooLarge);
+        }
+        let first = s[1]
+            .checked_add(s[0] * 40)
+            .ok_or(OidParseError::FirstComponentsTooLarge)?;
+        let mut v = Vec::new();
+        encode_base128(&mut v, first);
+        for &arc in &s[2..] {
+            encode_base128(&mut v, arc);
+        }
+        Ok(Oid::new(Cow::Owned(v)))
     }
 
-    /// Convert the OID to a string representation.
-    /// The string contains the IDs separated by dots, for ex: "1.2.840.113549.1.1.5"
-    pub fn to_string(&self) -> String {
-        if self.0.is_empty() {
-            return String::new();
+    /// Build a relative OID from an array of `u64` integers
+    pub fn from_relative(s: &[u64]) -> Result<Oid<'static>, OidParseError> {
+        if s.is_empty() {
+            return Err(OidParseError::TooShort);
+        }
+        let mut v = Vec::new();
+        for &arc in s {
+            encode_base128(&mut v, arc);
         }
+        Ok(Oid::new_relative(Cow::Owned(v)))
+    }
+
+    /// Return an OID owning its encoded content octets
+    pub fn to_owned(&self) -> Oid<'static> {
+        Oid {
+            asn1: Cow::Owned(self.asn1.to_vec()),
+            relative: self.relative,
+        }
+    }
+
+    /// Return the encoded content octets
+    pub fn bytes(&self) -> &[u8] {
+        &self.asn1
+    }
 
-        let mut s = self.0[0].to_string();
+    /// Return true if this is a relative OID
+    pub fn is_relative(&self) -> bool {
+        self.relative
+    }
+
+    /// Return an iterator on the encoded subidentifiers
+    fn subidentifiers(&self) -> impl Iterator<Item = &[u8]> {
+        // subidentifiers are encoded in base 128, the last byte having the high bit cleared
+        self.asn1.split_inclusive(|&b| b & 0x80 == 0)
+    }
 
-        for it in self.0.iter().skip(1) {
-            s.push('.');
-            s = s + &it.to_string();
+    /// Return true if the arcs of `prefix` are the first arcs of this OID
+    ///
+    /// Both OIDs must be absolute, or both relative. An OID starts with itself.
+    pub fn starts_with(&self, prefix: &Oid) -> bool {
+        self.relative == prefix.relative
+            && self.asn1.starts_with(&prefix.asn1)
+            && !matches!(prefix.asn1.last(), Some(b) if b & 0x80 != 0)
+    }
+
+    /// Return the arcs following `prefix`, as a relative OID, or `None` if this OID does not start
+    /// with `prefix`
+    ///
+    /// The result is empty if both OIDs are equal.
+    pub fn strip_prefix(&self, prefix: &Oid) -> Option<Oid<'_>> {
+        if !self.starts_with(prefix) {
+            return None;
         }
+        Some(Oid::new_relative(Cow::Borrowed(
+            &self.asn1[prefix.asn1.len()..],
+        )))
+    }
 
-        s
+    /// Return the OID without its last arc, or `None` if there would be no arcs left (or only one
+    /// arc, for absolute OIDs)
+    pub fn parent(&self) -> Option<Oid<'_>> {
+        let last = self.subidentifiers().last()?;
+        let len = self.asn1.len() - last.len();
+        if len == 0 {
+            return None;
+        }
+        Some(Oid {
+            asn1: Cow::Borrowed(&self.asn1[..len]),
+            relative: self.relative,
+        })
+    }
+
+    /// Return a new OID, with `arc` appended
+    pub fn child(&self, arc: u64) -> Oid<'static> {
+        let mut v = self.asn1.to_vec();
+        encode_base128(&mut v, arc);
+        Oid {
+            asn1: Cow::Owned(v),
+            relative: self.relative,
+        }
+    }
+
+    /// Return a new OID, with the arcs of the relative OID `relative` appended, or `None` if
+    /// `relative` is not a relative OID
+    pub fn join(&self, relative: &Oid) -> Option<Oid<'static>> {
+        if !relative.relative {
+            return None;
+        }
+        let mut v = self.asn1.to_vec();
+        v.extend_from_slice(&relative.asn1);
+        Some(Oid {
+            asn1: Cow::Owned(v),
+            relative: self.relative,
+        })
+    }
+
+    /// Return an iterator on every arc, decoded lazily, or `None` if an arc does not fit in a
+    /// `u64`
+    ///
+    /// For absolute OIDs, the first subidentifier is split into the two first arcs. Arcs of any
+    /// size can be formatted using `Display`, or decoded using
+    /// [`iter_bigint`](#method.iter_bigint) (with the `bigint` feature).
+    pub fn iter(&self) -> Option<OidArcs<'_>> {
+        // leading 0x80 octets (non-minimal encoding) do not add significant bits
+        let fits = self.subidentifiers().all(|subid| {
+            let subid = &subid[subid.iter().take_while(|&&b| b == 0x80).count()..];
+            match subid.first() {
+                Some(b) => (subid.len() - 1) * 7 + (8 - (b & 0x7f).leading_zeros() as usize) <= 64,
+                None => true,
+            }
+        });
+        if !fits {
+            return None;
+        }
+        Some(OidArcs {
+            data: &self.asn1,
+            split_first: !self.relative,
+            second: None,
+        })
+    }
+
+    /// Return the arcs of the OID, or `None` if an arc does not fit in a `u64`
+    pub fn to_vec(&self) -> Option<Vec<u64>> {
+        self.iter().map(|arcs| arcs.collect())
     }
 
-    /// Return an iterator on every ID
-    pub fn iter(&self) -> slice::Iter<u64> {
-        self.0.iter()
+    /// Return an iterator on every arc, decoded as big integers
+    #[cfg(feature = "bigint")]
+    pub fn iter_bigint(&self) -> impl Iterator<Item = BigUint> + '_ {
+        let relative = self.relative;
+        self.subidentifiers()
+            .enumerate()
+            .flat_map(move |(idx, subid)| {
+                let digits: Vec<u8> = subid.iter().map(|b| b & 0x7f).collect();
+                let n = BigUint::from_radix_be(&digits, 128).unwrap_or_default();
+                if relative || idx > 0 {
+                    return vec![n];
+                }
+                // first subidentifier = X*40 + Y (See 8.19.4)
+                let limit = BigUint::from(80u8);
+                if n < limit {
+                    let y = n.clone() % 40u8;
+                    vec![n / 40u8, y]
+                } else {
+                    vec![BigUint::from(2u8), n - limit]
+                }
+            })
+    }
+}
+
+/// Iterator on the arcs of an OID, see [`Oid::iter`](struct.Oid.html#method.iter)
+#[derive(Clone, Debug)]
+pub struct OidArcs<'b> {
+    data: &'b [u8],
+    split_first: bool,
+    second: Option<u64>,
+}
+
+impl<'b> Iterator for OidArcs<'b> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if let Some(arc) = self.second.take() {
+            return Some(arc);
+        }
+        if self.data.is_empty() {
+            return None;
+        }
+        let len = self
+            .data
+            .iter()
+            .position(|&b| b & 0x80 == 0)
+            .map_or(self.data.len(), |n| n + 1);
+        let (subid, rem) = self.data.split_at(len);
+        self.data = rem;
+        // the size of the arcs is checked when building the iterator
+        let acc = subid
+            .iter()
+            .fold(0u64, |acc, &b| (acc << 7) | u64::from(b & 0x7f));
+        if self.split_first {
+            self.split_first = false;
+            // first subidentifier = X*40 + Y (See 8.19.4), and Y is not bounded if X = 2
+            let (x, y) = if acc < 80 {
+                (acc / 40, acc % 40)
+            } else {
+                (2, acc - 80)
+            };
+            self.second = Some(y);
+            return Some(x);
+        }
+        Some(acc)
+    }
+}
+
+/// Format a subidentifier in decimal, after subtracting `offset` from its value
+///
+/// The value is accumulated in limbs of 9 decimal digits, so arcs of any size can be formatted.
+fn write_subidentifier(f: &mut fmt::Formatter, subid: &[u8], offset: u32) -> fmt::Result {
+    const BASE: u64 = 1_000_000_000;
+    // little-endian limbs
+    let mut limbs: Vec<u64> = vec![0];
+    for &b in subid {
+        let mut carry = u64::from(b & 0x7f);
+        for limb in limbs.iter_mut() {
+            let t = *limb * 128 + carry;
+            *limb = t % BASE;
+            carry = t / BASE;
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+    }
+    let mut borrow = u64::from(offset);
+    for limb in limbs.iter_mut() {
+        if *limb >= borrow {
+            *limb -= borrow;
+            break;
+        }
+        *limb = *limb + BASE - borrow;
+        borrow = 1;
     }
+    while limbs.len() > 1 && limbs[limbs.len() - 1] == 0 {
+        limbs.pop();
+    }
+    let mut it = limbs.iter().rev();
+    write!(f, "{}", it.next().unwrap_or(&0))?;
+    for limb in it {
+        write!(f, "{:09}", limb)?;
+    }
+    Ok(())
 }
 
-impl fmt::Display for Oid {
+impl<'a> fmt::Display for Oid<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(&self.to_string())
+        for (idx, subid) in self.subidentifiers().enumerate() {
+            if idx > 0 {
+                f.write_str(".")?;
+            }
+            if idx > 0 || self.relative {
+                write_subidentifier(f, subid, 0)?;
+                continue;
+            }
+            // first subidentifier = X*40 + Y (See 8.19.4)
+            let subid = &subid[subid.iter().take_while(|&&b| b == 0x80).count()..];
+            match subid {
+                // incomplete encoding, decoded as 0 by `iter`
+                [] => f.write_str("0.0")?,
+                [b] if *b < 80 => write!(f, "{}.{}", b / 40, b % 40)?,
+                _ => {
+                    f.write_str("2.")?;
+                    write_subidentifier(f, subid, 80)?;
+                }
+            }
+        }
+        Ok(())
     }
 }
 
-impl fmt::Debug for Oid {
+/// Compare two encoded subidentifiers by value
+fn cmp_subidentifiers(a: &[u8], b: &[u8]) -> Ordering {
+    let a = &a[a.iter().take_while(|&&b| b == 0x80).count()..];
+    let b = &b[b.iter().take_while(|&&b| b == 0x80).count()..];
+    // without leading zeroes, a longer encoding is a larger value
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// OIDs are ordered by their arcs (so a parent comes before its children), absolute OIDs coming
+/// before relative OIDs. OIDs with the same arcs, but different encodings, are ordered by their
+/// encoded bytes.
+impl<'a> Ord for Oid<'a> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.relative
+            .cmp(&other.relative)
+            .then_with(|| {
+                let mut a = self.subidentifiers();
+                let mut b = other.subidentifiers();
+                loop {
+                    match (a.next(), b.next()) {
+                        (None, None) => return Ordering::Equal,
+                        (None, Some(_)) => return Ordering::Less,
+                        (Some(_), None) => return Ordering::Greater,
+                        (Some(x), Some(y)) => match cmp_subidentifiers(x, y) {
+                            Ordering::Equal => (),
+                            o => return o,
+                        },
+                    }
+                }
+            })
+            .then_with(|| self.asn1.cmp(&other.asn1))
+    }
+}
+
+impl<'a> PartialOrd for Oid<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> fmt::Debug for Oid<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(&format!("OID({})", self.to_string()))
+        let prefix = if self.relative { "REL-OID" } else { "OID" };
+        write!(f, "{}({})", prefix, self)
+    }
+}
+
+/// Append the base-128 encoding of the decimal number `s`, after adding `offset` to it
+fn encode_decimal(v: &mut Vec<u8>, s: &str, offset: u8) {
+    // big-endian decimal digits
+    let mut digits: Vec<u8> = s.bytes().map(|b| b - b'0').collect();
+    let mut carry = offset;
+    for d in digits.iter_mut().rev() {
+        let t = *d + carry;
+        *d = t % 10;
+        carry = t / 10;
+    }
+    if carry > 0 {
+        digits.insert(0, carry);
+    }
+    // repeated division by 128
+    let mut groups = Vec::new();
+    loop {
+        let mut rem = 0u16;
+        let mut quotient = Vec::with_capacity(digits.len());
+        for &d in &digits {
+            let cur = rem * 10 + u16::from(d);
+            if cur >= 128 || !quotient.is_empty() {
+                quotient.push((cur / 128) as u8);
+            }
+            rem = cur % 128;
+        }
+        groups.push(rem as u8);
+        if quotient.is_empty() {
+            break;
+        }
+        digits = quotient;
+    }
+    for (idx, g) in groups.iter().enumerate().rev() {
+        v.push(if idx > 0 { g | 0x80 } else { *g });
     }
 }
 
-impl FromStr for Oid {
-    type Err = ParseIntError;
+/// Parse an OID in dotted form, for ex. `1.2.840.113549.1.1.5`
+///
+/// Arcs can be arbitrarily large. The arcs must be written in canonical form (without leading
+/// zeroes), so that parsing the `Display` output of an OID returns the same OID.
+impl FromStr for Oid<'static> {
+    type Err = OidParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let v: Result<Vec<_>, ParseIntError> = s.split(".").map(|c| c.parse::<u64>()).collect();
-        v.map(|v| Oid(v))
+        let arcs: Vec<&str> = s.split('.').collect();
+        let valid = |a: &&str| {
+            !a.is_empty()
+                && a.bytes().all(|b| b.is_ascii_digit())
+                && (a.len() == 1 || !a.starts_with('0'))
+        };
+        if !arcs.iter().all(valid) {
+            return Err(OidParseError::ParseIntError);
+        }
+        if arcs.len() < 2 {
+            return Err(OidParseError::TooShort);
+        }
+        let first = match arcs[0] {
+            "0" => 0,
+            "1" => 1,
+            "2" => 2,
+            _ => return Err(OidParseError::FirstComponentsTooLarge),
+        };
+        if first < 2 && (arcs[1].len() > 2 || arcs[1].parse::<u8>().map_or(true, |y| y >= 40)) {
+            return Err(OidParseError::FirstComponentsTooLarge);
+        }
+        let mut v = Vec::new();
+        encode_decimal(&mut v, arcs[1], first * 40);
+        for arc in &arcs[2..] {
+            encode_decimal(&mut v, arc, 0);
+        }
+        Ok(Oid::new(Cow::Owned(v)))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::error::OidParseError;
     use crate::oid::Oid;
+    use std::borrow::Cow;
     use std::str::FromStr;
 
     #[test]
     fn test_oid_fmt() {
-        let oid = Oid::from(&[1, 2, 840, 113549, 1, 1, 5]);
+        let oid = Oid::from(&[1, 2, 840, 113549, 1, 1, 5]).unwrap();
         assert_eq!(format!("{}", oid), "1.2.840.113549.1.1.5".to_owned());
         assert_eq!(format!("{:?}", oid), "OID(1.2.840.113549.1.1.5)".to_owned());
+        let oid = Oid::from_relative(&[8571, 3, 2]).unwrap();
+        assert_eq!(format!("{:?}", oid), "REL-OID(8571.3.2)".to_owned());
     }
 
     #[test]
     fn test_oid_from_str() {
-        let oid_ref = Oid::from(&[1, 2, 840, 113549, 1, 1, 5]);
+        let oid_ref = Oid::from(&[1, 2, 840, 113549, 1, 1, 5]).unwrap();
         let oid = Oid::from_str("1.2.840.113549.1.1.5").unwrap();
         assert_eq!(oid_ref, oid);
+        assert_eq!(Oid::from_str("1"), Err(OidParseError::TooShort));
+        assert_eq!(Oid::from_str("1.2.a"), Err(OidParseError::ParseIntError));
+    }
+
+    #[test]
+    fn test_oid_borrowed() {
+        let bytes = [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x05];
+        let oid = Oid::new(Cow::Borrowed(&bytes));
+        assert_eq!(oid.bytes(), &bytes);
+        assert_eq!(oid.to_vec(), Some(vec![1, 2, 840, 113549, 1, 1, 5]));
+        assert_eq!(
+            oid.to_owned(),
+            Oid::from(&[1, 2, 840, 113549, 1, 1, 5]).unwrap()
+        );
+        // same bytes, but relative
+        assert_ne!(oid, Oid::new_relative(Cow::Borrowed(&bytes)));
+        assert_eq!(
+            Oid::from(&[2, 999, 3]).unwrap().bytes(),
+            &[0x88, 0x37, 0x03]
+        );
+        assert_eq!(
+            Oid::from(&[1, 40]),
+            Err(OidParseError::FirstComponentsTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_oid_first_arcs() {
+        // Dubuisson 433: the first subidentifier is >= 128
+        let oid = Oid::new(Cow::Borrowed(&[0x81, 0x7a, 0x01, 0x10, 0x09]));
+        assert_eq!(oid.to_vec(), Some(vec![2, 170, 1, 16, 9]));
+        assert_eq!(oid.to_string(), "2.170.1.16.9");
+        assert_eq!(Oid::from(&[2, 170, 1, 16, 9]).unwrap(), oid);
+        // 2.48: first subidentifier is 128
+        let oid = Oid::from_str("2.48").unwrap();
+        assert_eq!(oid.bytes(), &[0x81, 0x00]);
+        assert_eq!(oid.to_vec(), Some(vec![2, 48]));
+        for s in &["0.0", "0.39", "1.0", "2.0", "2.39", "2.40", "2.79", "2.80"] {
+            let oid = Oid::from_str(s).unwrap();
+            assert_eq!(&oid.to_string(), s);
+            assert_eq!(oid.to_vec().map(|v| v.len()), Some(2));
+        }
+    }
+
+    #[test]
+    fn test_oid_large_arcs() {
+        // UUID-based OID (X.667)
+        let s = "2.25.329800735698586629295641978511506172918";
+        let oid = Oid::from_str(s).unwrap();
+        assert_eq!(oid.to_string(), s);
+        assert_eq!(oid.iter().map(|arcs| arcs.count()), None);
+        assert_eq!(
+            oid.bytes(),
+            &[
+                0x69, 0x83, 0xf0, 0x9d, 0xa7, 0xeb, 0xcf, 0xde, 0xe0, 0xc7, 0xa1, 0xa7, 0xb2, 0xc0,
+                0x94, 0x8c, 0xc8, 0xf9, 0xd7, 0x76
+            ][..]
+        );
+        let s = "2.18446744073709551615.18446744073709551616";
+        let oid = Oid::from_str(s).unwrap();
+        assert_eq!(oid.to_string(), s);
+        assert!(oid.iter().is_none());
+        let oid = Oid::from_str("1.2.18446744073709551615").unwrap();
+        assert_eq!(oid.to_vec(), Some(vec![1, 2, u64::MAX]));
+        // non-minimal encoding (accepted by BER)
+        let oid = Oid::new(Cow::Borrowed(&[0x2a, 0x80, 0x80, 0x03]));
+        assert_eq!(oid.to_vec(), Some(vec![1, 2, 3]));
+        assert_eq!(oid.to_string(), "1.2.3");
+        let oid = Oid::new_relative(Cow::Borrowed(&[
+            0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x81, 0x00,
+        ]));
+        assert_eq!(oid.to_vec(), Some(vec![128]));
+        assert_eq!(oid.to_string(), "128");
     }
 
+    #[test]
+    fn test_oid_from_str_invalid() {
+        for s in &[
+            "", "1", "1.", ".1", "1..2", "1.02", "1.+2", "1.2a", "3.1", "1.40", "0.100",
+        ] {
+            assert!(Oid::from_str(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_oid_hierarchy() {
+        let pen = Oid::from_str("1.3.6.1.4.1.55555").unwrap();
+        let oid = Oid::from_str("1.3.6.1.4.1.55555.3.200").unwrap();
+        assert!(oid.starts_with(&pen));
+        assert!(oid.starts_with(&oid));
+        assert!(!pen.starts_with(&oid));
+        // 1.3.6.1.4.1.5555 is not a prefix, even if its encoding is a prefix of the encoding
+        let other = Oid::from_str("1.3.6.1.4.1.5555").unwrap();
+        assert!(!oid.starts_with(&other));
+        assert!(!oid.starts_with(&Oid::new(Cow::Borrowed(&oid.bytes()[..7]))));
+        let rel = oid.strip_prefix(&pen).unwrap();
+        assert_eq!(rel, Oid::from_relative(&[3, 200]).unwrap());
+        assert!(!rel.starts_with(&pen));
+        assert!(rel.starts_with(&Oid::from_relative(&[3]).unwrap()));
+        assert_eq!(pen.strip_prefix(&pen).map(|o| o.bytes().len()), Some(0));
+        assert_eq!(pen.strip_prefix(&oid), None);
+        assert_eq!(pen.join(&rel), Some(oid.clone()));
+        assert_eq!(pen.join(&pen), None);
+        assert_eq!(pen.child(3).child(200), oid);
+        assert_eq!(rel.child(1).to_string(), "3.200.1");
+        let parent = oid.parent().unwrap();
+        assert_eq!(parent.parent(), Some(pen.clone()));
+        assert_eq!(Oid::from_str("2.999").unwrap().parent(), None);
+        assert_eq!(
+            Oid::from_str("1.2.3")
+                .unwrap()
+                .parent()
+                .unwrap()
+                .to_string(),
+            "1.2"
+        );
+        assert_eq!(Oid::from_relative(&[3]).unwrap().parent(), None);
+    }
+
+    #[test]
+    fn test_oid_ord() {
+        use std::collections::HashSet;
+        let mut v: Vec<Oid> = [
+            "2.5.4.3",
+            "1.3.6.1.2.1.1.1",
+            "1.3.6.1.2.1.1",
+            "1.3.6.1.2.1.16383",
+            "1.3.6.1.2.1.16384",
+            "1.3.6.1.2.1.2",
+            "1.2.840",
+            "2.100",
+            "0.9",
+        ]
+        .iter()
+        .map(|s| Oid::from_str(s).unwrap())
+        .collect();
+        v.push(Oid::from_relative(&[1]).unwrap());
+        v.sort();
+        let sorted: Vec<String> = v.iter().map(|o| format!("{:?}", o)).collect();
+        assert_eq!(
+            sorted,
+            vec![
+                "OID(0.9)",
+                "OID(1.2.840)",
+                "OID(1.3.6.1.2.1.1)",
+                "OID(1.3.6.1.2.1.1.1)",
+                "OID(1.3.6.1.2.1.2)",
+                "OID(1.3.6.1.2.1.16383)",
+                "OID(1.3.6.1.2.1.16384)",
+                "OID(2.5.4.3)",
+                "OID(2.100)",
+                "REL-OID(1)",
+            ]
+        );
+        // same arcs, different encodings
+        let a = Oid::new(Cow::Borrowed(&[0x2a, 0x03]));
+        let b = Oid::new(Cow::Borrowed(&[0x2a, 0x80, 0x03]));
+        assert!(a < b && b < Oid::from_str("1.2.4").unwrap());
+        let set: HashSet<Oid> = v.iter().cloned().chain(v.iter().cloned()).collect();
+        assert_eq!(set.len(), v.len());
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_oid_bigint() {
+        use num_bigint::BigUint;
+        let oid = Oid::from_str("2.25.329800735698586629295641978511506172918").unwrap();
+        let arcs: Vec<BigUint> = oid.iter_bigint().collect();
+        assert_eq!(arcs.len(), 3);
+        assert_eq!(arcs[1], BigUint::from(25u8));
+        assert_eq!(
+            arcs[2].to_string(),
+            "329800735698586629295641978511506172918"
+        );
+    }
 }
@@ -0,0 +1,419 @@
+//! OID internationalized resource identifiers (OID-IRI)
+//!
+//! An OID-IRI (X.680 section 34) identifies a node of the OID tree using the labels of the arcs
+//! from the root, separated by `/`, for ex. `/ISO/Member-Body/250` (1.2.250). Each label is
+//! either an integer (the number of the arc), or a Unicode label (X.660 section 7.5), which must be
+//! registered to be converted to the numeric form. A RELATIVE-OID-IRI has the same form, without
+//! the leading `/`, and is relative to a known node.
+//!
+//! Some Unicode labels are *long arcs*: they go from the root to a node which is not a top-level
+//! node (for ex. `/UUID` is the same as `/Joint-ISO-ITU-T/UUID`, or 2.25).
+//!
+//! An [`IriRegistry`](struct.IriRegistry.html) contains the Unicode labels, and converts OIDs
+//! between the two forms:
+//!
+//! ```rust
+//! # extern crate der_parser;
+//! use der_parser::oid::Oid;
+//! use der_parser::oid_iri::{IriArc, IriRegistry};
+//!
+//! # fn main() {
+//! let mut registry = IriRegistry::builtin();
+//! let oid = registry.to_oid("/Joint-ISO-ITU-T/Example/1").unwrap();
+//! assert_eq!(oid.to_string(), "2.999.1");
+//! registry.insert(IriArc::new(&[2, 999], "Test", &[1]).unwrap());
+//! assert_eq!(registry.to_iri(&oid), Some("/Joint-ISO-ITU-T/Example/Test".to_string()));
+//! // long arc
+//! let oid: Oid = "2.25.1234".parse().unwrap();
+//! assert_eq!(registry.to_iri(&oid), Some("/UUID/1234".to_string()));
+//! # }
+//! ```
+
+use crate::error::OidParseError;
+use crate::oid::Oid;
+use std::borrow::Cow;
+
+/// Test if `c` is a `ucschar` character (RFC 3987), allowed in Unicode labels
+fn is_ucschar(c: char) -> bool {
+    match c as u32 {
+        0xa0..=0xd7ff | 0xf900..=0xfdcf | 0xfdf0..=0xffef | 0xe1000..=0xefffd => true,
+        // the last two code points of each plane are not allowed
+        c @ 0x10000..=0xdffff => c & 0xffff <= 0xfffd,
+        _ => false,
+    }
+}
+
+/// Test if `label` is a valid arc identifier (X.660 section 7.5): an integer without leading
+/// zeroes, or a non-integer Unicode label
+///
+/// Non-integer Unicode labels contain letters, digits, `-`, `.`, `_`, `~` and non-ASCII Unicode
+/// characters. They cannot start or end with `-`, or have `--` as their third and fourth
+/// characters.
+pub fn is_valid_label(label: &str) -> bool {
+    if label.is_empty() {
+        return false;
+    }
+    if is_integer_label(label) {
+        return label == "0" || !label.starts_with('0');
+    }
+    label
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~') || is_ucschar(c))
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.get(2..4) != Some("--")
+}
+
+fn is_integer_label(label: &str) -> bool {
+    label.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Test if `iri` is a valid OID-IRI (`/` followed by labels separated by `/`) or, if `relative`
+/// is true, a valid RELATIVE-OID-IRI (labels separated by `/`)
+pub fn is_valid_oid_iri(iri: &str, relative: bool) -> bool {
+    let labels = if relative {
+        iri
+    } else {
+        match iri.strip_prefix('/') {
+            Some(labels) => labels,
+            None => return false,
+        }
+    };
+    labels.split('/').all(is_valid_label)
+}
+
+/// Test if the arcs of `arcs` are the decimal numbers of `path`
+fn same_arcs<S: AsRef<str>>(arcs: &[u64], path: &[S]) -> bool {
+    arcs.len() == path.len()
+        && arcs
+            .iter()
+            .zip(path)
+            .all(|(a, p)| a.to_string() == p.as_ref())
+}
+
+/// A Unicode label, identifying one or more arcs below a node
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IriArc {
+    parent: Cow<'static, [u64]>,
+    label: Cow<'static, str>,
+    arcs: Cow<'static, [u64]>,
+}
+
+impl IriArc {
+    /// Build a new label for the arcs `arcs` below the node `parent` (the root if empty)
+    ///
+    /// `arcs` contains a single arc, except for long arcs. The label must be a valid non-integer
+    /// Unicode label.
+    pub fn new(parent: &[u64], label: &str, arcs: &[u64]) -> Result<IriArc, OidParseError> {
+        if !is_valid_label(label) || is_integer_label(label) {
+            return Err(OidParseError::InvalidIri);
+        }
+        if arcs.is_empty() {
+            return Err(OidParseError::TooShort);
+        }
+        Ok(IriArc {
+            parent: Cow::Owned(parent.to_vec()),
+            label: Cow::Owned(label.to_string()),
+            arcs: Cow::Owned(arcs.to_vec()),
+        })
+    }
+
+    const fn builtin(parent: &'static [u64], label: &'static str, arcs: &'static [u64]) -> IriArc {
+        IriArc {
+            parent: Cow::Borrowed(parent),
+            label: Cow::Borrowed(label),
+            arcs: Cow::Borrowed(arcs),
+        }
+    }
+
+    /// Arcs of the parent node
+    pub fn parent(&self) -> &[u64] {
+        &self.parent
+    }
+
+    /// The Unicode label
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Arcs identified by the label, below the parent node
+    pub fn arcs(&self) -> &[u64] {
+        &self.arcs
+    }
+
+    /// Return true if the label is a long arc (identifies more than one arc)
+    pub fn is_long(&self) -> bool {
+        self.arcs.len() > 1
+    }
+}
+
+/// A set of Unicode labels, to convert OIDs between the numeric and the IRI forms
+///
+/// Unicode labels are compared exactly (case-sensitive). When converting to the IRI form, the
+/// labels identifying the most arcs are preferred.
+#[derive(Clone, Debug)]
+pub struct IriRegistry {
+    arcs: Cow<'static, [IriArc]>,
+}
+
+impl Default for IriRegistry {
+    fn default() -> Self {
+        IriRegistry::new()
+    }
+}
+
+impl IriRegistry {
+    /// Build an empty registry
+    ///
+    /// Only OID-IRIs made of integer labels can be converted.
+    pub const fn new() -> IriRegistry {
+        IriRegistry {
+            arcs: Cow::Borrowed(&[]),
+        }
+    }
+
+    /// Build a registry containing the Unicode labels of the top-level arcs, of some of the arcs
+    /// below them, and the `/UUID` long arc
+    pub const fn builtin() -> IriRegistry {
+        IriRegistry {
+            arcs: Cow::Borrowed(BUILTIN),
+        }
+    }
+
+    /// Add a label, replacing the label with the same name below the same node, if any
+    pub fn insert(&mut self, arc: IriArc) {
+        let arcs = self.arcs.to_mut();
+        match arcs
+            .iter_mut()
+            .find(|a| a.parent == arc.parent && a.label == arc.label)
+        {
+            Some(a) => *a = arc,
+            None => arcs.push(arc),
+        }
+    }
+
+    /// Iterate over the labels
+    pub fn iter(&self) -> impl Iterator<Item = &IriArc> {
+        self.arcs.iter()
+    }
+
+    /// Follow the labels of `labels` (separated by `/`), starting from the node `path`
+    fn resolve(&self, path: &mut Vec<String>, labels: &str) -> Result<(), OidParseError> {
+        for label in labels.split('/') {
+            if is_integer_label(label) {
+                path.push(label.to_string());
+                continue;
+            }
+            let arc = self
+                .arcs
+                .iter()
+                .find(|a| a.label == label && same_arcs(&a.parent, path))
+                .ok_or(OidParseError::UnknownLabel)?;
+            path.extend(arc.arcs.iter().map(u64::to_string));
+        }
+        Ok(())
+    }
+
+    /// Labels of the arcs of `path`, starting after the node `path[..start]`
+    fn labels(&self, path: &[&str], start: usize) -> Vec<String> {
+        let mut labels = Vec::new();
+        let mut pos = start;
+        while pos < path.len() {
+            let best = self
+                .arcs
+                .iter()
+                .filter(|a| {
+                    a.arcs.len() <= path.len() - pos
+                        && same_arcs(&a.parent, &path[..pos])
+                        && same_arcs(&a.arcs, &path[pos..pos + a.arcs.len()])
+                })
+                .max_by_key(|a| a.arcs.len());
+            match best {
+                Some(a) => {
+                    labels.push(a.label.to_string());
+                    pos += a.arcs.len();
+                }
+                None => {
+                    labels.push(path[pos].to_string());
+                    pos += 1;
+                }
+            }
+        }
+        labels
+    }
+
+    /// Convert an OID-IRI to the numeric form
+    pub fn to_oid(&self, iri: &str) -> Result<Oid<'static>, OidParseError> {
+        if !is_valid_oid_iri(iri, false) {
+            return Err(OidParseError::InvalidIri);
+        }
+        let mut path = Vec::new();
+        self.resolve(&mut path, &iri[1..])?;
+        path.join(".").parse()
+    }
+
+    /// Convert a RELATIVE-OID-IRI, relative to the node `base`, to a relative OID
+    pub fn to_relative_oid(&self, base: &Oid, iri: &str) -> Result<Oid<'static>, OidParseError> {
+        if !is_valid_oid_iri(iri, true) || base.is_relative() {
+            return Err(OidParseError::InvalidIri);
+        }
+        let base_str = base.to_string();
+        let mut path: Vec<String> = base_str.split('.').map(String::from).collect();
+        self.resolve(&mut path, iri)?;
+        let oid: Oid = path.join(".").parse()?;
+        let relative = oid.strip_prefix(base).ok_or(OidParseError::InvalidIri)?;
+        Ok(relative.to_owned())
+    }
+
+    /// Convert an OID to the OID-IRI form, or return `None` if the OID is relative
+    ///
+    /// Arcs without a Unicode label are written as integers.
+    pub fn to_iri(&self, oid: &Oid) -> Option<String> {
+        if oid.is_relative() {
+            return None;
+        }
+        let s = oid.to_string();
+        let path: Vec<&str> = s.split('.').collect();
+        Some(format!("/{}", self.labels(&path, 0).join("/")))
+    }
+
+    /// Convert a relative OID to the RELATIVE-OID-IRI form, relative to the node `base`, or
+    /// return `None` if `relative` is not relative or `base` is relative
+    pub fn to_relative_iri(&self, base: &Oid, relative: &Oid) -> Option<String> {
+        if base.is_relative() {
+            return None;
+        }
+        let s = base.join(relative)?.to_string();
+        let path: Vec<&str> = s.split('.').collect();
+        // each arc of the relative OID ends with an octet having the high bit cleared
+        let n = relative.bytes().iter().filter(|&&b| b & 0x80 == 0).count();
+        let start = path.len() - n;
+        Some(self.labels(&path, start).join("/"))
+    }
+}
+
+#[rustfmt::skip]
+static BUILTIN: &[IriArc] = &[
+    IriArc::builtin(&[], "ITU-T", &[0]),
+    IriArc::builtin(&[], "ISO", &[1]),
+    IriArc::builtin(&[], "Joint-ISO-ITU-T", &[2]),
+    IriArc::builtin(&[], "UUID", &[2, 25]),
+    IriArc::builtin(&[0], "Recommendation", &[0]),
+    IriArc::builtin(&[0], "Administration", &[2]),
+    IriArc::builtin(&[0], "Network-Operator", &[3]),
+    IriArc::builtin(&[0], "Identified-Organization", &[4]),
+    IriArc::builtin(&[1], "Standard", &[0]),
+    IriArc::builtin(&[1], "Registration-Authority", &[1]),
+    IriArc::builtin(&[1], "Member-Body", &[2]),
+    IriArc::builtin(&[1], "Identified-Organization", &[3]),
+    IriArc::builtin(&[2], "ASN.1", &[1]),
+    IriArc::builtin(&[2], "Country", &[16]),
+    IriArc::builtin(&[2], "International-Organizations", &[23]),
+    IriArc::builtin(&[2], "UUID", &[25]),
+    IriArc::builtin(&[2], "Tag-Based", &[27]),
+    IriArc::builtin(&[2], "Example", &[999]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_iri_syntax() {
+        for s in &["/ISO/Member-Body/250", "/0", "/a.b_c~d/été/日本", "/UUID/0"] {
+            assert!(is_valid_oid_iri(s, false), "{}", s);
+            assert!(is_valid_oid_iri(&s[1..], true), "{}", s);
+        }
+        for s in &[
+            "",
+            "/",
+            "ISO",
+            "/ISO/",
+            "//ISO",
+            "/ISO//2",
+            "/01",
+            "/-a",
+            "/a-",
+            "/ab--c",
+            "/a b",
+            "/a#b",
+            "/\u{fffe}",
+            "/\u{e000}",
+        ] {
+            assert!(!is_valid_oid_iri(s, false), "{}", s);
+        }
+        assert!(!is_valid_oid_iri("/ISO", true));
+        assert!(!is_valid_oid_iri("", true));
+    }
+
+    #[test]
+    fn test_iri_conversion() {
+        let registry = IriRegistry::builtin();
+        let oid = registry.to_oid("/ISO/Member-Body/250/1").unwrap();
+        assert_eq!(oid.to_string(), "1.2.250.1");
+        assert_eq!(
+            registry.to_iri(&oid),
+            Some("/ISO/Member-Body/250/1".to_string())
+        );
+        assert_eq!(registry.to_oid("/1/2/250/1"), Ok(oid));
+        // long arc, and large arcs
+        let s = "2.25.329800735698586629295641978511506172918";
+        let oid = Oid::from_str(s).unwrap();
+        let iri = "/UUID/329800735698586629295641978511506172918";
+        assert_eq!(registry.to_iri(&oid).as_deref(), Some(iri));
+        assert_eq!(registry.to_oid(iri), Ok(oid.clone()));
+        assert_eq!(
+            registry.to_oid("/Joint-ISO-ITU-T/UUID/329800735698586629295641978511506172918"),
+            Ok(oid)
+        );
+        // errors
+        assert_eq!(
+            registry.to_oid("/ISO/Unknown"),
+            Err(OidParseError::UnknownLabel)
+        );
+        assert_eq!(
+            registry.to_oid("/Member-Body"),
+            Err(OidParseError::UnknownLabel)
+        );
+        assert_eq!(registry.to_oid("/ISO"), Err(OidParseError::TooShort));
+        assert_eq!(
+            registry.to_oid("/3/1"),
+            Err(OidParseError::FirstComponentsTooLarge)
+        );
+        assert_eq!(registry.to_oid("ISO/1"), Err(OidParseError::InvalidIri));
+        assert_eq!(
+            IriRegistry::new().to_oid("/ISO/1"),
+            Err(OidParseError::UnknownLabel)
+        );
+    }
+
+    #[test]
+    fn test_iri_relative() {
+        let mut registry = IriRegistry::builtin();
+        let base = Oid::from_str("1.3.6.1.4.1.55555").unwrap();
+        registry.insert(IriArc::new(&[1, 3, 6, 1, 4, 1, 55555], "Products", &[3]).unwrap());
+        let rel = registry.to_relative_oid(&base, "Products/7").unwrap();
+        assert_eq!(rel, Oid::from_relative(&[3, 7]).unwrap());
+        assert_eq!(
+            registry.to_relative_iri(&base, &rel),
+            Some("Products/7".to_string())
+        );
+        assert_eq!(
+            registry.to_relative_oid(&base, "Unknown/7"),
+            Err(OidParseError::UnknownLabel)
+        );
+        assert_eq!(registry.to_relative_iri(&base, &base), None);
+        // replace a label
+        registry.insert(IriArc::new(&[1, 3, 6, 1, 4, 1, 55555], "Products", &[4]).unwrap());
+        assert_eq!(registry.iter().count(), BUILTIN.len() + 1);
+        assert_eq!(
+            registry.to_relative_iri(&base, &rel),
+            Some("3/7".to_string())
+        );
+        assert_eq!(
+            IriArc::new(&[], "123", &[1]),
+            Err(OidParseError::InvalidIri)
+        );
+    }
+}
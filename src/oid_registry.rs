@@ -0,0 +1,397 @@
+//! Registry of object identifiers
+//!
+//! This module (enabled by the `oid-registry` feature) associates OIDs with a short name, a long
+//! name and a description. It contains a built-in table of common OIDs (PKIX, PKCS, X.500
+//! attributes, elliptic curves, hash functions, SNMP), which can be queried directly using
+//! [`lookup`](fn.lookup.html) and [`lookup_name`](fn.lookup_name.html). The names follow the
+//! conventions of OpenSSL when possible.
+//!
+//! Applications with their own OIDs can build an [`OidRegistry`](struct.OidRegistry.html),
+//! optionally starting from the built-in table, and insert new entries:
+//!
+//! ```rust
+//! # extern crate der_parser;
+//! use der_parser::oid;
+//! use der_parser::oid_registry::{OidEntry, OidRegistry};
+//!
+//! # fn main() {
+//! let mut registry = OidRegistry::with_builtin();
+//! registry.insert(OidEntry::new(
+//!     oid!(1.3.6.1.4.1.55555.1),
+//!     "myExtension",
+//!     "My Extension",
+//!     "Private certificate extension",
+//! ));
+//!
+//! let entry = registry.get(&oid!(1.2.840.113549.1.1.11)).expect("unknown OID");
+//! assert_eq!(entry.long_name(), "sha256WithRSAEncryption");
+//! let entry = registry.get_by_name("myExtension").expect("unknown name");
+//! assert_eq!(entry.oid().to_string(), "1.3.6.1.4.1.55555.1");
+//! # }
+//! ```
+//!
+//! Registries can also be used by the pretty printer, see
+//! [`PrettyBer::set_registry`](../ber/struct.PrettyBer.html#method.set_registry).
+
+use crate::oid;
+use crate::oid::Oid;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// An entry of an OID registry
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OidEntry {
+    oid: Oid<'static>,
+    sn: Cow<'static, str>,
+    ln: Cow<'static, str>,
+    description: Cow<'static, str>,
+}
+
+impl OidEntry {
+    /// Build a new entry
+    pub fn new<S, L, D>(oid: Oid<'static>, sn: S, ln: L, description: D) -> OidEntry
+    where
+        S: Into<Cow<'static, str>>,
+        L: Into<Cow<'static, str>>,
+        D: Into<Cow<'static, str>>,
+    {
+        OidEntry {
+            oid,
+            sn: sn.into(),
+            ln: ln.into(),
+            description: description.into(),
+        }
+    }
+
+    const fn builtin(
+        oid: Oid<'static>,
+        sn: &'static str,
+        ln: &'static str,
+        description: &'static str,
+    ) -> OidEntry {
+        OidEntry {
+            oid,
+            sn: Cow::Borrowed(sn),
+            ln: Cow::Borrowed(ln),
+            description: Cow::Borrowed(description),
+        }
+    }
+
+    /// The object identifier
+    pub fn oid(&self) -> &Oid<'static> {
+        &self.oid
+    }
+
+    /// Short name (for ex. `CN`)
+    pub fn short_name(&self) -> &str {
+        &self.sn
+    }
+
+    /// Long name (for ex. `commonName`)
+    pub fn long_name(&self) -> &str {
+        &self.ln
+    }
+
+    /// Human-readable description
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// A set of OID entries, indexed by OID and by name
+///
+/// Names are matched exactly against both the short and long names of the entries. If several
+/// entries have the same name, the last one inserted is returned.
+#[derive(Clone, Debug, Default)]
+pub struct OidRegistry {
+    entries: Vec<OidEntry>,
+    /// Encoded bytes of the OIDs, for absolute (`[0]`) and relative (`[1]`) OIDs: the same bytes
+    /// can encode both an absolute and a relative OID
+    by_oid: [HashMap<Vec<u8>, usize>; 2],
+    by_name: HashMap<Cow<'static, str>, usize>,
+}
+
+impl OidRegistry {
+    /// Build an empty registry
+    pub fn new() -> OidRegistry {
+        OidRegistry::default()
+    }
+
+    /// Build a registry containing the built-in entries
+    pub fn with_builtin() -> OidRegistry {
+        let mut registry = OidRegistry::new();
+        for entry in BUILTIN {
+            registry.insert(entry.clone());
+        }
+        registry
+    }
+
+    /// Insert an entry, and return the previous entry for the same OID, if any
+    pub fn insert(&mut self, entry: OidEntry) -> Option<OidEntry> {
+        let names = [entry.sn.clone(), entry.ln.clone()];
+        let kind = entry.oid.is_relative() as usize;
+        if let Some(&idx) = self.by_oid[kind].get(entry.oid.bytes()) {
+            let previous = std::mem::replace(&mut self.entries[idx], entry);
+            for name in &[&previous.sn, &previous.ln] {
+                if self.by_name.get(*name) == Some(&idx) {
+                    self.by_name.remove(*name);
+                }
+            }
+            for name in &names {
+                self.by_name.insert(name.clone(), idx);
+            }
+            return Some(previous);
+        }
+        let idx = self.entries.len();
+        self.by_oid[kind].insert(entry.oid.bytes().to_vec(), idx);
+        self.entries.push(entry);
+        for name in &names {
+            self.by_name.insert(name.clone(), idx);
+        }
+        None
+    }
+
+    /// Find the entry for an OID
+    pub fn get(&self, oid: &Oid) -> Option<&OidEntry> {
+        self.by_oid[oid.is_relative() as usize]
+            .get(oid.bytes())
+            .map(|&idx| &self.entries[idx])
+    }
+
+    /// Find an entry by short or long name
+    pub fn get_by_name(&self, name: &str) -> Option<&OidEntry> {
+        self.by_name.get(name).map(|&idx| &self.entries[idx])
+    }
+
+    /// Find the OID registered with a short or long name
+    pub fn oid_by_name(&self, name: &str) -> Option<&Oid<'static>> {
+        self.get_by_name(name).map(OidEntry::oid)
+    }
+
+    /// Iterate over the entries, in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = &OidEntry> {
+        self.entries.iter()
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Test if the registry is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// The built-in entries
+pub fn builtin_entries() -> &'static [OidEntry] {
+    BUILTIN
+}
+
+/// Find the built-in entry for an OID
+///
+/// This does not allocate, but is a linear search: to do many lookups, or to add entries, use
+/// [`OidRegistry::with_builtin`](struct.OidRegistry.html#method.with_builtin).
+pub fn lookup(oid: &Oid) -> Option<&'static OidEntry> {
+    BUILTIN.iter().find(|e| e.oid == *oid)
+}
+
+/// Find a built-in entry by short or long name
+pub fn lookup_name(name: &str) -> Option<&'static OidEntry> {
+    BUILTIN.iter().find(|e| e.sn == name || e.ln == name)
+}
+
+#[rustfmt::skip]
+static BUILTIN: &[OidEntry] = &[
+    // PKCS #1
+    OidEntry::builtin(oid!(1.2.840.113549.1.1.1), "rsaEncryption", "rsaEncryption", "PKCS #1 RSA encryption"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.1.4), "RSA-MD5", "md5WithRSAEncryption", "PKCS #1 RSA signature with MD5"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.1.5), "RSA-SHA1", "sha1WithRSAEncryption", "PKCS #1 RSA signature with SHA-1"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.1.7), "RSAES-OAEP", "rsaesOaep", "PKCS #1 RSAES-OAEP encryption scheme"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.1.8), "MGF1", "mgf1", "PKCS #1 mask generation function MGF1"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.1.10), "RSASSA-PSS", "rsassaPss", "PKCS #1 RSASSA-PSS signature scheme"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.1.11), "RSA-SHA256", "sha256WithRSAEncryption", "PKCS #1 RSA signature with SHA-256"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.1.12), "RSA-SHA384", "sha384WithRSAEncryption", "PKCS #1 RSA signature with SHA-384"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.1.13), "RSA-SHA512", "sha512WithRSAEncryption", "PKCS #1 RSA signature with SHA-512"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.1.14), "RSA-SHA224", "sha224WithRSAEncryption", "PKCS #1 RSA signature with SHA-224"),
+    // PKCS #5
+    OidEntry::builtin(oid!(1.2.840.113549.1.5.12), "PBKDF2", "PBKDF2", "PKCS #5 password-based key derivation function 2"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.5.13), "PBES2", "PBES2", "PKCS #5 password-based encryption scheme 2"),
+    // PKCS #7
+    OidEntry::builtin(oid!(1.2.840.113549.1.7.1), "pkcs7-data", "pkcs7-data", "PKCS #7 data content type"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.7.2), "pkcs7-signedData", "pkcs7-signedData", "PKCS #7 signed data content type"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.7.3), "pkcs7-envelopedData", "pkcs7-envelopedData", "PKCS #7 enveloped data content type"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.7.5), "pkcs7-digestData", "pkcs7-digestData", "PKCS #7 digested data content type"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.7.6), "pkcs7-encryptedData", "pkcs7-encryptedData", "PKCS #7 encrypted data content type"),
+    // PKCS #9
+    OidEntry::builtin(oid!(1.2.840.113549.1.9.1), "emailAddress", "emailAddress", "PKCS #9 e-mail address attribute"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.9.3), "contentType", "contentType", "PKCS #9 content type attribute"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.9.4), "messageDigest", "messageDigest", "PKCS #9 message digest attribute"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.9.5), "signingTime", "signingTime", "PKCS #9 signing time attribute"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.9.7), "challengePassword", "challengePassword", "PKCS #9 challenge password attribute"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.9.14), "extReq", "extensionRequest", "PKCS #9 extension request attribute"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.9.20), "friendlyName", "friendlyName", "PKCS #9 friendly name attribute"),
+    OidEntry::builtin(oid!(1.2.840.113549.1.9.21), "localKeyID", "localKeyID", "PKCS #9 local key identifier attribute"),
+    // Hash functions and MACs
+    OidEntry::builtin(oid!(1.2.840.113549.2.5), "MD5", "md5", "MD5 hash function"),
+    OidEntry::builtin(oid!(1.2.840.113549.2.9), "hmacWithSHA256", "hmacWithSHA256", "HMAC with SHA-256"),
+    OidEntry::builtin(oid!(1.3.14.3.2.26), "SHA1", "sha1", "SHA-1 hash function"),
+    OidEntry::builtin(oid!(2.16.840.1.101.3.4.2.1), "SHA256", "sha256", "SHA-256 hash function"),
+    OidEntry::builtin(oid!(2.16.840.1.101.3.4.2.2), "SHA384", "sha384", "SHA-384 hash function"),
+    OidEntry::builtin(oid!(2.16.840.1.101.3.4.2.3), "SHA512", "sha512", "SHA-512 hash function"),
+    OidEntry::builtin(oid!(2.16.840.1.101.3.4.2.4), "SHA224", "sha224", "SHA-224 hash function"),
+    OidEntry::builtin(oid!(2.16.840.1.101.3.4.2.8), "SHA3-256", "sha3-256", "SHA3-256 hash function"),
+    OidEntry::builtin(oid!(2.16.840.1.101.3.4.2.9), "SHA3-384", "sha3-384", "SHA3-384 hash function"),
+    OidEntry::builtin(oid!(2.16.840.1.101.3.4.2.10), "SHA3-512", "sha3-512", "SHA3-512 hash function"),
+    // Ciphers
+    OidEntry::builtin(oid!(2.16.840.1.101.3.4.1.2), "AES-128-CBC", "aes-128-cbc", "AES-128 in CBC mode"),
+    OidEntry::builtin(oid!(2.16.840.1.101.3.4.1.42), "AES-256-CBC", "aes-256-cbc", "AES-256 in CBC mode"),
+    // DSA and elliptic curves
+    OidEntry::builtin(oid!(1.2.840.10040.4.1), "DSA", "dsaEncryption", "DSA public key"),
+    OidEntry::builtin(oid!(1.2.840.10040.4.3), "DSA-SHA1", "dsaWithSHA1", "DSA signature with SHA-1"),
+    OidEntry::builtin(oid!(1.2.840.10045.2.1), "id-ecPublicKey", "id-ecPublicKey", "Elliptic curve public key"),
+    OidEntry::builtin(oid!(1.2.840.10045.4.1), "ecdsa-with-SHA1", "ecdsa-with-SHA1", "ECDSA signature with SHA-1"),
+    OidEntry::builtin(oid!(1.2.840.10045.4.3.1), "ecdsa-with-SHA224", "ecdsa-with-SHA224", "ECDSA signature with SHA-224"),
+    OidEntry::builtin(oid!(1.2.840.10045.4.3.2), "ecdsa-with-SHA256", "ecdsa-with-SHA256", "ECDSA signature with SHA-256"),
+    OidEntry::builtin(oid!(1.2.840.10045.4.3.3), "ecdsa-with-SHA384", "ecdsa-with-SHA384", "ECDSA signature with SHA-384"),
+    OidEntry::builtin(oid!(1.2.840.10045.4.3.4), "ecdsa-with-SHA512", "ecdsa-with-SHA512", "ECDSA signature with SHA-512"),
+    OidEntry::builtin(oid!(1.2.840.10045.3.1.1), "prime192v1", "prime192v1", "NIST P-192 elliptic curve"),
+    OidEntry::builtin(oid!(1.2.840.10045.3.1.7), "prime256v1", "prime256v1", "NIST P-256 elliptic curve"),
+    OidEntry::builtin(oid!(1.3.132.0.10), "secp256k1", "secp256k1", "SECG secp256k1 elliptic curve"),
+    OidEntry::builtin(oid!(1.3.132.0.33), "secp224r1", "secp224r1", "NIST P-224 elliptic curve"),
+    OidEntry::builtin(oid!(1.3.132.0.34), "secp384r1", "secp384r1", "NIST P-384 elliptic curve"),
+    OidEntry::builtin(oid!(1.3.132.0.35), "secp521r1", "secp521r1", "NIST P-521 elliptic curve"),
+    OidEntry::builtin(oid!(1.3.36.3.3.2.8.1.1.7), "brainpoolP256r1", "brainpoolP256r1", "Brainpool P256r1 elliptic curve"),
+    OidEntry::builtin(oid!(1.3.36.3.3.2.8.1.1.11), "brainpoolP384r1", "brainpoolP384r1", "Brainpool P384r1 elliptic curve"),
+    OidEntry::builtin(oid!(1.3.36.3.3.2.8.1.1.13), "brainpoolP512r1", "brainpoolP512r1", "Brainpool P512r1 elliptic curve"),
+    OidEntry::builtin(oid!(1.3.101.110), "X25519", "X25519", "X25519 key agreement"),
+    OidEntry::builtin(oid!(1.3.101.111), "X448", "X448", "X448 key agreement"),
+    OidEntry::builtin(oid!(1.3.101.112), "ED25519", "ED25519", "Ed25519 signature algorithm"),
+    OidEntry::builtin(oid!(1.3.101.113), "ED448", "ED448", "Ed448 signature algorithm"),
+    // X.500 attributes
+    OidEntry::builtin(oid!(2.5.4.3), "CN", "commonName", "Common name"),
+    OidEntry::builtin(oid!(2.5.4.4), "SN", "surname", "Surname"),
+    OidEntry::builtin(oid!(2.5.4.5), "serialNumber", "serialNumber", "Serial number of a device or entity"),
+    OidEntry::builtin(oid!(2.5.4.6), "C", "countryName", "Country name"),
+    OidEntry::builtin(oid!(2.5.4.7), "L", "localityName", "Locality name"),
+    OidEntry::builtin(oid!(2.5.4.8), "ST", "stateOrProvinceName", "State or province name"),
+    OidEntry::builtin(oid!(2.5.4.9), "street", "streetAddress", "Street address"),
+    OidEntry::builtin(oid!(2.5.4.10), "O", "organizationName", "Organization name"),
+    OidEntry::builtin(oid!(2.5.4.11), "OU", "organizationalUnitName", "Organizational unit name"),
+    OidEntry::builtin(oid!(2.5.4.12), "title", "title", "Title"),
+    OidEntry::builtin(oid!(2.5.4.13), "description", "description", "Description"),
+    OidEntry::builtin(oid!(2.5.4.17), "postalCode", "postalCode", "Postal code"),
+    OidEntry::builtin(oid!(2.5.4.41), "name", "name", "Name"),
+    OidEntry::builtin(oid!(2.5.4.42), "GN", "givenName", "Given name"),
+    OidEntry::builtin(oid!(2.5.4.43), "initials", "initials", "Initials"),
+    OidEntry::builtin(oid!(2.5.4.44), "generationQualifier", "generationQualifier", "Generation qualifier"),
+    OidEntry::builtin(oid!(2.5.4.46), "dnQualifier", "dnQualifier", "Distinguished name qualifier"),
+    OidEntry::builtin(oid!(2.5.4.65), "pseudonym", "pseudonym", "Pseudonym"),
+    OidEntry::builtin(oid!(0.9.2342.19200300.100.1.1), "UID", "userId", "User identifier"),
+    OidEntry::builtin(oid!(0.9.2342.19200300.100.1.25), "DC", "domainComponent", "Domain name component"),
+    // X.509 certificate extensions
+    OidEntry::builtin(oid!(2.5.29.14), "subjectKeyIdentifier", "X509v3 Subject Key Identifier", "Subject key identifier extension"),
+    OidEntry::builtin(oid!(2.5.29.15), "keyUsage", "X509v3 Key Usage", "Key usage extension"),
+    OidEntry::builtin(oid!(2.5.29.16), "privateKeyUsagePeriod", "X509v3 Private Key Usage Period", "Private key usage period extension"),
+    OidEntry::builtin(oid!(2.5.29.17), "subjectAltName", "X509v3 Subject Alternative Name", "Subject alternative name extension"),
+    OidEntry::builtin(oid!(2.5.29.18), "issuerAltName", "X509v3 Issuer Alternative Name", "Issuer alternative name extension"),
+    OidEntry::builtin(oid!(2.5.29.19), "basicConstraints", "X509v3 Basic Constraints", "Basic constraints extension"),
+    OidEntry::builtin(oid!(2.5.29.20), "crlNumber", "X509v3 CRL Number", "CRL number extension"),
+    OidEntry::builtin(oid!(2.5.29.21), "CRLReason", "X509v3 CRL Reason Code", "CRL reason code extension"),
+    OidEntry::builtin(oid!(2.5.29.30), "nameConstraints", "X509v3 Name Constraints", "Name constraints extension"),
+    OidEntry::builtin(oid!(2.5.29.31), "crlDistributionPoints", "X509v3 CRL Distribution Points", "CRL distribution points extension"),
+    OidEntry::builtin(oid!(2.5.29.32), "certificatePolicies", "X509v3 Certificate Policies", "Certificate policies extension"),
+    OidEntry::builtin(oid!(2.5.29.32.0), "anyPolicy", "X509v3 Any Policy", "Any certificate policy"),
+    OidEntry::builtin(oid!(2.5.29.33), "policyMappings", "X509v3 Policy Mappings", "Policy mappings extension"),
+    OidEntry::builtin(oid!(2.5.29.35), "authorityKeyIdentifier", "X509v3 Authority Key Identifier", "Authority key identifier extension"),
+    OidEntry::builtin(oid!(2.5.29.36), "policyConstraints", "X509v3 Policy Constraints", "Policy constraints extension"),
+    OidEntry::builtin(oid!(2.5.29.37), "extendedKeyUsage", "X509v3 Extended Key Usage", "Extended key usage extension"),
+    OidEntry::builtin(oid!(2.5.29.54), "inhibitAnyPolicy", "X509v3 Inhibit Any Policy", "Inhibit any policy extension"),
+    // PKIX
+    OidEntry::builtin(oid!(1.3.6.1.5.5.7.1.1), "authorityInfoAccess", "Authority Information Access", "Authority information access extension"),
+    OidEntry::builtin(oid!(1.3.6.1.5.5.7.2.1), "id-qt-cps", "Policy Qualifier CPS", "Certification practice statement policy qualifier"),
+    OidEntry::builtin(oid!(1.3.6.1.5.5.7.2.2), "id-qt-unotice", "Policy Qualifier User Notice", "User notice policy qualifier"),
+    OidEntry::builtin(oid!(1.3.6.1.5.5.7.3.1), "serverAuth", "TLS Web Server Authentication", "TLS server authentication key purpose"),
+    OidEntry::builtin(oid!(1.3.6.1.5.5.7.3.2), "clientAuth", "TLS Web Client Authentication", "TLS client authentication key purpose"),
+    OidEntry::builtin(oid!(1.3.6.1.5.5.7.3.3), "codeSigning", "Code Signing", "Code signing key purpose"),
+    OidEntry::builtin(oid!(1.3.6.1.5.5.7.3.4), "emailProtection", "E-mail Protection", "E-mail protection key purpose"),
+    OidEntry::builtin(oid!(1.3.6.1.5.5.7.3.8), "timeStamping", "Time Stamping", "Time stamping key purpose"),
+    OidEntry::builtin(oid!(1.3.6.1.5.5.7.3.9), "OCSPSigning", "OCSP Signing", "OCSP response signing key purpose"),
+    OidEntry::builtin(oid!(1.3.6.1.5.5.7.48.1), "OCSP", "OCSP", "OCSP access method"),
+    OidEntry::builtin(oid!(1.3.6.1.5.5.7.48.2), "caIssuers", "CA Issuers", "CA issuers access method"),
+    OidEntry::builtin(oid!(1.3.6.1.4.1.11129.2.4.2), "ct_precert_scts", "CT Precertificate SCTs", "Certificate transparency signed certificate timestamps"),
+    // SNMP
+    OidEntry::builtin(oid!(1.3.6.1.2.1), "mib-2", "mib-2", "SNMP MIB-II"),
+    OidEntry::builtin(oid!(1.3.6.1.2.1.1), "system", "system", "SNMP MIB-II system group"),
+    OidEntry::builtin(oid!(1.3.6.1.2.1.1.1), "sysDescr", "sysDescr", "SNMP description of the entity"),
+    OidEntry::builtin(oid!(1.3.6.1.2.1.1.2), "sysObjectID", "sysObjectID", "SNMP vendor identification of the entity"),
+    OidEntry::builtin(oid!(1.3.6.1.2.1.1.3), "sysUpTime", "sysUpTime", "SNMP time since the network management was initialized"),
+    OidEntry::builtin(oid!(1.3.6.1.2.1.1.4), "sysContact", "sysContact", "SNMP contact person for the node"),
+    OidEntry::builtin(oid!(1.3.6.1.2.1.1.5), "sysName", "sysName", "SNMP name of the node"),
+    OidEntry::builtin(oid!(1.3.6.1.2.1.1.6), "sysLocation", "sysLocation", "SNMP physical location of the node"),
+    OidEntry::builtin(oid!(1.3.6.1.2.1.2), "interfaces", "interfaces", "SNMP MIB-II interfaces group"),
+    OidEntry::builtin(oid!(1.3.6.1.4.1), "enterprises", "enterprises", "Private enterprise numbers"),
+    OidEntry::builtin(oid!(1.3.6.1.6.3.1.1.4.1), "snmpTrapOID", "snmpTrapOID", "SNMP notification identifier"),
+    OidEntry::builtin(oid!(1.3.6.1.6.3.1.1.5.1), "coldStart", "coldStart", "SNMP cold start notification"),
+    OidEntry::builtin(oid!(1.3.6.1.6.3.1.1.5.2), "warmStart", "warmStart", "SNMP warm start notification"),
+    OidEntry::builtin(oid!(1.3.6.1.6.3.1.1.5.3), "linkDown", "linkDown", "SNMP link down notification"),
+    OidEntry::builtin(oid!(1.3.6.1.6.3.1.1.5.4), "linkUp", "linkUp", "SNMP link up notification"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_lookup() {
+        let entry = lookup(&Oid::from(&[2, 5, 4, 3]).unwrap()).expect("commonName");
+        assert_eq!(entry.short_name(), "CN");
+        assert_eq!(entry.long_name(), "commonName");
+        assert_eq!(entry.description(), "Common name");
+        assert!(lookup(&Oid::from(&[2, 5, 4, 1000]).unwrap()).is_none());
+        assert!(lookup(&Oid::from_relative(&[2, 5, 4, 3]).unwrap()).is_none());
+        let entry = lookup_name("prime256v1").expect("prime256v1");
+        assert_eq!(entry.oid().to_string(), "1.2.840.10045.3.1.7");
+        assert_eq!(
+            lookup_name("sha256").map(|e| e.short_name()),
+            Some("SHA256")
+        );
+        assert!(lookup_name("SHA-256").is_none());
+    }
+
+    #[test]
+    fn test_builtin_unique() {
+        let registry = OidRegistry::with_builtin();
+        assert_eq!(registry.len(), builtin_entries().len());
+        for entry in builtin_entries() {
+            assert_eq!(registry.get(entry.oid()), Some(entry));
+            assert_eq!(lookup_name(entry.short_name()), Some(entry));
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = OidRegistry::new();
+        assert!(registry.is_empty());
+        let oid = Oid::from(&[1, 3, 6, 1, 4, 1, 55555, 1]).unwrap();
+        let entry = OidEntry::new(oid.clone(), "test", "testOid", String::from("Test"));
+        assert_eq!(registry.insert(entry.clone()), None);
+        assert_eq!(registry.get(&oid), Some(&entry));
+        assert_eq!(registry.oid_by_name("testOid"), Some(&oid));
+        // replacing an entry also replaces its names
+        let other = OidEntry::new(oid.clone(), "other", "otherOid", "Other");
+        assert_eq!(registry.insert(other.clone()), Some(entry));
+        assert_eq!(registry.len(), 1);
+        assert!(registry.get_by_name("test").is_none());
+        assert_eq!(registry.get_by_name("other"), Some(&other));
+        assert_eq!(registry.iter().collect::<Vec<_>>(), vec![&other]);
+        // a relative OID with the same encoding is a different entry
+        let rel = Oid::new_relative(Cow::Owned(oid.bytes().to_vec()));
+        let relative = OidEntry::new(rel.clone(), "rel", "relativeOid", "Relative");
+        assert_eq!(registry.insert(relative.clone()), None);
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.get(&oid), Some(&other));
+        assert_eq!(registry.get(&rel), Some(&relative));
+    }
+}
@@ -78,6 +78,50 @@ fn test_set_indefinite_length() {
     );
 }
 
+#[test]
+fn test_seq_empty_definite_length() {
+    // an empty definite sequence is not an indefinite one
+    let data = hex!("30 00 02 01 01");
+    assert_eq!(
+        ber_read_element_header(&data).map(|(_, hdr)| hdr.len),
+        Ok(BerSize::Definite(0))
+    );
+    assert_eq!(
+        parse_ber(&data),
+        Ok((&data[2..], BerObject::from_seq(vec![])))
+    );
+    let data = hex!("30 80 00 00");
+    assert_eq!(
+        ber_read_element_header(&data).map(|(_, hdr)| hdr.len),
+        Ok(BerSize::Indefinite)
+    );
+    assert_eq!(
+        parse_ber(&data),
+        Ok((&b""[..], BerObject::from_seq(vec![])))
+    );
+}
+
+#[test]
+fn test_indefinite_length_primitive() {
+    let data = hex!("04 80 41 00 00");
+    assert_eq!(
+        parse_ber(&data),
+        Err(Err::Error(BerError::IndefiniteLengthUnexpected))
+    );
+}
+
+#[test]
+fn test_unknown_indefinite_length() {
+    let data = hex!("a0 80 30 80 02 01 01 00 00 00 00 ff");
+    let (rem, obj) = parse_ber(&data).expect("parsing failed");
+    assert_eq!(rem, &[0xff]);
+    assert_eq!(obj.tag, BerTag(0));
+    assert_eq!(
+        obj.content,
+        BerObjectContent::Unknown(BerTag(0), &data[2..9])
+    );
+}
+
 #[test]
 fn test_ber_int() {
     let empty = &b""[..];
@@ -121,10 +165,83 @@ fn test_ber_bitstring_constructed() {
         0x23, 0x80, 0x03, 0x03, 0x00, 0x0a, 0x3b, 0x03, 0x05, 0x04, 0x5f, 0x29, 0x1c, 0xd0, 0x00,
         0x00,
     ];
+    let (rem, obj) = parse_ber_bitstring(bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(obj.as_bytes(), Ok(&hex!("0a 3b 5f 29 1c d0")[..]));
+    assert_eq!(obj.as_segments().map(|s| s.len()), Ok(2));
+    match obj.content {
+        BerObjectContent::Segmented(tag, ref s) => {
+            assert_eq!(tag, BerTag::BitString);
+            assert_eq!(s.unused_bits, 4);
+            assert_eq!(
+                s.segments[1],
+                BerObject::from_obj(BerObjectContent::BitString(
+                    4,
+                    BitStringObject {
+                        data: &bytes[10..14]
+                    }
+                ))
+            );
+        }
+        _ => panic!("unexpected content"),
+    }
+    // unused bits in a segment which is not the last one
+    let bytes = &hex!("23 0c 03 03 04 0a 30 03 05 04 5f 29 1c d0");
     assert_eq!(
         parse_ber_bitstring(bytes),
-        Err(Err::Error(BerError::Unsupported))
-    ); // XXX valid encoding
+        Err(Err::Error(BerError::BerValueError))
+    );
+}
+
+#[test]
+fn test_ber_octetstring_constructed() {
+    // definite length
+    let bytes = hex!("24 0c 04 04 01 02 03 04 04 04 05 06 07 08");
+    let (rem, obj) = parse_ber_octetstring(&bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(obj.tag, BerTag::OctetString);
+    assert!(obj.is_constructed());
+    assert_eq!(obj.as_bytes(), Ok(&hex!("01 02 03 04 05 06 07 08")[..]));
+    assert_eq!(
+        obj.as_segments(),
+        Ok(&[
+            BerObject::from_obj(BerObjectContent::OctetString(&bytes[4..8])),
+            BerObject::from_obj(BerObjectContent::OctetString(&bytes[10..14])),
+        ][..])
+    );
+    // indefinite length, nested constructed segment
+    let bytes = hex!("24 80 04 02 41 42 24 80 04 01 43 00 00 04 00 00 00 ff");
+    let (rem, obj) = parse_ber(&bytes).expect("parsing failed");
+    assert_eq!(rem, &[0xff]);
+    assert_eq!(obj.as_bytes(), Ok(&b"ABC"[..]));
+    assert_eq!(obj.as_segments().map(|s| s.len()), Ok(3));
+    // segments must be octet strings
+    let bytes = hex!("24 05 02 03 01 00 01");
+    assert_eq!(
+        parse_ber_octetstring(&bytes),
+        Err(Err::Error(BerError::InvalidTag))
+    );
+    // truncated segment
+    let bytes = hex!("24 04 04 04 01 02 03 04");
+    assert_eq!(
+        parse_ber_octetstring(&bytes),
+        Err(Err::Error(BerError::ObjectTooShort))
+    );
+}
+
+#[test]
+fn test_ber_string_constructed() {
+    let bytes = hex!("2c 80 04 03 53 6f 6d 04 07 65 2d 53 74 61 74 65 00 00");
+    let (rem, obj) = parse_ber_utf8string(&bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(obj.tag, BerTag::Utf8String);
+    assert_eq!(obj.as_bytes(), Ok(&b"Some-State"[..]));
+    assert_eq!(obj.as_slice(), Err(BerError::BerTypeError));
+    // constructed strings are not valid in DER
+    assert_eq!(
+        der_parser::parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
 }
 
 #[test]
@@ -148,9 +265,9 @@ fn test_ber_oid() {
     let bytes = [
         0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x05,
     ];
-    let expected = BerObject::from_obj(BerObjectContent::OID(Oid::from(&[
-        1, 2, 840, 113549, 1, 1, 5,
-    ])));
+    let expected = BerObject::from_obj(BerObjectContent::OID(
+        Oid::from(&[1, 2, 840, 113549, 1, 1, 5]).unwrap(),
+    ));
     assert_eq!(parse_ber_oid(&bytes), Ok((empty, expected)));
 }
 
@@ -159,6 +276,108 @@ fn test_ber_enum() {
     let empty = &b""[..];
     let expected = BerObject::from_obj(BerObjectContent::Enum(2));
     assert_eq!(parse_ber_enum(&[0x0a, 0x01, 0x02]), Ok((empty, expected)));
+    let expected = BerObject::from_obj(BerObjectContent::Enum(-1));
+    assert_eq!(parse_ber_enum(&[0x0a, 0x01, 0xff]), Ok((empty, expected)));
+    assert_eq!(
+        parse_ber_enum(&hex!("0a 09 01 00 00 00 00 00 00 00 00")),
+        Err(Err::Error(BerError::IntegerTooLarge))
+    );
+}
+
+#[test]
+fn test_ber_integer_signed() {
+    let (_, obj) = parse_ber_integer(&[0x02, 0x01, 0xff]).expect("parsing failed");
+    assert_eq!(obj.as_i32(), Ok(-1));
+    assert_eq!(obj.as_u32(), Err(BerError::IntegerNegative));
+    let (_, obj) = parse_ber_integer(&hex!("02 02 00 ff")).expect("parsing failed");
+    assert_eq!(obj.as_u8(), Ok(255));
+    assert_eq!(obj.as_i8(), Err(BerError::IntegerTooLarge));
+    let bytes = hex!("02 11 00 ff ff ff ff ff ff ff ff ff ff ff ff ff ff ff ff");
+    let (_, obj) = parse_ber_integer(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_u128(), Ok(u128::MAX));
+    assert_eq!(obj.as_i128(), Err(BerError::IntegerTooLarge));
+}
+
+#[test]
+fn test_ber_real() {
+    let empty = &b""[..];
+    assert_eq!(
+        parse_ber_real(&[0x09, 0x00]),
+        Ok((
+            empty,
+            BerObject::from_obj(BerObjectContent::Real(RealValue::Zero))
+        ))
+    );
+    // -3 * 2^1 * 16^1
+    let (rem, obj) = parse_ber_real(&hex!("09 03 e4 01 03")).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(
+        obj.as_real(),
+        Ok(&RealValue::Binary {
+            negative: true,
+            mantissa: &[3],
+            base: 16,
+            scale: 1,
+            exponent: 1,
+        })
+    );
+    assert_eq!(obj.as_f64(), Ok(-96.0));
+    let bytes = hex!("09 08 03 31 32 33 2e 45 2d 32");
+    let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+    assert_eq!(
+        obj.as_real(),
+        Ok(&RealValue::Decimal(DecimalForm::NR3, "123.E-2"))
+    );
+    assert_eq!(obj.as_f64(), Ok(1.23));
+    assert_eq!(
+        parse_ber(&[0x09, 0x01, 0x41]).map(|(_, o)| o.as_f64()),
+        Ok(Ok(f64::NEG_INFINITY))
+    );
+    assert_eq!(
+        parse_ber_real(&[0x09, 0x02, 0x40, 0x00]),
+        Err(Err::Error(BerError::InvalidLength))
+    );
+    assert_eq!(
+        parse_ber_real(&[0x29, 0x01, 0x40]),
+        Err(Err::Error(BerError::ConstructUnexpected))
+    );
+    assert_eq!(
+        BerObject::from_int_slice(b"\x01").as_f64(),
+        Err(BerError::BerTypeError)
+    );
+}
+
+#[test]
+fn test_ber_time() {
+    // UTCTime with offset and no seconds
+    let (rem, obj) = parse_ber_utctime(b"\x17\x0f7001010100+0100").expect("parsing failed");
+    assert!(rem.is_empty());
+    let t = obj.as_time().expect("invalid time");
+    assert_eq!(t.tz, ASN1TimeZone::Offset(1, 0));
+    assert_eq!(t.to_system_time(), Ok(std::time::UNIX_EPOCH));
+    // GeneralizedTime in local time, with fraction of minute
+    let (_, obj) = parse_ber_generalizedtime(b"\x18\x0e198511062101,5").expect("parsing failed");
+    assert_eq!(
+        obj.as_time(),
+        Ok(ASN1Time {
+            year: 1985,
+            month: 11,
+            day: 6,
+            hour: 21,
+            minute: 1,
+            second: 30,
+            nanosecond: 0,
+            tz: ASN1TimeZone::Undefined,
+        })
+    );
+    assert!(obj.as_time().unwrap().to_system_time().is_err());
+    // the BER parser does not check the value
+    let (_, obj) = parse_ber_utctime(b"\x17\x0b0213010000Z").expect("parsing failed");
+    assert_eq!(obj.as_time(), Err(BerError::BerValueError));
+    assert_eq!(
+        BerObject::from_int_slice(b"\x01").as_time(),
+        Err(BerError::BerTypeError)
+    );
 }
 
 #[test]
@@ -175,7 +394,9 @@ fn test_ber_utf8string() {
 fn test_ber_relativeoid() {
     let empty = &b""[..];
     let bytes = hex!("0d 04 c2 7b 03 02");
-    let expected = BerObject::from_obj(BerObjectContent::RelativeOID(Oid::from(&[8571, 3, 2])));
+    let expected = BerObject::from_obj(BerObjectContent::RelativeOID(
+        Oid::from_relative(&[8571, 3, 2]).unwrap(),
+    ));
     assert_eq!(parse_ber_relative_oid(&bytes), Ok((empty, expected)));
 }
 
@@ -185,4 +406,119 @@ fn test_ber_bmpstring() {
     let bytes = hex!("1e 08 00 55 00 73 00 65 00 72");
     let expected = BerObject::from_obj(BerObjectContent::BmpString(b"\x00U\x00s\x00e\x00r"));
     assert_eq!(parse_ber_bmpstring(&bytes), Ok((empty, expected)));
+    let (_, obj) = parse_ber_bmpstring(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_string(), Ok("User".to_string()));
+    assert_eq!(obj.as_str(), Err(BerError::BerTypeError));
+}
+
+#[test]
+fn test_ber_universalstring() {
+    let empty = &b""[..];
+    let bytes = hex!("1c 08 00 00 00 55 00 01 f6 00");
+    let expected = BerObject::from_obj(BerObjectContent::UniversalString(&bytes[2..]));
+    assert_eq!(parse_ber_universalstring(&bytes), Ok((empty, expected)));
+    let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_string(), Ok("U\u{1f600}".to_string()));
+}
+
+#[test]
+fn test_ber_string_accessors() {
+    let (_, obj) = parse_ber_printablestring(b"\x13\x05ab-12").expect("parsing failed");
+    assert_eq!(obj.as_str(), Ok("ab-12"));
+    // '@' is not allowed in a PrintableString, but the BER parser does not check the content
+    let (_, obj) = parse_ber_printablestring(b"\x13\x03a@b").expect("parsing failed");
+    assert_eq!(obj.as_str(), Err(BerError::StringInvalidCharset));
+    assert_eq!(obj.as_string(), Err(BerError::StringInvalidCharset));
+    assert_eq!(obj.to_string_lossy(), Ok("a\u{fffd}b".to_string()));
+    let (_, obj) = parse_ber_utf8string(b"\x0c\x02\xc3\x28").expect("parsing failed");
+    assert_eq!(obj.as_str(), Err(BerError::StringInvalidCharset));
+    assert_eq!(obj.to_string_lossy(), Ok("\u{fffd}(".to_string()));
+    let (_, obj) = parse_ber_t61string(b"\x14\x03\xc2e!").expect("parsing failed");
+    assert_eq!(obj.as_string(), Ok("e\u{301}!".to_string()));
+    // constructed form
+    let bytes = hex!("36 80 04 02 61 62 04 01 63 00 00");
+    let (_, obj) = parse_ber_ia5string(&bytes).expect("parsing failed");
+    assert_eq!(obj.as_str(), Ok("abc"));
+    let (_, obj) = parse_ber_octetstring(b"\x04\x01a").expect("parsing failed");
+    assert_eq!(obj.as_str(), Err(BerError::BerTypeError));
+    assert_eq!(obj.to_string_lossy(), Err(BerError::BerTypeError));
+}
+
+#[test]
+fn test_ber_location() {
+    // indefinite-length sequence, containing an integer and a definite-length set
+    let bytes = hex!("ff 30 80 02 01 05 31 03 01 01 ff 00 00 aa");
+    let (rem, obj) = parse_ber(&bytes[1..]).expect("parsing failed");
+    assert_eq!(rem, &bytes[13..]);
+    assert_eq!(obj.raw(), Some(&bytes[1..13]));
+    assert_eq!(obj.raw_content(), Some(&bytes[3..13]));
+    assert_eq!(obj.header_len(), Some(2));
+    assert_eq!(obj.span(), Some(0..12));
+    assert_eq!(obj[0].raw(), Some(&bytes[3..6]));
+    assert_eq!(obj[0].span(), Some(2..5));
+    assert_eq!(obj[1].span(), Some(5..10));
+    assert_eq!(obj[1][0].raw(), Some(&bytes[8..11]));
+    assert_eq!(obj[1][0].offset(), Some(7));
+    // the location is not used when comparing objects
+    assert_eq!(obj[0], BerObject::from_int_slice(b"\x05"));
+    assert_eq!(BerObject::from_int_slice(b"\x05").raw(), None);
+    // helper functions
+    let bytes = hex!("9f 22 01 2a");
+    let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+    assert_eq!(obj.header_len(), Some(3));
+    let (_, obj) = parse_ber_integer(&hex!("02 01 2a")).expect("parsing failed");
+    assert_eq!(obj.span(), Some(0..3));
+    let (_, obj) = parse_ber_explicit(&hex!("a0 03 02 01 2a"), BerTag(0), parse_ber_integer)
+        .expect("parsing failed");
+    assert_eq!(obj.span(), Some(0..5));
+    assert_eq!(obj.header_len(), Some(2));
+    assert_eq!(
+        obj.as_context_specific().unwrap().1.unwrap().span(),
+        Some(2..5)
+    );
+}
+
+#[test]
+fn test_ber_options() {
+    let opts = |f: fn(&mut ParserOptions)| {
+        let mut o = ParserOptions::default();
+        f(&mut o);
+        o
+    };
+    // SEQUENCE { SEQUENCE { INTEGER 1, OID 1.2.840.113549 }, OCTET STRING (constructed) }
+    let bytes =
+        hex!("30 80 30 0b 02 01 01 06 06 2a 86 48 86 f7 0d 24 80 04 02 61 62 04 01 63 00 00 00 00");
+    let (_, obj) =
+        parse_ber_with_options(&bytes, &ParserOptions::default()).expect("parsing failed");
+    assert_eq!(parse_ber(&bytes), Ok((&b""[..], obj)));
+    let check = |o: ParserOptions, e: BerError| {
+        assert_eq!(parse_ber_with_options(&bytes, &o), Err(Err::Error(e)));
+    };
+    check(opts(|o| o.max_depth = 1), BerError::BerMaxDepth);
+    check(opts(|o| o.max_length = 10), BerError::MaxLengthExceeded);
+    check(opts(|o| o.max_objects = 6), BerError::MaxObjectsExceeded);
+    check(opts(|o| o.max_elements = 1), BerError::MaxElementsExceeded);
+    check(opts(|o| o.max_oid_arcs = 3), BerError::MaxOidArcsExceeded);
+    check(
+        opts(|o| o.max_string_size = 2),
+        BerError::MaxStringSizeExceeded,
+    );
+    let o = ParserOptions {
+        max_depth: 2,
+        max_length: 11,
+        max_objects: 7,
+        max_elements: 2,
+        max_oid_arcs: 4,
+        max_string_size: 3,
+    };
+    assert!(parse_ber_with_options(&bytes, &o).is_ok());
+    // limits are not ignored in sequences using the definite length form
+    let bytes = hex!("30 06 30 04 02 02 00 ff");
+    let o = opts(|o| o.max_depth = 1);
+    let e = parse_ber_with_options(&bytes, &o).expect_err("parsing should fail");
+    assert_eq!(e, Err::Error(BerError::BerMaxDepth));
+    assert!(match e {
+        Err::Error(e) => e.is_limit_exceeded(),
+        _ => false,
+    });
 }
@@ -73,7 +73,7 @@ fn struct01() {
             class: 0,
             structured: 1,
             tag: BerTag::Sequence,
-            len: 0xa,
+            len: BerSize::Definite(0xa),
         },
         MyStruct {
             a: BerObject::from_int_slice(b"\x01\x00\x01"),
@@ -96,7 +96,7 @@ fn struct02() {
     ];
     #[derive(Debug, PartialEq)]
     struct Attr<'a> {
-        oid: Oid,
+        oid: Oid<'a>,
         val: BerObject<'a>,
     };
     #[derive(Debug, PartialEq)]
@@ -111,19 +111,19 @@ fn struct02() {
         l: vec![
             Rdn {
                 a: Attr {
-                    oid: Oid::from(&[2, 5, 4, 6]), // countryName
+                    oid: Oid::from(&[2, 5, 4, 6]).unwrap(), // countryName
                     val: BerObject::from_obj(BerObjectContent::PrintableString(b"FR")),
                 },
             },
             Rdn {
                 a: Attr {
-                    oid: Oid::from(&[2, 5, 4, 8]), // stateOrProvinceName
+                    oid: Oid::from(&[2, 5, 4, 8]).unwrap(), // stateOrProvinceName
                     val: BerObject::from_obj(BerObjectContent::UTF8String(b"Some-State")),
                 },
             },
             Rdn {
                 a: Attr {
-                    oid: Oid::from(&[2, 5, 4, 10]), // organizationName
+                    oid: Oid::from(&[2, 5, 4, 10]).unwrap(), // organizationName
                     val: BerObject::from_obj(BerObjectContent::UTF8String(
                         b"Internet Widgits Pty Ltd",
                     )),
@@ -140,7 +140,7 @@ fn struct02() {
     fn parse_attr_type_and_value(i: &[u8]) -> IResult<&[u8], Attr, BerError> {
         parse_der_struct!(
             i,
-            o: map_res!(parse_ber_oid, |x: BerObject| x.as_oid().map(|o| o.clone()))
+            o: map_res!(parse_ber_oid, |x: BerObject| x.as_oid().map(|o| o.to_owned()))
                 >> s: parse_directory_string
                 >> (Attr { oid: o, val: s })
         )
@@ -168,7 +168,7 @@ fn struct_with_garbage() {
             class: 0,
             structured: 1,
             tag: BerTag::Sequence,
-            len: 0xc,
+            len: BerSize::Definite(0xc),
         },
         MyStruct {
             a: BerObject::from_int_slice(b"\x01\x00\x01"),
@@ -193,7 +193,7 @@ fn struct_verify_tag() {
             class: 0,
             structured: 1,
             tag: BerTag::Sequence,
-            len: 0xa,
+            len: BerSize::Definite(0xa),
         },
         MyStruct {
             a: BerObject::from_int_slice(b"\x01\x00\x01"),
@@ -209,6 +209,37 @@ fn struct_verify_tag() {
     );
 }
 
+#[test]
+fn struct_indefinite_length() {
+    let bytes = [
+        0x30, 0x80, 0x02, 0x03, 0x01, 0x00, 0x01, 0x02, 0x03, 0x01, 0x00, 0x00, 0x00, 0x00, 0xff,
+    ];
+    // DER: the indefinite form of length is not allowed
+    assert_eq!(
+        parse_struct01(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+}
+
+#[test]
+fn sequence_of_empty() {
+    fn parser(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+        parse_der_sequence_of!(i, parse_ber_integer)
+    }
+    // an empty definite sequence must not be read as an indefinite one
+    let bytes = [0x30, 0x00, 0x02, 0x01, 0x01];
+    assert_eq!(
+        parser(&bytes),
+        Ok((&bytes[2..], BerObject::from_seq(vec![])))
+    );
+    // the indefinite form of length is rejected
+    let bytes = [0x30, 0x80, 0x02, 0x01, 0x01, 0x00, 0x00, 0x02, 0x01, 0x02];
+    assert_eq!(
+        parser(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+}
+
 #[test]
 fn tagged_explicit() {
     fn parse_int_explicit(i: &[u8]) -> IResult<&[u8], u32, BerError> {
@@ -235,6 +266,12 @@ fn tagged_explicit() {
         }
         _ => assert!(false),
     }
+    // indefinite length
+    let bytes_indef = &[0xa2, 0x80, 0x02, 0x03, 0x01, 0x00, 0x01, 0x00, 0x00];
+    assert_eq!(
+        parse_int_explicit(bytes_indef),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
     // omitting EXPLICIT keyword
     let a = parse_int_explicit(bytes);
     let b = parse_int_noexplicit(bytes);
@@ -310,3 +347,30 @@ fn application() {
         _ => assert!(false),
     }
 }
+
+#[test]
+fn error_context() {
+    fn parse_seq(i: &[u8]) -> IResult<&[u8], BerObject<'_>, BerError> {
+        parse_der_sequence_defined!(
+            i,
+            parse_ber_integer >> parse_der_tagged!(EXPLICIT 0, parse_ber_integer)
+        )
+    }
+    let bytes = &[0x30, 0x08, 0x02, 0x01, 0x01, 0xa0, 0x03, 0x04, 0x01, 0x00];
+    let e = match parse_seq(bytes) {
+        Err(Err::Error(e)) => e,
+        _ => panic!("parsing should fail"),
+    };
+    assert_eq!(e, BerError::InvalidTag);
+    assert_eq!(e.kind(), &BerError::InvalidTag);
+    assert_eq!(e.offset(), Some(7));
+    let frames = &e.context().expect("no context").frames;
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[1].tag, BerTag(0));
+    assert_eq!(frames[1].offset, 5);
+    assert_eq!(frames[2].index, Some(1));
+    assert_eq!(
+        e.to_string(),
+        "invalid tag at offset 7 (in SEQUENCE[1] > [0] EXPLICIT > INTEGER)"
+    );
+}
@@ -0,0 +1,126 @@
+#![cfg(feature = "der-dump")]
+
+#[macro_use]
+extern crate pretty_assertions;
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+const SAMPLE_DUMP: &str = "    0:d=0  hl=2 l=  30 cons: SEQUENCE
+    2:d=1  hl=2 l=   1 prim:   INTEGER           :1
+    5:d=1  hl=2 l=  11 cons:   cont [ 0 ]
+    7:d=2  hl=2 l=   9 prim:     OBJECT IDENTIFIER :1.2.840.113549.1.1.11 (sha256WithRSAEncryption)
+   18:d=1  hl=2 l=   7 cons:   SET
+   20:d=2  hl=2 l=   1 prim:     UTF8String        :'x'
+   23:d=2  hl=2 l=   2 prim:     PrintableString   :'ab'
+   27:d=1  hl=2 l=   3 prim:   OCTET STRING      :010203
+";
+
+fn der_dump(args: &[&str], stdin: Option<&[u8]>) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_der-dump"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("could not run der-dump");
+    if let Some(data) = stdin {
+        child.stdin.take().unwrap().write_all(data).unwrap();
+    }
+    drop(child.stdin.take());
+    child.wait_with_output().expect("could not run der-dump")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).expect("invalid UTF-8 output")
+}
+
+#[test]
+fn test_der_dump_formats() {
+    for file in &[
+        "tests/data/sample.der",
+        "tests/data/sample.pem",
+        "tests/data/sample.hex",
+    ] {
+        let output = der_dump(&[file], None);
+        assert!(output.status.success(), "{}", file);
+        assert_eq!(stdout(&output), SAMPLE_DUMP);
+    }
+    // base64, from stdin
+    let output = der_dump(&[], Some(b"MB4CAQGgCwYJKoZIhvcNAQELMQcMAXgTAmFiBAMBAgM=\n"));
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), SAMPLE_DUMP);
+    // forced format
+    let output = der_dump(&["-i", "der", "tests/data/sample.hex"], None);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_der_dump_tagged() {
+    // several elements in nested explicit tagged values
+    let output = der_dump(&[], Some(b"30 0c a0 0a 02 01 01 a1 05 02 01 02 05 00"));
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "    0:d=0  hl=2 l=  12 cons: SEQUENCE
+    2:d=1  hl=2 l=  10 cons:   cont [ 0 ]
+    4:d=2  hl=2 l=   1 prim:     INTEGER           :1
+    7:d=2  hl=2 l=   5 cons:     cont [ 1 ]
+    9:d=3  hl=2 l=   1 prim:       INTEGER           :2
+   12:d=3  hl=2 l=   0 prim:       NULL
+"
+    );
+    // deeply nested tagged values are not expanded past the recursion limit
+    let mut data = vec![0x05, 0x00];
+    for _ in 0..10_000 {
+        let len = data.len();
+        let mut hdr = vec![0xa0, 0x83, (len >> 16) as u8, (len >> 8) as u8, len as u8];
+        hdr.extend(data);
+        data = hdr;
+    }
+    let output = der_dump(&["-i", "der"], Some(&data));
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).lines().count(), 51);
+}
+
+#[test]
+fn test_der_dump_strict() {
+    let output = der_dump(&["--strict", "tests/data/sample.pem"], None);
+    assert!(output.status.success());
+    assert!(stdout(&output).ends_with("DER encoding is valid\n"));
+    let output = der_dump(&["--strict", "tests/data/sample.ber"], None);
+    assert_eq!(output.status.code(), Some(1));
+    let violations: Vec<_> = stdout(&output)
+        .lines()
+        .filter(|l| l.starts_with("DER violation"))
+        .map(String::from)
+        .collect();
+    assert_eq!(
+        violations,
+        vec![
+            "DER violation: length not minimally encoded (X.690 10.1) at offset 0",
+            "DER violation: invalid boolean value (X.690 11.1) at offset 3",
+            "DER violation: indefinite length (X.690 10.1) at offset 6",
+            "DER violation: indefinite length (X.690 10.1) at offset 10",
+            "DER violation: constructed string (X.690 10.2) at offset 10",
+        ]
+    );
+}
+
+#[test]
+fn test_der_dump_extract() {
+    let output = der_dump(&["-e", "1.0", "tests/data/sample.der"], None);
+    assert!(output.status.success());
+    assert_eq!(
+        output.stdout,
+        &b"\x06\x09\x2a\x86\x48\x86\xf7\x0d\x01\x01\x0b"[..]
+    );
+    let output = der_dump(&["--extract", "2.1", "tests/data/sample.pem"], None);
+    assert_eq!(output.stdout, &b"\x13\x02ab"[..]);
+    let output = der_dump(&["--extract", "", "tests/data/sample.ber"], None);
+    assert_eq!(output.stdout.len(), 17);
+    let output = der_dump(&["-e", "4", "tests/data/sample.der"], None);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+}
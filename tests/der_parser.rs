@@ -10,7 +10,10 @@ extern crate hex_literal;
 #[macro_use]
 extern crate rusticata_macros;
 
-use der_parser::ber::{ber_read_element_content_as, BerObjectContent, BerTag, BitStringObject};
+use der_parser::ber::{
+    ber_read_element_content_as, ber_read_element_header, parse_ber, parse_ber_integer, BerObject,
+    BerObjectContent, BerSize, BerTag, BitStringObject, ParserOptions, MAX_RECURSION,
+};
 use der_parser::der::*;
 use der_parser::error::*;
 use der_parser::oid::*;
@@ -50,6 +53,28 @@ fn test_der_int() {
     assert!(res.is_ok());
     let res = parse_der_u64(&bytes);
     assert!(res.is_err());
+    // non-minimal encodings
+    let bytes = hex!("02 02 00 01");
+    assert_eq!(
+        parse_der_integer(&bytes),
+        Err(Err::Error(BerError::IntegerNonMinimal))
+    );
+    let bytes = hex!("02 02 ff ff");
+    assert_eq!(
+        parse_der_integer(&bytes),
+        Err(Err::Error(BerError::IntegerNonMinimal))
+    );
+    assert!(parse_ber_integer(&bytes).is_ok());
+    // empty content
+    let bytes = hex!("02 00");
+    assert_eq!(
+        parse_der_integer(&bytes),
+        Err(Err::Error(BerError::IntegerEmpty))
+    );
+    assert!(parse_ber_integer(&bytes).is_ok());
+    // minimal encodings with a leading 0x00 or 0xff octet
+    assert!(parse_der_integer(&hex!("02 02 00 80")).is_ok());
+    assert!(parse_der_integer(&hex!("02 02 ff 7f")).is_ok());
 }
 
 #[test]
@@ -115,9 +140,9 @@ fn test_der_oid() {
     let bytes = [
         0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x05,
     ];
-    let expected = DerObject::from_obj(BerObjectContent::OID(Oid::from(&[
-        1, 2, 840, 113549, 1, 1, 5,
-    ])));
+    let expected = DerObject::from_obj(BerObjectContent::OID(
+        Oid::from(&[1, 2, 840, 113549, 1, 1, 5]).unwrap(),
+    ));
     assert_eq!(parse_der_oid(&bytes), Ok((empty, expected)));
 }
 
@@ -126,6 +151,37 @@ fn test_der_enum() {
     let empty = &b""[..];
     let expected = DerObject::from_obj(BerObjectContent::Enum(2));
     assert_eq!(parse_der_enum(&[0x0a, 0x01, 0x02]), Ok((empty, expected)));
+    assert_eq!(
+        parse_der_enum(&[0x0a, 0x02, 0xff, 0x80]),
+        Err(Err::Error(BerError::IntegerNonMinimal))
+    );
+    assert_eq!(
+        parse_der_enum(&[0x0a, 0x00]),
+        Err(Err::Error(BerError::IntegerEmpty))
+    );
+}
+
+#[test]
+fn test_der_real() {
+    let (rem, obj) = parse_der_real(&hex!("09 03 80 fb 05")).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(obj.as_f64(), Ok(0.15625));
+    let (_, obj) = parse_der_real(&hex!("09 07 03 2d 31 2e 45 2b 30")).expect("parsing failed");
+    assert_eq!(obj.as_f64(), Ok(-1.0));
+    assert!(parse_der_real(&[0x09, 0x01, 0x42]).is_ok());
+    // valid BER, but not canonical
+    for bytes in &[
+        &hex!("09 03 90 fb 05")[..],
+        &hex!("09 03 80 fb 0a")[..],
+        &hex!("09 03 01 2d 31")[..],
+        &hex!("09 07 03 2b 31 2e 45 2b 30")[..],
+    ] {
+        assert!(parse_ber(bytes).is_ok());
+        assert_eq!(
+            parse_der_real(bytes),
+            Err(Err::Error(BerError::DerConstraintFailed))
+        );
+    }
 }
 
 #[test]
@@ -138,11 +194,40 @@ fn test_der_utf8string() {
     assert_eq!(parse_der_utf8string(&bytes), Ok((empty, expected)));
 }
 
+#[cfg(feature = "strict-strings")]
+#[test]
+fn test_der_strict_strings() {
+    assert!(parse_der_printablestring(b"\x13\x03a-b").is_ok());
+    assert_eq!(
+        parse_der_printablestring(b"\x13\x03a@b"),
+        Err(Err::Error(BerError::StringInvalidCharset))
+    );
+    assert_eq!(
+        parse_der_numericstring(b"\x12\x02\x31a"),
+        Err(Err::Error(BerError::StringInvalidCharset))
+    );
+    assert_eq!(
+        parse_der_utf8string(b"\x0c\x01\xff"),
+        Err(Err::Error(BerError::StringInvalidCharset))
+    );
+    assert_eq!(
+        parse_der_bmpstring(b"\x1e\x03\x00a\x00"),
+        Err(Err::Error(BerError::StringInvalidCharset))
+    );
+    // also verified inside sequences
+    assert_eq!(
+        parse_der(b"\x30\x03\x16\x01\x80"),
+        Err(Err::Error(BerError::StringInvalidCharset))
+    );
+}
+
 #[test]
 fn test_der_relativeoid() {
     let empty = &b""[..];
     let bytes = [0x0d, 0x04, 0xc2, 0x7b, 0x03, 0x02];
-    let expected = DerObject::from_obj(BerObjectContent::RelativeOID(Oid::from(&[8571, 3, 2])));
+    let expected = DerObject::from_obj(BerObjectContent::RelativeOID(
+        Oid::from_relative(&[8571, 3, 2]).unwrap(),
+    ));
     assert_eq!(parse_der_relative_oid(&bytes), Ok((empty, expected)));
 }
 
@@ -162,6 +247,42 @@ fn test_der_set() {
     assert_eq!(parse_der_set(&bytes), Ok((empty, expected)));
 }
 
+#[test]
+fn test_der_set_order() {
+    // SET OF: sorted by encoding
+    let bytes = hex!("31 0a 02 01 01 02 01 02 02 02 01 00");
+    assert!(parse_der(&bytes).is_ok());
+    let bytes = hex!("31 0a 02 02 01 00 02 01 01 02 01 02");
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    assert!(parse_ber(&bytes).is_ok());
+    // duplicate elements are allowed
+    let bytes = hex!("31 06 02 01 01 02 01 01");
+    assert!(parse_der(&bytes).is_ok());
+    // without the schema, the elements of a SET must also be sorted by encoding, even if they
+    // are sorted by tag
+    let bytes = hex!("31 08 a5 02 05 00 86 02 41 42");
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    let bytes = hex!("31 08 86 02 41 42 a5 02 05 00");
+    assert!(parse_der(&bytes).is_ok());
+    let bytes = hex!("31 06 81 01 00 80 01 00");
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // nested sets are also checked
+    let bytes = hex!("30 08 31 06 02 01 02 02 01 01");
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+}
+
 #[test]
 fn test_der_seq_defined() {
     let empty = &b""[..];
@@ -182,16 +303,33 @@ fn test_der_seq_defined() {
 fn test_der_set_defined() {
     let empty = &b""[..];
     let bytes = [
-        0x31, 0x0a, 0x02, 0x03, 0x01, 0x00, 0x01, 0x02, 0x03, 0x01, 0x00, 0x00,
+        0x31, 0x0a, 0x02, 0x03, 0x01, 0x00, 0x00, 0x02, 0x03, 0x01, 0x00, 0x01,
     ];
     let expected = DerObject::from_set(vec![
-        DerObject::from_int_slice(b"\x01\x00\x01"),
         DerObject::from_int_slice(b"\x01\x00\x00"),
+        DerObject::from_int_slice(b"\x01\x00\x01"),
     ]);
     fn parser(i: &[u8]) -> IResult<&[u8], DerObject, BerError> {
         parse_der_set_defined!(i, parse_der_integer >> parse_der_integer)
     };
     assert_eq!(parser(&bytes), Ok((empty, expected)));
+    let bytes = hex!("31 0a 02 03 01 00 01 02 03 01 00 00");
+    assert_eq!(
+        parser(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // the components of a SET are sorted by tag, not by encoding
+    fn parse_tagged(i: &[u8]) -> IResult<&[u8], DerObject, BerError> {
+        parse_der_set_defined!(
+            i,
+            parse_der_tagged!(EXPLICIT 5, parse_der_null)
+                >> parse_der_tagged!(IMPLICIT 6, BerTag::Ia5String)
+        )
+    };
+    let bytes = hex!("31 08 a5 02 05 00 86 02 41 42");
+    assert!(parse_tagged(&bytes).is_ok());
+    let bytes = hex!("31 08 86 02 41 42 a5 02 05 00");
+    assert!(parse_tagged(&bytes).is_err());
 }
 
 #[test]
@@ -226,16 +364,22 @@ fn test_der_seq_of_incomplete() {
 fn test_der_set_of() {
     let empty = &b""[..];
     let bytes = [
-        0x31, 0x0a, 0x02, 0x03, 0x01, 0x00, 0x01, 0x02, 0x03, 0x01, 0x00, 0x00,
+        0x31, 0x0a, 0x02, 0x03, 0x01, 0x00, 0x00, 0x02, 0x03, 0x01, 0x00, 0x01,
     ];
     let expected = DerObject::from_set(vec![
-        DerObject::from_int_slice(b"\x01\x00\x01"),
         DerObject::from_int_slice(b"\x01\x00\x00"),
+        DerObject::from_int_slice(b"\x01\x00\x01"),
     ]);
     fn parser(i: &[u8]) -> IResult<&[u8], DerObject, BerError> {
         parse_der_set_of!(i, parse_der_integer)
     };
     assert_eq!(parser(&bytes), Ok((empty, expected)));
+    // elements not sorted by encoding
+    let bytes = hex!("31 0a 02 03 01 00 01 02 03 01 00 00");
+    assert_eq!(
+        parser(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
 }
 
 #[test]
@@ -246,16 +390,30 @@ fn test_der_utctime() {
     assert_eq!(parse_der_utctime(&bytes), Ok((empty, expected)));
     let bytes = hex!("17 0c 30 32 31 32 31 33 31 34 32 39 32 33");
     parse_der_utctime(&bytes).err().expect("expected error");
+    // invalid date (February 30)
+    let bytes = b"\x17\x0d020230142923Z";
+    assert!(parse_der_utctime(bytes).is_err());
 }
 
 #[test]
 fn test_der_generalizedtime() {
     let empty = &b""[..];
-    let bytes = [
-        0x18, 0x0D, 0x30, 0x32, 0x31, 0x32, 0x31, 0x33, 0x31, 0x34, 0x32, 0x39, 0x32, 0x33, 0x5A,
-    ];
+    let bytes = hex!("18 0F 32 30 30 32 31 32 31 33 31 34 32 39 32 33 5A");
     let expected = DerObject::from_obj(BerObjectContent::GeneralizedTime(&bytes[2..]));
     assert_eq!(parse_der_generalizedtime(&bytes), Ok((empty, expected)));
+    // fractional seconds without trailing zeroes
+    let bytes = hex!("18 11 32 30 30 32 31 32 31 33 31 34 32 39 32 33 2e 35 5A");
+    let expected = DerObject::from_obj(BerObjectContent::GeneralizedTime(&bytes[2..]));
+    assert_eq!(parse_der_generalizedtime(&bytes), Ok((empty, expected)));
+    // two-digit year
+    let bytes = hex!("18 0D 30 32 31 32 31 33 31 34 32 39 32 33 5A");
+    assert!(parse_der_generalizedtime(&bytes).is_err());
+    // trailing zero in fractional seconds
+    let bytes = hex!("18 12 32 30 30 32 31 32 31 33 31 34 32 39 32 33 2e 35 30 5A");
+    assert!(parse_der_generalizedtime(&bytes).is_err());
+    // invalid time (hour 24)
+    let bytes = b"\x18\x0f20021213242923Z";
+    assert!(parse_der_generalizedtime(bytes).is_err());
 }
 
 #[test]
@@ -275,6 +433,7 @@ fn test_der_contextspecific() {
         structured: 1,
         tag: BerTag(0),
         content: BerObjectContent::Unknown(BerTag(0), &bytes[2..]),
+        location: None,
     };
     assert_eq!(parse_der(&bytes), Ok((empty, expected)));
 }
@@ -291,6 +450,7 @@ fn test_der_explicit() {
             BerTag(0),
             Some(Box::new(DerObject::from_int_slice(b"\x02"))),
         ),
+        location: None,
     };
     assert_eq!(
         parse_der_explicit(&bytes, BerTag(0), parse_der_integer),
@@ -313,11 +473,12 @@ fn test_der_implicit() {
         structured: 0,
         tag: BerTag(1),
         content: BerObjectContent::ContextSpecific(BerTag(1), Some(Box::new(pass))),
+        location: None,
     };
     fn der_read_ia5string_content(
         i: &[u8],
         _tag: BerTag,
-        len: usize,
+        len: BerSize,
     ) -> IResult<&[u8], BerObjectContent, BerError> {
         ber_read_element_content_as(i, DerTag::Ia5String, len, false, 0)
     }
@@ -342,11 +503,12 @@ fn test_der_implicit_long_tag() {
         structured: 0,
         tag: BerTag(0x52),
         content: BerObjectContent::ContextSpecific(BerTag(0x52), Some(Box::new(pass))),
+        location: None,
     };
     fn der_read_ia5string_content(
         i: &[u8],
         _tag: BerTag,
-        len: usize,
+        len: BerSize,
     ) -> IResult<&[u8], BerObjectContent, BerError> {
         ber_read_element_content_as(i, DerTag::Ia5String, len, false, 0)
     }
@@ -364,9 +526,7 @@ fn test_der_implicit_long_tag() {
 #[test]
 fn test_der_optional() {
     let empty = &b""[..];
-    let bytes1 = [
-        0x30, 0x0a, 0x0a, 0x03, 0x00, 0x00, 0x01, 0x02, 0x03, 0x01, 0x00, 0x01,
-    ];
+    let bytes1 = [0x30, 0x08, 0x0a, 0x01, 0x01, 0x02, 0x03, 0x01, 0x00, 0x01];
     let bytes2 = [0x30, 0x05, 0x02, 0x03, 0x01, 0x00, 0x01];
     let expected1 = DerObject::from_seq(vec![
         DerObject::from_obj(BerObjectContent::ContextSpecific(
@@ -401,15 +561,15 @@ fn test_der_seq_dn() {
     ];
     let expected = DerObject::from_seq(vec![
         DerObject::from_set(vec![DerObject::from_seq(vec![
-            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 6]))), // countryName
+            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 6]).unwrap())), // countryName
             DerObject::from_obj(BerObjectContent::PrintableString(b"FR")),
         ])]),
         DerObject::from_set(vec![DerObject::from_seq(vec![
-            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 8]))), // stateOrProvinceName
+            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 8]).unwrap())), // stateOrProvinceName
             DerObject::from_obj(BerObjectContent::UTF8String(b"Some-State")),
         ])]),
         DerObject::from_set(vec![DerObject::from_seq(vec![
-            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 10]))), // organizationName
+            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 10]).unwrap())), // organizationName
             DerObject::from_obj(BerObjectContent::UTF8String(b"Internet Widgits Pty Ltd")),
         ])]),
     ]);
@@ -428,15 +588,15 @@ fn test_der_seq_dn_defined() {
     ];
     let expected = DerObject::from_seq(vec![
         DerObject::from_set(vec![DerObject::from_seq(vec![
-            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 6]))), // countryName
+            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 6]).unwrap())), // countryName
             DerObject::from_obj(BerObjectContent::PrintableString(b"FR")),
         ])]),
         DerObject::from_set(vec![DerObject::from_seq(vec![
-            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 8]))), // stateOrProvinceName
+            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 8]).unwrap())), // stateOrProvinceName
             DerObject::from_obj(BerObjectContent::UTF8String(b"Some-State")),
         ])]),
         DerObject::from_set(vec![DerObject::from_seq(vec![
-            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 10]))), // organizationName
+            DerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 10]).unwrap())), // organizationName
             DerObject::from_obj(BerObjectContent::UTF8String(b"Internet Widgits Pty Ltd")),
         ])]),
     ]);
@@ -493,11 +653,11 @@ fn test_der_defined_set_macros() {
     }
     let empty = &b""[..];
     let bytes = [
-        0x31, 0x0a, 0x02, 0x03, 0x01, 0x00, 0x01, 0x02, 0x03, 0x01, 0x00, 0x00,
+        0x31, 0x0a, 0x02, 0x03, 0x01, 0x00, 0x00, 0x02, 0x03, 0x01, 0x00, 0x01,
     ];
     let expected = DerObject::from_set(vec![
-        DerObject::from_int_slice(b"\x01\x00\x01"),
         DerObject::from_int_slice(b"\x01\x00\x00"),
+        DerObject::from_int_slice(b"\x01\x00\x01"),
     ]);
     assert_eq!(localparse_set(&bytes), Ok((empty, expected)));
 }
@@ -506,30 +666,33 @@ fn test_der_defined_set_macros() {
 fn test_parse_u32() {
     let empty = &b""[..];
     assert_eq!(parse_der_u32(&[0x02, 0x01, 0x01]), Ok((empty, 1)));
-    assert_eq!(parse_der_u32(&[0x02, 0x01, 0xff]), Ok((empty, 255)));
+    assert_eq!(parse_der_u32(&[0x02, 0x02, 0x00, 0xff]), Ok((empty, 255)));
     assert_eq!(parse_der_u32(&[0x02, 0x02, 0x01, 0x23]), Ok((empty, 0x123)));
     assert_eq!(
-        parse_der_u32(&[0x02, 0x02, 0xff, 0xff]),
+        parse_der_u32(&[0x02, 0x03, 0x00, 0xff, 0xff]),
         Ok((empty, 0xffff))
     );
     assert_eq!(
         parse_der_u32(&[0x02, 0x03, 0x01, 0x23, 0x45]),
         Ok((empty, 0x12345))
     );
-    assert_eq!(
-        parse_der_u32(&[0x02, 0x03, 0xff, 0xff, 0xff]),
-        Ok((empty, 0xffffff))
-    );
     assert_eq!(
         parse_der_u32(&[0x02, 0x04, 0x01, 0x23, 0x45, 0x67]),
         Ok((empty, 0x1234567))
     );
     assert_eq!(
-        parse_der_u32(&[0x02, 0x04, 0xff, 0xff, 0xff, 0xff]),
+        parse_der_u32(&[0x02, 0x05, 0x00, 0xff, 0xff, 0xff, 0xff]),
         Ok((empty, 0xffffffff))
     );
     let s = &[0x02, 0x05, 0x01, 0x23, 0x45, 0x67, 0x89];
     assert_eq!(parse_der_u32(s), Err(Err::Error(BerError::IntegerTooLarge)));
+    let s = &[0x02, 0x01, 0xff];
+    assert_eq!(parse_der_u32(s), Err(Err::Error(BerError::IntegerNegative)));
+    let s = &[0x02, 0x02, 0xff, 0xff];
+    assert_eq!(
+        parse_der_u32(s),
+        Err(Err::Error(BerError::IntegerNonMinimal))
+    );
     let s = &[0x01, 0x01, 0xff];
     assert_eq!(parse_der_u32(s), Err(Err::Error(BerError::InvalidTag)));
 }
@@ -538,32 +701,417 @@ fn test_parse_u32() {
 fn test_parse_u64() {
     let empty = &b""[..];
     assert_eq!(parse_der_u64(&[0x02, 0x01, 0x01]), Ok((empty, 1)));
-    assert_eq!(parse_der_u64(&[0x02, 0x01, 0xff]), Ok((empty, 255)));
+    assert_eq!(parse_der_u64(&[0x02, 0x02, 0x00, 0xff]), Ok((empty, 255)));
     assert_eq!(parse_der_u64(&[0x02, 0x02, 0x01, 0x23]), Ok((empty, 0x123)));
     assert_eq!(
-        parse_der_u64(&[0x02, 0x02, 0xff, 0xff]),
+        parse_der_u64(&[0x02, 0x03, 0x00, 0xff, 0xff]),
         Ok((empty, 0xffff))
     );
     assert_eq!(
         parse_der_u64(&[0x02, 0x03, 0x01, 0x23, 0x45]),
         Ok((empty, 0x12345))
     );
-    assert_eq!(
-        parse_der_u64(&[0x02, 0x03, 0xff, 0xff, 0xff]),
-        Ok((empty, 0xffffff))
-    );
     assert_eq!(
         parse_der_u64(&[0x02, 0x04, 0x01, 0x23, 0x45, 0x67]),
         Ok((empty, 0x1234567))
     );
     assert_eq!(
-        parse_der_u64(&[0x02, 0x04, 0xff, 0xff, 0xff, 0xff]),
+        parse_der_u64(&[0x02, 0x05, 0x00, 0xff, 0xff, 0xff, 0xff]),
         Ok((empty, 0xffffffff))
     );
     assert_eq!(
         parse_der_u64(&[0x02, 0x05, 0x01, 0x23, 0x45, 0x67, 0x89]),
         Ok((empty, 0x123456789))
     );
+    let s = &[0x02, 0x01, 0x80];
+    assert_eq!(parse_der_u64(s), Err(Err::Error(BerError::IntegerNegative)));
+    let s = &[0x02, 0x02, 0x00, 0x01];
+    assert_eq!(
+        parse_der_u64(s),
+        Err(Err::Error(BerError::IntegerNonMinimal))
+    );
     let s = &[0x01, 0x01, 0xff];
     assert_eq!(parse_der_u64(s), Err(Err::Error(BerError::InvalidTag)));
 }
+
+#[test]
+fn test_der_nested_constraints() {
+    // valid nested objects
+    let bytes = hex!("30 09 31 07 30 05 01 01 ff 05 00");
+    assert!(parse_der(&bytes).is_ok());
+    // invalid boolean inside a sequence
+    let bytes = hex!("30 03 01 01 01");
+    assert!(parse_ber(&bytes).is_ok());
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    assert_eq!(
+        parse_der_sequence(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // invalid boolean, nested in a set and a sequence
+    let bytes = hex!("31 05 30 03 01 01 01");
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // non-minimal length inside a sequence
+    let bytes = hex!("30 04 04 81 01 41");
+    assert!(parse_ber(&bytes).is_ok());
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // constructed octet string inside a sequence
+    let bytes = hex!("30 07 24 05 04 03 41 41 41");
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // UTCTime not in UTC inside a sequence
+    let bytes = hex!("30 13 17 11 30 32 31 32 31 33 31 34 32 39 32 33 2b 30 31 30 30");
+    assert!(parse_ber(&bytes).is_ok());
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // padding bits set in a nested bitstring
+    let bytes = hex!("30 06 03 04 06 6e 5d e0");
+    assert!(parse_ber(&bytes).is_ok());
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // truncated object inside a sequence
+    let bytes = hex!("30 03 02 03 01");
+    assert_eq!(parse_der(&bytes), Err(Err::Error(BerError::ObjectTooShort)));
+    // constructed boolean
+    let bytes = hex!("21 01 ff");
+    assert!(parse_ber(&bytes).is_ok());
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // end-of-content inside a definite length sequence
+    let bytes = hex!("30 02 00 00");
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // invalid boolean inside an explicit tagged value
+    let bytes = hex!("30 05 a0 03 01 01 01");
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // non-minimal integer inside an application tagged value
+    let bytes = hex!("61 04 02 02 00 01");
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::IntegerNonMinimal))
+    );
+    // the content of an implicit tagged value is not checked
+    let bytes = hex!("80 02 00 01");
+    assert!(parse_der(&bytes).is_ok());
+}
+
+#[test]
+fn test_der_header_constraints() {
+    // indefinite length
+    let bytes = hex!("30 80 02 01 01 00 00");
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // indefinite length, header read using BER
+    let (rem, hdr) = ber_read_element_header(&bytes).expect("parsing failed");
+    assert_eq!(hdr.len, BerSize::Indefinite);
+    assert_eq!(
+        der_read_element_content(rem, hdr),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // long form used for a length < 128
+    let bytes = hex!("04 81 7f");
+    assert_eq!(
+        der_read_element_header(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // leading zero in length octets
+    let bytes = hex!("04 82 00 80");
+    assert_eq!(
+        der_read_element_header(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // high tag number form used for a low tag number
+    let bytes = hex!("9f 02 01 00");
+    assert_eq!(
+        der_read_element_header(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    // empty sequence followed by data
+    let bytes = hex!("30 00 02 01 01");
+    assert_eq!(
+        parse_der(&bytes),
+        Ok((&bytes[2..], DerObject::from_seq(vec![])))
+    );
+}
+
+#[test]
+fn test_der_max_depth() {
+    let mut obj = DerObject::from_seq(vec![]);
+    for _ in 0..=MAX_RECURSION {
+        obj = DerObject::from_seq(vec![obj]);
+    }
+    let bytes = obj.to_der_vec().expect("serialization failed");
+    assert_eq!(parse_der(&bytes), Err(Err::Error(BerError::BerMaxDepth)));
+}
+
+#[test]
+fn test_der_max_depth_tagged() {
+    // explicit tagged values, nested deeper than the recursion limit
+    let mut bytes = vec![0x05, 0x00];
+    for _ in 0..=MAX_RECURSION {
+        let len = bytes.len();
+        let mut hdr = if len < 0x80 {
+            vec![0xa0, len as u8]
+        } else {
+            vec![0xa0, 0x81, len as u8]
+        };
+        hdr.extend(bytes);
+        bytes = hdr;
+    }
+    assert_eq!(parse_der(&bytes), Err(Err::Error(BerError::BerMaxDepth)));
+    let (_, (_, violations)) = parse_der_lenient(&bytes).expect("parsing failed");
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].rule, DerRule::ConstructedContent);
+}
+
+#[test]
+fn test_der_location() {
+    let bytes = hex!("30 0a 02 03 01 00 01 a0 03 0c 01 61 ff");
+    let (rem, obj) = parse_der(&bytes).expect("parsing failed");
+    assert_eq!(rem, &bytes[12..]);
+    assert_eq!(obj.raw(), Some(&bytes[..12]));
+    assert_eq!(obj.header_len(), Some(2));
+    assert_eq!(obj.span(), Some(0..12));
+    assert_eq!(obj[0].raw_content(), Some(&bytes[4..7]));
+    assert_eq!(obj[0].span(), Some(2..7));
+    // context-specific objects are not parsed, only their location is recorded
+    assert_eq!(obj[1].span(), Some(7..12));
+    let (_, obj) = parse_der_sequence(&bytes).expect("parsing failed");
+    assert_eq!(obj[1].offset(), Some(7));
+    let (_, obj) = parse_der_bitstring(&hex!("03 02 00 ff")).expect("parsing failed");
+    assert_eq!(obj.raw_content(), Some(&hex!("00 ff")[..]));
+    // the header is not part of the input
+    let (rem, hdr) = der_read_element_header(&bytes).expect("parsing failed");
+    let (_, obj) = der_read_element_content(rem, hdr).expect("parsing failed");
+    assert_eq!(obj.raw(), None);
+    assert_eq!(obj[0].span(), Some(0..5));
+}
+
+#[test]
+fn test_der_macros_location() {
+    fn parse_seq_of(i: &[u8]) -> IResult<&[u8], DerObject, BerError> {
+        parse_der_sequence_of!(i, parse_der_integer)
+    }
+    fn parse_set_of(i: &[u8]) -> IResult<&[u8], DerObject, BerError> {
+        parse_der_set_of!(i, parse_der_integer)
+    }
+    fn parse_seq(i: &[u8]) -> IResult<&[u8], DerObject, BerError> {
+        parse_der_sequence_defined!(i, parse_der_integer >> parse_set_of)
+    }
+    fn parse_implicit(i: &[u8]) -> IResult<&[u8], DerObject, BerError> {
+        parse_der_tagged!(i, IMPLICIT 2, BerTag::Integer)
+    }
+    let bytes = hex!("30 06 02 01 01 02 01 02");
+    let (_, obj) = parse_seq_of(&bytes).expect("parsing failed");
+    assert_eq!(obj.raw(), Some(&bytes[..]));
+    assert_eq!(obj.header_len(), Some(2));
+    assert_eq!(obj.span(), Some(0..8));
+    assert_eq!(obj[0].span(), Some(2..5));
+    assert_eq!(obj[1].span(), Some(5..8));
+    // the offsets of nested objects are relative to the input of the outer macro
+    let bytes = hex!("30 0b 02 01 01 31 06 02 01 02 02 01 03");
+    let (_, obj) = parse_seq(&bytes).expect("parsing failed");
+    assert_eq!(obj.span(), Some(0..13));
+    assert_eq!(obj[1].span(), Some(5..13));
+    assert_eq!(obj[1].header_len(), Some(2));
+    assert_eq!(obj[1][1].span(), Some(10..13));
+    assert_eq!(obj[1][1].raw(), Some(&bytes[10..]));
+    let bytes = hex!("82 01 05");
+    let (_, obj) = parse_implicit(&bytes).expect("parsing failed");
+    assert_eq!(obj.raw(), Some(&bytes[..]));
+    assert_eq!(obj.raw_content(), Some(&bytes[2..]));
+}
+
+#[test]
+fn test_der_error_context() {
+    // the boolean in the set is not a valid DER boolean
+    let bytes = hex!("30 08 02 01 01 31 03 01 01 01");
+    let e = match parse_der(&bytes) {
+        Err(Err::Error(e)) => e,
+        _ => panic!("parsing should fail"),
+    };
+    assert_eq!(e, BerError::DerConstraintFailed);
+    assert_eq!(e.offset(), Some(9));
+    let ctx = e.context().expect("no context");
+    let offsets: Vec<_> = ctx.frames.iter().map(|f| f.offset).collect();
+    assert_eq!(offsets, vec![7, 5, 0]);
+    assert_eq!(
+        e.to_string(),
+        "DER constraint failed at offset 9 (in SEQUENCE[1] > SET[0] > BOOLEAN)"
+    );
+    // the context does not depend on the location of the input in memory
+    let copy = bytes.to_vec();
+    let e2 = match parse_der(&copy) {
+        Err(Err::Error(e)) => e,
+        _ => panic!("parsing should fail"),
+    };
+    assert_eq!(e.context(), e2.context());
+    assert_eq!(format!("{:?}", e), format!("{:?}", e2));
+    // errors without context
+    assert_eq!(BerError::InvalidLength.to_string(), "invalid length");
+    assert_eq!(BerError::InvalidLength.offset(), None);
+}
+
+#[test]
+fn test_der_options() {
+    let bytes = hex!("30 0c 02 01 01 31 07 04 01 00 06 02 2a 03");
+    assert!(parse_der_with_options(&bytes, &ParserOptions::default()).is_ok());
+    let check = |o: ParserOptions, e: BerError| {
+        assert_eq!(parse_der_with_options(&bytes, &o), Err(Err::Error(e)));
+    };
+    let default = ParserOptions::default();
+    check(
+        ParserOptions {
+            max_depth: 0,
+            ..default
+        },
+        BerError::BerMaxDepth,
+    );
+    check(
+        ParserOptions {
+            max_length: 8,
+            ..default
+        },
+        BerError::MaxLengthExceeded,
+    );
+    check(
+        ParserOptions {
+            max_objects: 4,
+            ..default
+        },
+        BerError::MaxObjectsExceeded,
+    );
+    check(
+        ParserOptions {
+            max_elements: 1,
+            ..default
+        },
+        BerError::MaxElementsExceeded,
+    );
+    check(
+        ParserOptions {
+            max_oid_arcs: 2,
+            ..default
+        },
+        BerError::MaxOidArcsExceeded,
+    );
+    check(
+        ParserOptions {
+            max_string_size: 0,
+            ..default
+        },
+        BerError::MaxStringSizeExceeded,
+    );
+}
+
+#[test]
+fn test_der_lenient() {
+    let bytes = hex!(
+        "30 80 02 02 00 01 03 02 01 01 24 80 04 01 61 00 00
+         31 06 02 01 02 02 01 01 1f 02 01 05 05 00 00 00"
+    );
+    assert!(parse_der(&bytes).is_err());
+    let (rem, (obj, violations)) = parse_der_lenient(&bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(obj.as_sequence().map(|v| v.len()), Ok(6));
+    let v: Vec<_> = violations.iter().map(|v| (v.offset, v.rule)).collect();
+    assert_eq!(
+        v,
+        vec![
+            (0, DerRule::IndefiniteLength),
+            (2, DerRule::NonMinimalInteger),
+            (6, DerRule::BitStringPadding),
+            (10, DerRule::IndefiniteLength),
+            (10, DerRule::ConstructedString),
+            (17, DerRule::SetOrder),
+            (25, DerRule::NonMinimalTag),
+        ]
+    );
+    assert_eq!(violations[5].tag, BerTag::Set);
+    assert_eq!(
+        violations[1].to_string(),
+        "integer not minimally encoded (X.690 8.3.2) at offset 2"
+    );
+    // valid DER
+    let bytes = hex!("30 0c 02 01 01 31 07 04 01 00 06 02 2a 03");
+    let (_, (obj, violations)) = parse_der_lenient(&bytes).expect("parsing failed");
+    assert!(violations.is_empty());
+    assert_eq!(parse_der(&bytes), Ok((&b""[..], obj)));
+    // OID subidentifier with a leading 0x80 octet
+    let bytes = hex!("06 03 2a 80 03");
+    let (_, (_, violations)) = parse_der_lenient(&bytes).expect("parsing failed");
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].rule, DerRule::NonMinimalOid);
+    // the report is empty if and only if the object is accepted by parse_der
+    let check = |bytes: &[u8], expected: &[(usize, DerRule)]| {
+        let (_, (_, violations)) = parse_der_lenient(bytes).expect("parsing failed");
+        let v: Vec<_> = violations.iter().map(|v| (v.offset, v.rule)).collect();
+        assert_eq!(v, expected);
+        assert_eq!(parse_der(bytes).is_ok(), expected.is_empty());
+    };
+    check(&hex!("03 02 09 00"), &[(0, DerRule::BitStringUnusedBits)]);
+    check(&hex!("02 00"), &[(0, DerRule::EmptyInteger)]);
+    check(&hex!("21 01 ff"), &[(0, DerRule::ConstructedPrimitive)]);
+    check(&hex!("30 02 00 00"), &[(2, DerRule::EndOfContent)]);
+    check(&hex!("30 05 a0 03 01 01 01"), &[(4, DerRule::BooleanValue)]);
+    check(
+        &hex!("a0 04 02 02 00 01"),
+        &[(2, DerRule::NonMinimalInteger)],
+    );
+    check(&hex!("a0 02 ff ff"), &[(0, DerRule::ConstructedContent)]);
+    check(&hex!("30 07 a0 05 a1 03 01 01 ff"), &[]);
+    check(&hex!("80 02 00 01"), &[]);
+    // time types
+    check(
+        &hex!("1f 1f 08 32 30 32 30 30 32 32 38"),
+        &[(0, DerRule::DateFormat)],
+    );
+    check(&hex!("1f 1f 0a 32 30 32 30 2d 30 32 2d 32 38"), &[]);
+    check(
+        &hex!("1f 20 05 31 32 3a 30 30"),
+        &[(0, DerRule::TimeOfDayFormat)],
+    );
+    check(&hex!("1f 20 08 31 32 3a 30 30 3a 30 30"), &[]);
+    check(
+        &hex!("1f 21 10 32 30 32 30 2d 30 32 2d 32 38 54 31 32 3a 30 30"),
+        &[(0, DerRule::DateTimeFormat)],
+    );
+    check(
+        &hex!("1f 21 13 32 30 32 30 2d 30 32 2d 32 38 54 31 32 3a 30 30 3a 30 30"),
+        &[],
+    );
+    check(&hex!("1f 22 02 50 31"), &[(0, DerRule::DurationFormat)]);
+    check(
+        &hex!("1f 22 0e 50 31 59 32 4d 31 30 44 54 32 48 33 30 4d"),
+        &[],
+    );
+    // constructed strings
+    for tag in &[0x27, 0x35, 0x39, 0x3a] {
+        let mut bytes = vec![*tag];
+        bytes.extend_from_slice(&hex!("05 04 03 41 41 41"));
+        check(&bytes, &[(0, DerRule::ConstructedString)]);
+    }
+}
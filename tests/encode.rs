@@ -0,0 +1,149 @@
+#[macro_use]
+extern crate pretty_assertions;
+
+#[macro_use]
+extern crate hex_literal;
+extern crate der_parser;
+extern crate nom;
+
+use der_parser::ber::*;
+use der_parser::der::*;
+use der_parser::error::SerializeError;
+use der_parser::oid::Oid;
+
+fn assert_roundtrip(bytes: &[u8]) {
+    let (rem, obj) = parse_der(bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    let encoded = obj.to_der_vec().expect("serialization failed");
+    assert_eq!(&encoded[..], bytes);
+    assert_eq!(parse_der(&encoded), Ok((&b""[..], obj)));
+}
+
+#[test]
+fn test_encode_primitive() {
+    assert_roundtrip(&hex!("01 01 ff"));
+    assert_roundtrip(&hex!("02 03 01 00 01"));
+    assert_roundtrip(&hex!("03 04 06 6e 5d c0"));
+    assert_roundtrip(&hex!("04 05 41 41 41 41 41"));
+    assert_roundtrip(&hex!("05 00"));
+    assert_roundtrip(&hex!("06 09 2a 86 48 86 f7 0d 01 01 05"));
+    assert_roundtrip(&hex!("09 00"));
+    assert_roundtrip(&hex!("09 03 80 fb 05"));
+    assert_roundtrip(&hex!("09 01 43"));
+    assert_roundtrip(&hex!("09 07 03 2d 31 2e 45 2b 30"));
+    assert_roundtrip(&hex!("0a 01 02"));
+    assert_roundtrip(&hex!("0c 0a 53 6f 6d 65 2d 53 74 61 74 65"));
+    assert_roundtrip(&hex!("0d 04 c2 7b 03 02"));
+    assert_roundtrip(&hex!("17 0d 30 32 31 32 31 33 31 34 32 39 32 33 5a"));
+    assert_roundtrip(&hex!("1e 08 00 55 00 73 00 65 00 72"));
+}
+
+#[test]
+fn test_encode_constructed() {
+    // Name (RDNSequence)
+    assert_roundtrip(&hex!(
+        "30 45 31 0b 30 09 06 03 55 04 06 13 02 46 52
+         31 13 30 11 06 03 55 04 08 0c 0a 53 6f 6d 65 2d 53 74 61 74 65
+         31 21 30 1f 06 03 55 04 0a 0c 18 49 6e 74 65 72 6e 65 74 20 57
+         69 64 67 69 74 73 20 50 74 79 20 4c 74 64"
+    ));
+    // context-specific and application values
+    assert_roundtrip(&hex!("30 07 a0 03 02 01 02 81 00"));
+    assert_roundtrip(&hex!("5f 52 04 70 61 73 73"));
+}
+
+#[test]
+fn test_encode_long_length() {
+    let data = vec![0x41; 300];
+    let obj = BerObject::from_obj(BerObjectContent::OctetString(&data));
+    let encoded = obj.to_der_vec().expect("serialization failed");
+    assert_eq!(&encoded[..4], &hex!("04 82 01 2c"));
+    assert_roundtrip(&encoded);
+}
+
+#[test]
+fn test_encode_explicit() {
+    let obj = BerObject::from_seq(vec![
+        BerObject {
+            class: 2,
+            structured: 1,
+            tag: BerTag(0),
+            content: BerObjectContent::ContextSpecific(
+                BerTag(0),
+                Some(Box::new(BerObject::from_int_slice(b"\x02"))),
+            ),
+            location: None,
+        },
+        BerObject::from_obj(BerObjectContent::ContextSpecific(BerTag(1), None)),
+        BerObject::from_obj(BerObjectContent::OID(Oid::from(&[2, 5, 4, 3]).unwrap())),
+    ]);
+    assert_eq!(
+        obj.to_der_vec(),
+        Ok(hex!("30 0a a0 03 02 01 02 06 03 55 04 03").to_vec())
+    );
+}
+
+#[test]
+fn test_encode_segmented() {
+    // constructed BER strings are encoded using the primitive form
+    let bytes = hex!("23 80 03 03 00 0a 3b 03 05 04 5f 29 1c d0 00 00");
+    let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+    assert_eq!(
+        obj.to_der_vec(),
+        Ok(hex!("03 07 04 0a 3b 5f 29 1c d0").to_vec())
+    );
+    let bytes = hex!("24 08 04 02 41 42 04 02 43 44");
+    let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+    assert_eq!(obj.to_der_vec(), Ok(hex!("04 04 41 42 43 44").to_vec()));
+}
+
+#[test]
+fn test_encode_writer() {
+    let obj = BerObject::from_obj(BerObjectContent::Null);
+    let mut v = vec![0xff];
+    assert_eq!(obj.write_der(&mut v), Ok(2));
+    assert_eq!(v, vec![0xff, 0x05, 0x00]);
+    let mut buf = [0u8; 1];
+    assert_eq!(
+        obj.write_der(&mut &mut buf[..]),
+        Err(SerializeError::IOError(std::io::ErrorKind::WriteZero))
+    );
+}
+
+#[test]
+fn test_encode_invalid() {
+    let obj = BerObject {
+        class: 4,
+        structured: 0,
+        tag: BerTag::Null,
+        content: BerObjectContent::Null,
+        location: None,
+    };
+    assert_eq!(obj.to_der_vec(), Err(SerializeError::InvalidClass));
+}
+
+#[test]
+fn test_encode_canonicalize_sets() {
+    // SET OF: sorted by encoding, the shorter encoding being padded with zeroes
+    let bytes = hex!("31 0b 04 02 41 42 04 01 41 04 02 41 00");
+    assert!(parse_der(&bytes).is_err());
+    let (_, mut obj) = parse_ber(&bytes).expect("parsing failed");
+    obj.canonicalize_sets().expect("canonicalization failed");
+    let encoded = obj.to_der_vec().expect("serialization failed");
+    assert_eq!(
+        encoded,
+        hex!("31 0b 04 01 41 04 02 41 00 04 02 41 42").to_vec()
+    );
+    assert_roundtrip(&encoded);
+    // SET, inside a sequence: sorted by encoding too
+    let bytes = hex!("30 0c 31 0a 86 02 41 42 a5 02 05 00 30 00");
+    assert!(parse_der(&bytes).is_err());
+    let (_, mut obj) = parse_ber(&bytes).expect("parsing failed");
+    obj.canonicalize_sets().expect("canonicalization failed");
+    let encoded = obj.to_der_vec().expect("serialization failed");
+    assert_eq!(
+        encoded,
+        hex!("30 0c 31 0a 30 00 86 02 41 42 a5 02 05 00").to_vec()
+    );
+    assert!(parse_der(&encoded).is_ok());
+}
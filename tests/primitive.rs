@@ -9,8 +9,10 @@ extern crate nom;
 use der_parser::ber::*;
 use der_parser::der::*;
 use der_parser::error::*;
+use der_parser::oid;
 use der_parser::oid::Oid;
 use nom::{Err, Needed};
+use std::borrow::Cow;
 
 #[test]
 fn test_flat_take() {
@@ -24,11 +26,11 @@ fn test_flat_take() {
         Ok((empty, BerObject::from_obj(BerObjectContent::Boolean(false))))
     );
     assert_eq!(
-        ber_read_element_content_as(&[0xff], BerTag::Boolean, 0x01, false, 0),
+        ber_read_element_content_as(&[0xff], BerTag::Boolean, BerSize::Definite(1), false, 0),
         Ok((empty, BerObjectContent::Boolean(true)))
     );
     assert_eq!(
-        ber_read_element_content_as(&[0x00], BerTag::Boolean, 0x01, false, 0),
+        ber_read_element_content_as(&[0x00], BerTag::Boolean, BerSize::Definite(1), false, 0),
         Ok((empty, BerObjectContent::Boolean(false)))
     );
 }
@@ -40,7 +42,9 @@ fn test_oid() {
         parse_der(&[0x06, 0x06, 42, 129, 122, 1, 16, 9]),
         Ok((
             empty,
-            BerObject::from_obj(BerObjectContent::OID(Oid::from(&[1, 2, 250, 1, 16, 9])))
+            BerObject::from_obj(BerObjectContent::OID(
+                Oid::from(&[1, 2, 250, 1, 16, 9]).unwrap()
+            ))
         ))
     );
     // Dubuisson 433
@@ -48,9 +52,53 @@ fn test_oid() {
         parse_der(&[0x06, 0x05, 129, 122, 1, 16, 9]),
         Ok((
             empty,
-            BerObject::from_obj(BerObjectContent::OID(Oid::from(&[250, 1, 16, 9])))
+            BerObject::from_obj(BerObjectContent::OID(
+                Oid::from(&[2, 170, 1, 16, 9]).unwrap()
+            ))
         ))
     );
+    // subidentifier not minimally encoded (leading 0x80 octet)
+    let bytes = [0x06, 0x04, 0x2a, 0x80, 0x86, 0x48];
+    assert_eq!(
+        parse_der(&bytes),
+        Err(Err::Error(BerError::DerConstraintFailed))
+    );
+    let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+    assert_eq!(
+        obj.as_oid().map(|oid| oid.to_string()),
+        Ok("1.2.840".to_string())
+    );
+    assert_eq!(obj.as_oid().map(|oid| oid.bytes()), Ok(&bytes[2..]));
+    // UUID-based OID (X.667), with an arc larger than 64 bits
+    let bytes = hex!("06 14 69 83 f0 9d a7 eb cf de e0 c7 a1 a7 b2 c0 94 8c c8 f9 d7 76");
+    let (_, obj) = parse_der(&bytes).expect("parsing failed");
+    let oid = obj.as_oid().expect("not an OID");
+    assert_eq!(
+        oid.to_string(),
+        "2.25.329800735698586629295641978511506172918"
+    );
+    assert!(oid.iter().is_none());
+}
+
+#[test]
+fn test_oid_macro() {
+    #[rustfmt::skip]
+    const OID_RSA: Oid<'static> = oid!(1.2.840.113549.1.1.1);
+    let bytes = hex!("06 09 2a 86 48 86 f7 0d 01 01 01");
+    let (_, obj) = parse_der_oid(&bytes).expect("parsing failed");
+    let oid = obj.as_oid().expect("not an OID");
+    assert_eq!(oid, &OID_RSA);
+    assert_eq!(oid.bytes(), &oid!(raw 1.2.840.113549.1.1.1));
+    let name = match oid.bytes() {
+        &oid!(raw 1.2.840.113549.1.1.1) => "rsaEncryption",
+        _ => "unknown",
+    };
+    assert_eq!(name, "rsaEncryption");
+    assert_eq!(oid!(2.999.3), Oid::from(&[2, 999, 3]).unwrap());
+    assert_eq!(
+        oid!(rel 8571.3.2),
+        Oid::from_relative(&[8571, 3, 2]).unwrap()
+    );
 }
 
 #[test]
@@ -60,25 +108,258 @@ fn test_rel_oid() {
         parse_der(&[0x0d, 0x04, 0xc2, 0x7b, 0x03, 0x02]),
         Ok((
             empty,
-            BerObject::from_obj(BerObjectContent::RelativeOID(Oid::from(&[8571, 3, 2])))
+            BerObject::from_obj(BerObjectContent::RelativeOID(
+                Oid::from_relative(&[8571, 3, 2]).unwrap()
+            ))
+        ))
+    );
+}
+
+#[test]
+fn test_oid_iri() {
+    let empty = &b""[..];
+    let bytes = b"\x1f\x23\x14/ISO/Member-Body/250";
+    assert_eq!(
+        parse_der_oid_iri(bytes),
+        Ok((
+            empty,
+            BerObject::from_obj(BerObjectContent::OidIri("/ISO/Member-Body/250"))
         ))
     );
+    let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    assert_eq!(obj.as_str(), Ok("/ISO/Member-Body/250"));
+    assert_eq!(
+        format!("{}", obj.as_pretty(0, 2)),
+        "    0:d=0  hl=3 l=  20 prim: OID-IRI           :'/ISO/Member-Body/250' (1.2.250)\n"
+    );
+    assert_eq!(obj.to_der_vec().as_ref().map(|v| &v[..]), Ok(&bytes[..]));
+    assert_eq!(
+        parse_ber_relative_oid_iri(b"\x1f\x24\x0bExample/1/a"),
+        Ok((
+            empty,
+            BerObject::from_obj(BerObjectContent::RelativeOidIri("Example/1/a"))
+        ))
+    );
+    // invalid syntax: relative, empty label, leading zero
+    for bytes in &[
+        &b"\x1f\x23\x03ISO"[..],
+        &b"\x1f\x23\x05/ISO/"[..],
+        &b"\x1f\x24\x06ISO/02"[..],
+    ] {
+        assert_eq!(
+            parse_ber(bytes).err(),
+            Some(Err::Error(BerError::BerValueError))
+        );
+    }
+    assert!(parse_ber(b"\x1f\x23\x02/\xff").is_err());
+    assert!(parse_ber(b"\x3f\x23\x04\x04\x02/1").is_err());
+}
+
+#[test]
+fn test_string_types() {
+    let empty = &b""[..];
+    // VisibleString, as used in the explicitText of X.509 policy qualifiers
+    let bytes = b"\x1a\x05a b~!";
+    assert_eq!(
+        parse_der_visiblestring(bytes),
+        Ok((
+            empty,
+            BerObject::from_obj(BerObjectContent::VisibleString(b"a b~!"))
+        ))
+    );
+    let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    assert_eq!(obj.as_str(), Ok("a b~!"));
+    assert_eq!(obj.to_der_vec().as_ref().map(|v| &v[..]), Ok(&bytes[..]));
+    let (_, obj) = parse_ber_visiblestring(b"\x1a\x02a\n").expect("parsing failed");
+    assert_eq!(obj.as_str(), Err(BerError::StringInvalidCharset));
+    // constructed form (BER only)
+    let bytes = b"\x3a\x07\x04\x02ab\x04\x01c";
+    let (_, obj) = parse_ber_visiblestring(bytes).expect("parsing failed");
+    assert_eq!(obj.as_str(), Ok("abc"));
+    assert_eq!(
+        parse_der_visiblestring(bytes).err(),
+        Some(Err::Error(BerError::DerConstraintFailed))
+    );
+    let (_, obj) = parse_der_graphicstring(b"\x19\x03\xc3\xa9!").expect("parsing failed");
+    assert_eq!(obj.as_str(), Ok("\u{e9}!"));
+    let (_, obj) = parse_der_videotexstring(b"\x15\x02ab").expect("parsing failed");
+    assert_eq!(obj.content, BerObjectContent::VideotexString(b"ab"));
+    let (_, obj) = parse_der_objectdescriptor(b"\x07\x04desc").expect("parsing failed");
+    assert_eq!(obj.content, BerObjectContent::ObjDescriptor(b"desc"));
+    assert_eq!(obj.as_str(), Ok("desc"));
+}
+
+#[test]
+fn test_time_types() {
+    let empty = &b""[..];
+    let bytes = b"\x1f\x1f\x0a2020-02-29";
+    assert_eq!(
+        parse_der_date(bytes),
+        Ok((
+            empty,
+            BerObject::from_obj(BerObjectContent::Date(b"2020-02-29"))
+        ))
+    );
+    let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    let t = obj.as_time().expect("invalid date");
+    assert_eq!((t.year, t.month, t.day), (2020, 2, 29));
+    assert_eq!(obj.as_str(), Ok("2020-02-29"));
+    assert_eq!(
+        format!("{}", obj.as_pretty(0, 2)),
+        "    0:d=0  hl=3 l=  10 prim: DATE              :'2020-02-29'\n"
+    );
+    assert_eq!(obj.to_der_vec().as_ref().map(|v| &v[..]), Ok(&bytes[..]));
+    let (_, obj) = parse_der_datetime(b"\x1f\x21\x131985-11-06T21:06:27").expect("parsing failed");
+    let t = obj.as_time().expect("invalid date");
+    assert_eq!((t.year, t.hour, t.minute, t.second), (1985, 21, 6, 27));
+    let (_, obj) = parse_der_timeofday(b"\x1f\x20\x0812:30:00").expect("parsing failed");
+    assert_eq!(obj.as_str(), Ok("12:30:00"));
+    let (_, obj) = parse_der_duration(b"\x1f\x22\x0eP1Y2M10DT2H30M").expect("parsing failed");
+    assert_eq!(obj.as_str(), Ok("P1Y2M10DT2H30M"));
+    let (_, obj) = parse_der_time(b"\x0e\x0e2020-02-29T12Z").expect("parsing failed");
+    assert_eq!(obj.content, BerObjectContent::Time(b"2020-02-29T12Z"));
+    // the values are only checked by the DER parser
+    for bytes in &[
+        &b"\x1f\x1f\x0a2021-02-29"[..],
+        &b"\x1f\x20\x0824:00:00"[..],
+        &b"\x1f\x21\x0a1985-11-06"[..],
+        &b"\x1f\x22\x02P1"[..],
+    ] {
+        assert!(parse_ber(bytes).is_ok());
+        assert_eq!(
+            parse_der(bytes).err(),
+            Some(Err::Error(BerError::DerConstraintFailed))
+        );
+    }
+    assert_eq!(
+        parse_ber(b"\x3f\x1f\x04\x04\x022020").err(),
+        Some(Err::Error(BerError::ConstructUnexpected))
+    );
+}
+
+#[test]
+fn test_external_types() {
+    let empty = &b""[..];
+    // EXTERNAL, with direct-reference and single-ASN1-type encoding
+    let bytes = hex!("28 09 06 02 2a 03 a0 03 02 01 05");
+    let (rem, obj) = parse_der_external(&bytes).expect("parsing failed");
+    assert_eq!(rem, empty);
+    assert_eq!(obj.tag, BerTag::External);
+    let components = obj.as_sequence().expect("not a sequence");
+    assert_eq!(components.len(), 2);
+    assert_eq!(components[0].as_oid(), Ok(&oid!(1.2.3)));
+    assert_eq!(components[1].span(), Some(6..11));
+    assert_eq!(obj.to_der_vec().as_ref().map(|v| &v[..]), Ok(&bytes[..]));
+    assert!(
+        format!("{}", obj.as_pretty(0, 2)).starts_with("    0:d=0  hl=2 l=   9 cons: EXTERNAL\n")
+    );
+    assert_eq!(
+        parse_ber(&hex!("08 00")).err(),
+        Some(Err::Error(BerError::ConstructExpected))
+    );
+    let (_, obj) = parse_ber_embedded_pdv(&hex!("2b 04 a0 02 81 00")).expect("parsing failed");
+    assert_eq!(obj.as_sequence().map(|v| v.len()), Ok(1));
+    let (_, obj) =
+        parse_ber_characterstring(&hex!("3d 80 a0 02 81 00 00 00")).expect("parsing failed");
+    assert_eq!(obj.as_sequence().map(|v| v.len()), Ok(1));
+    assert!(parse_der_characterstring(&hex!("3d 80 a0 02 81 00 00 00")).is_err());
+}
+
+#[test]
+fn test_external_values() {
+    // X.208 form: indirect-reference, data-value-descriptor and octet-aligned
+    let (_, obj) =
+        parse_der(&hex!("28 0c 02 01 01 07 03 61 62 63 81 02 01 02")).expect("parsing failed");
+    let ext = obj.as_external().expect("invalid EXTERNAL");
+    assert_eq!(ext.identification, Identification::PresentationContextId(1));
+    assert_eq!(ext.data_value_descriptor.as_deref(), Some("abc"));
+    assert_eq!(
+        ext.data_value,
+        ExternalData::OctetAligned(Cow::Borrowed(&[1, 2][..]))
+    );
+    // X.208 form: both references, and arbitrary
+    let (_, obj) =
+        parse_ber(&hex!("28 0b 06 02 2a 03 02 01 03 82 02 07 80")).expect("parsing failed");
+    let ext = obj.as_external().expect("invalid EXTERNAL");
+    assert_eq!(
+        ext.identification,
+        Identification::ContextNegotiation {
+            presentation_context_id: 3,
+            transfer_syntax: oid!(1.2.3),
+        }
+    );
+    assert_eq!(
+        ext.data_value,
+        ExternalData::Arbitrary(7, Cow::Borrowed(&[0x80][..]))
+    );
+    // X.680 form
+    let (_, obj) =
+        parse_ber(&hex!("28 0d a0 04 81 02 2a 03 81 01 78 82 02 01 02")).expect("parsing failed");
+    let ext = obj.as_external().expect("invalid EXTERNAL");
+    assert_eq!(ext.identification, Identification::Syntax(oid!(1.2.3)));
+    assert_eq!(ext.data_value_descriptor.as_deref(), Some("x"));
+    assert_eq!(
+        ext.data_value,
+        ExternalData::OctetAligned(Cow::Borrowed(&[1, 2][..]))
+    );
+    // no reference, and trailing component
+    for bytes in &[
+        &hex!("28 05 a0 03 02 01 05")[..],
+        &hex!("28 0b 06 02 2a 03 a0 03 02 01 05 81 00")[..],
+    ] {
+        let (_, obj) = parse_ber(bytes).expect("parsing failed");
+        assert!(obj.as_external().is_err());
+    }
+    let (_, obj) = parse_ber(&hex!("30 00")).expect("parsing failed");
+    assert_eq!(obj.as_external(), Err(BerError::BerTypeError));
+}
+
+#[test]
+fn test_embedded_pdv_values() {
+    let bytes = hex!("2b 10 a0 0a a0 08 80 02 2a 03 81 02 2a 04 82 02 01 02");
+    let (_, obj) = parse_der(&bytes).expect("parsing failed");
+    let pdv = obj.as_embedded_pdv().expect("invalid EMBEDDED PDV");
+    assert_eq!(
+        pdv.identification,
+        Identification::Syntaxes {
+            abstract_syntax: oid!(1.2.3),
+            transfer_syntax: oid!(1.2.4),
+        }
+    );
+    assert_eq!(pdv.data_value, Cow::Borrowed(&[1, 2][..]));
+    // the data-value-descriptor must be absent
+    let (_, obj) =
+        parse_ber(&hex!("2b 0b a0 02 85 00 81 01 78 82 02 01 02")).expect("parsing failed");
+    assert_eq!(obj.as_embedded_pdv(), Err(BerError::BerValueError));
+}
+
+#[test]
+fn test_character_string_values() {
+    let (_, obj) = parse_der(&hex!("3d 08 a0 02 85 00 82 02 68 69")).expect("parsing failed");
+    let cs = obj.as_character_string().expect("invalid CHARACTER STRING");
+    assert_eq!(cs.identification, Identification::Fixed);
+    assert_eq!(cs.string_value, Cow::Borrowed(&b"hi"[..]));
+    // indefinite length, and string value using the constructed form
+    let bytes = hex!("3d 80 a0 02 85 00 a2 80 04 01 68 04 01 69 00 00 00 00");
+    let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+    let cs = obj.as_character_string().expect("invalid CHARACTER STRING");
+    assert_eq!(cs.string_value, Cow::<[u8]>::Owned(b"hi".to_vec()));
 }
 
 #[test]
 fn test_unknown_tag() {
-    let bytes = hex!("1d 01 00");
+    let bytes = hex!("0f 01 00");
     let res = parse_ber(&bytes).expect("parsing failed");
     assert!(res.0.is_empty());
     assert_eq!(
         res.1,
-        BerObject::from_obj(BerObjectContent::Unknown(BerTag(0x1d), &bytes[2..]))
+        BerObject::from_obj(BerObjectContent::Unknown(BerTag(0x0f), &bytes[2..]))
     );
     let res = parse_der(&bytes).expect("parsing failed");
     assert!(res.0.is_empty());
     assert_eq!(
         res.1,
-        BerObject::from_obj(BerObjectContent::Unknown(BerTag(0x1d), &bytes[2..]))
+        BerObject::from_obj(BerObjectContent::Unknown(BerTag(0x0f), &bytes[2..]))
     );
 }
 
@@ -93,7 +374,8 @@ fn test_unknown_context_specific() {
             class: 2,
             structured: 0,
             tag: BerTag(0),
-            content: BerObjectContent::Unknown(BerTag(0x0), &bytes[2..])
+            content: BerObjectContent::Unknown(BerTag(0x0), &bytes[2..]),
+            location: None,
         }
     );
 }
@@ -109,7 +391,8 @@ fn test_unknown_long_tag() {
             class: 2,
             structured: 0,
             tag: BerTag(0x22),
-            content: BerObjectContent::Unknown(BerTag(0x22), &bytes[3..])
+            content: BerObjectContent::Unknown(BerTag(0x22), &bytes[3..]),
+            location: None,
         }
     );
 }
@@ -125,7 +408,8 @@ fn test_unknown_longer_tag() {
             class: 2,
             structured: 0,
             tag: BerTag(0x1122),
-            content: BerObjectContent::Unknown(BerTag(0x1122), &bytes[4..])
+            content: BerObjectContent::Unknown(BerTag(0x1122), &bytes[4..]),
+            location: None,
         }
     );
 }
@@ -194,7 +478,7 @@ fn test_invalid_param() {
         class: 8,
         structured: 0,
         tag: BerTag(2),
-        len: 1,
+        len: BerSize::Definite(1),
     };
     der_read_element_content(&bytes, hdr)
         .err()